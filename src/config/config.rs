@@ -1,24 +1,46 @@
 
 
+use serde::Deserialize;
+
 pub const duet_ip: &str = "192.168.10.2";
+// Free rr_gcode buffer space (bytes) required before the print engine streams the next move
+pub const DUET_MIN_BUFFER_FREE: u32 = 100;
 
 pub const MICROWAVE_SERIAL_PORT: &str = "/dev/ttyUSB0";
 pub const MICROWAVE_BAUD_RATE: u32 = 9600;
 pub const MICROCIRCUITS_VENDOR_ID: u16 = 0xFFFF;
 pub const MICROCIRCUITS_PRODUCT_ID: u16 = 0xFFFF;
+pub const MICROWAVE_TELEMETRY_POLL_MS: u64 = 500;
+// Frequency band of the connected generator model (915 MHz ISM)
+pub const MICROWAVE_BAND_MHZ: [f64; 2] = [902.0, 928.0];
+// Default power slew rate; power changes are ramped, never stepped
+pub const MICROWAVE_POWER_RAMP_W_PER_S: f32 = 20.0;
 
 pub const ARDUINO_SERIAL_PORT: &str = "COM5"; // TODO: Configure for your hardware
 pub const ARDUINO_BAUD_RATE: u32 = 115200;
+// Pneumatic controller vents if no heartbeat arrives for this long (0 disables the watchdog)
+pub const ARDUINO_WATCHDOG_MS: u32 = 1000;
+// Rate firmware that supports it pushes telemetry at (0 keeps the 10 Hz READ poll)
+pub const ARDUINO_STREAM_HZ: u32 = 50;
+// Pressure channels of the pneumatic controller, by firmware channel number
+pub const ARDUINO_CHANNELS: &[&str] = &["material"];
+// USB vendor IDs used to auto-select the Arduino: Arduino LLC, Arduino SRL, WCH (CH340 clones)
+pub const ARDUINO_VENDOR_IDS: &[u16] = &[0x2341, 0x2A03, 0x1A86];
+
+// Defaults for the runtime config file (see config/file.rs); override them there
+// instead of editing these constants.
 
 // Helper function to create TargetProperties for MiniCircuit driver
-pub fn build_target_properties() -> minicircuit_commands::properties::TargetProperties {
+pub fn build_target_properties(
+    microwave: &crate::config::file::MicrowaveConfig,
+) -> minicircuit_commands::properties::TargetProperties {
     use minicircuit_commands::properties::{VendorId, ProductId};
     use minicircuit_commands::prelude::BaudRate;
     minicircuit_commands::properties::TargetProperties {
-        vendor_id: VendorId { vendor_id: MICROCIRCUITS_VENDOR_ID },
-        product_id: ProductId { product_id: MICROCIRCUITS_PRODUCT_ID },
-        port: Some(MICROWAVE_SERIAL_PORT.to_string()),
-        baud_rate: BaudRate { baud_rate: MICROWAVE_BAUD_RATE },
+        vendor_id: VendorId { vendor_id: microwave.vendor_id },
+        product_id: ProductId { product_id: microwave.product_id },
+        port: Some(microwave.serial_port.clone()),
+        baud_rate: BaudRate { baud_rate: microwave.baud_rate },
         ..Default::default()
     }
 }
@@ -35,20 +57,136 @@ pub enum DuetCommand {
 
 #[derive(Clone, Debug)]
 pub enum MicrowaveCommand {
-    Connect,
+    Connect(String),  // serial port chosen in the Connections panel
     Disconnect,
     RfOn,
     RfOff,
-    SetPowerWatts(f32),
+    // Target power, slewed by the driver; None ramps at the configured default rate
+    SetPowerWatts { watts: f32, ramp_w_per_s: Option<f32> },
     SetFrequencyHz(i32),
+    // Step across [start_hz, stop_hz] at power_w, then park at the best match
+    Sweep { start_hz: i32, stop_hz: i32, step_hz: i32, power_w: f32 },
+    // Closed-loop resonance tracking while RF is on; None switches it off
+    SetTracking(Option<TrackingSettings>),
+    // Pulsed (PWM) RF; None returns to continuous wave
+    SetPulse(Option<PulseSettings>),
+    // Reset the generator's latched error flags
+    ClearErrors,
+}
+
+// Fault reported by the generator, decoded from its status word
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeneratorFault {
+    PaFault,                             // PA or RF chain failure
+    OverTemperature { shutdown: bool },  // PA temperature high; shutdown: RF was cut
+    ReflectedPower { shutdown: bool },   // reflected power high; shutdown: protection cut RF
+    InterlockOpen,                       // external shutdown input open
+    Other(u32),                          // status bit without a specific meaning here
+}
+
+impl GeneratorFault {
+    /// Whether the generator has switched RF off because of this fault.
+    pub fn shuts_down_rf(&self) -> bool {
+        match self {
+            GeneratorFault::PaFault | GeneratorFault::InterlockOpen => true,
+            GeneratorFault::OverTemperature { shutdown } | GeneratorFault::ReflectedPower { shutdown } => *shutdown,
+            GeneratorFault::Other(_) => false,
+        }
+    }
+
+    /// Operator-facing description.
+    pub fn description(&self) -> String {
+        match self {
+            GeneratorFault::PaFault => "PA fault, RF disabled".into(),
+            GeneratorFault::OverTemperature { shutdown: false } => "PA temperature high".into(),
+            GeneratorFault::OverTemperature { shutdown: true } => "PA over-temperature, RF shut down".into(),
+            GeneratorFault::ReflectedPower { shutdown: false } => "Reflected power high".into(),
+            GeneratorFault::ReflectedPower { shutdown: true } => "Reflected-power protection, RF shut down".into(),
+            GeneratorFault::InterlockOpen => "External interlock open, RF disabled".into(),
+            GeneratorFault::Other(bits) => format!("Generator status 0x{:X}", bits),
+        }
+    }
+}
+
+// Pulse period limits supported by the generator's PWM (20 kHz .. 1 Hz)
+pub const PULSE_PERIOD_MIN_US: u32 = 50;
+pub const PULSE_PERIOD_MAX_US: u32 = 1_000_000;
+
+// Pulsed RF parameters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PulseSettings {
+    pub period_us: u32,     // pulse period (1 / PWM frequency)
+    pub duty_pct: f32,      // share of each period with RF on, 0 < duty < 100
+}
+
+impl Default for PulseSettings {
+    fn default() -> Self {
+        Self { period_us: 1_000, duty_pct: 50.0 }
+    }
+}
+
+impl PulseSettings {
+    pub fn frequency_hz(&self) -> u32 {
+        1_000_000 / self.period_us.max(1)
+    }
+
+    /// Problems with these settings, empty when the generator can run them.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if !(PULSE_PERIOD_MIN_US..=PULSE_PERIOD_MAX_US).contains(&self.period_us) {
+            errors.push(format!(
+                "pulse period must be {}..{} µs, got {}",
+                PULSE_PERIOD_MIN_US, PULSE_PERIOD_MAX_US, self.period_us
+            ));
+        }
+        if !(self.duty_pct > 0.0 && self.duty_pct < 100.0) {
+            errors.push(format!("duty cycle must be between 0 and 100 %, got {}", self.duty_pct));
+        }
+        errors
+    }
+}
+
+// Resonance tracking loop parameters (also the `microwave.tracking` config section)
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrackingSettings {
+    pub step_hz: i32,       // dither step per update
+    pub interval_ms: u64,   // time between updates (lets the load settle)
+    pub span_hz: i32,       // max deviation from the frequency tracking started at
+}
+
+impl Default for TrackingSettings {
+    fn default() -> Self {
+        Self {
+            step_hz: 100_000,
+            interval_ms: 500,
+            span_hz: 5_000_000,
+        }
+    }
+}
+
+// Tracking loop status reported by the microwave driver
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrackingStatus {
+    pub enabled: bool,                 // requested by the operator
+    pub active: bool,                  // enabled and RF on, loop is stepping
+    pub frequency_hz: Option<i32>,     // frequency the loop last commanded
+    pub bounds_hz: Option<(i32, i32)>,
+    pub reflection_ratio: Option<f32>, // reflected / forward at the last update
+    pub at_limit: bool,                // pinned against a bound
 }
 
 #[derive(Clone, Debug)]
 pub enum ArduinoCommand {
-    Connect,
+    Connect(String),  // serial port chosen in the Connections panel
     Disconnect,
-    Enable(bool),
-    SetPressureSetpoint(f32),
+    // Per pressure channel, by index into `ArduinoState::channels`
+    Enable(usize, bool),
+    SetPressureSetpoint(usize, f32),
+    SetRampRate(usize, f32),   // psi/s the regulator slews setpoint changes at; 0 steps at once
+    GetPid(usize),
+    SetPid(usize, PidGains),
+    Autotune(usize, f32),      // step-response autotune with a setpoint step of this many psi
 }
 
 // AppUI is defined in ui/app.rs; config only holds configuration and shared data types.
@@ -62,6 +200,8 @@ pub struct DuetState {
     pub status: Option<String>,
     pub last_command: Option<String>,
     pub position: [f32; 3],
+    pub gcode_in_flight: usize,     // commands enqueued but not yet acknowledged by the Duet
+    pub buffer_free: Option<u32>,   // free G-code buffer space from the last rr_gcode "buff" reply
 }
 
 #[derive(Clone, Default, Debug)]
@@ -70,20 +210,133 @@ pub struct MicrowaveState {
     pub enabled: bool,
     pub last_error: Option<String>,
     pub status: Option<String>,
-    pub power_watts: f32,
-    pub vswr: Option<f32>,
-    pub forward_ratio: Option<f32>,
-    pub temperature_c: Option<f32>,
+    pub frequency_hz: Option<i32>,      // last frequency sent to the generator
+    pub frequency_readback_hz: Option<i32>, // frequency reported by the generator
+    pub band_hz: Option<(i32, i32)>,    // frequency limits of the connected model
+    pub power_watts: f32,               // last power confirmed by the generator
+    pub target_power_w: f32,            // setpoint the driver is ramping towards
+    pub commanded_power_w: f32,         // instantaneous ramped power sent to the generator
+    pub vswr: Option<f32>,              // derived from forward/reflected power
+    pub forward_ratio: Option<f32>,     // (forward - reflected) / forward: share of power coupled into the load
+    pub temperature_c: Option<f32>,     // PA temperature
+    pub forward_power_w: Option<f32>,
+    pub reflected_power_w: Option<f32>,
+    pub pa_current_a: Option<f32>,
+    pub pa_voltage_v: Option<f32>,
+    pub status_word: Option<u32>,       // raw generator status bits
+    pub faults: Vec<GeneratorFault>,    // decoded from the status word and error replies
+    pub sweep: Vec<SweepPoint>,         // points of the current or last frequency sweep
+    pub sweep_in_progress: bool,
+    pub sweep_best_hz: Option<i32>,     // minimum-reflection frequency of the last completed sweep
+    pub tracking: TrackingStatus,
+    pub pulse: Option<PulseSettings>,   // pulsed mode sent to the generator; None is CW
+}
+
+// One frequency sweep measurement
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepPoint {
+    pub frequency_hz: i32,
+    pub forward_w: f32,
+    pub reflected_w: f32,
 }
 
 #[derive(Clone, Default, Debug)]
 pub struct ArduinoState {
     pub connected: bool,
+    pub channels: Vec<PressureChannel>, // one per configured regulator, in firmware channel order
+    pub protocol_version: Option<u8>,   // 1 or 2 once negotiated on connect
+    pub firmware: Option<FirmwareInfo>, // identify reply of the connected controller
+    pub firmware_problem: Option<String>, // why enabling is refused (unknown or incompatible firmware)
+    pub watchdog_ms: Option<u32>,       // firmware watchdog timeout once armed
+    pub heartbeat_misses: u32,          // consecutive heartbeats without an ACK
+    pub watchdog_tripped: bool,         // firmware vented after losing the host heartbeat
+    pub autotune: Option<AutotuneStatus>,
+    pub step_test: Vec<StepSample>,     // response recorded during the last autotune step
+    pub stream_hz: Option<u32>,         // rate the firmware pushes telemetry at; None while polled
+    pub sample_rate_hz: Option<f32>,    // streamed rate, measured from device timestamps
+    pub dropped_samples: u64,           // gaps in the streamed sample counter
+    pub device_time_ms: Option<u32>,    // device timestamp of the latest streamed sample
+    pub status: Option<String>,
+    pub last_error: Option<String>,
+}
+
+// One pressure regulator of the pneumatic controller
+#[derive(Clone, Default, Debug)]
+pub struct PressureChannel {
+    pub name: String,                   // as addressed by jobs, e.g. "material", "sheath"
     pub enabled: bool,
     pub pressure_setpoint_psi: f32,
     pub pressure_measured_psi: f32,
     pub loop_current_ma: Option<f32>,
     pub signal_ok: Option<bool>,
-    pub status: Option<String>,
-    pub last_error: Option<String>,
+    pub ramp_rate_psi_per_s: Option<f32>, // last ramp rate the controller accepted
+    pub pid: Option<PidGains>,          // gains last read from or written to the controller
+}
+
+impl ArduinoState {
+    pub fn new(channel_names: &[String]) -> Self {
+        let channels = channel_names
+            .iter()
+            .map(|name| PressureChannel { name: name.clone(), ..PressureChannel::default() })
+            .collect();
+        Self { channels, ..Self::default() }
+    }
+
+    /// Index of the channel called `name`.
+    pub fn channel_index(&self, name: &str) -> Option<usize> {
+        self.channels.iter().position(|c| c.name == name)
+    }
+}
+
+// Pressure loop gains of the pneumatic controller
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PidGains {
+    pub kp: f32,
+    pub ki: f32,
+    pub kd: f32,
+}
+
+impl PidGains {
+    /// Problems with these gains, empty when the controller can use them.
+    pub fn validate(&self) -> Vec<String> {
+        [("Kp", self.kp), ("Ki", self.ki), ("Kd", self.kd)]
+            .into_iter()
+            .filter(|(_, gain)| !(gain.is_finite() && *gain >= 0.0))
+            .map(|(name, gain)| format!("{} must be a non-negative number, got {}", name, gain))
+            .collect()
+    }
+}
+
+// Step-response autotune, as reported by the pneumatic controller
+#[derive(Clone, Debug, PartialEq)]
+pub enum AutotuneStatus {
+    Running { channel: usize, from_psi: f32, to_psi: f32 },
+    Done(AutotuneResult),
+    Failed(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AutotuneResult {
+    pub gains: PidGains,        // suggested gains; applied only on request
+    pub rise_time_s: f32,       // 10–90 % of the step
+    pub overshoot_pct: f32,
+}
+
+// One sample of the pressure response during a step test
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StepSample {
+    pub t_s: f32,               // since the step was commanded
+    pub setpoint_psi: f32,
+    pub measured_psi: f32,
+}
+
+// Pneumatic controller firmware, as reported by its identify reply
+#[derive(Clone, Debug, PartialEq)]
+pub struct FirmwareInfo {
+    pub name: String,
+    pub version: String,            // as reported, e.g. "1.2.0"
+    pub commands: Vec<String>,      // supported commands
+    pub pressure_range_psi: (f32, f32),
+    pub sensor: String,             // pressure sensor type, e.g. "4-20mA"
+    pub channels: usize,            // pressure channels; 1 when not reported
 }
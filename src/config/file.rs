@@ -0,0 +1,517 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::config::{
+    ARDUINO_BAUD_RATE, ARDUINO_CHANNELS, ARDUINO_SERIAL_PORT, ARDUINO_STREAM_HZ, ARDUINO_WATCHDOG_MS, MICROCIRCUITS_PRODUCT_ID, MICROCIRCUITS_VENDOR_ID,
+    MICROWAVE_BAND_MHZ, MICROWAVE_BAUD_RATE, MICROWAVE_POWER_RAMP_W_PER_S, MICROWAVE_SERIAL_PORT, MICROWAVE_TELEMETRY_POLL_MS, TrackingSettings, duet_ip,
+};
+use crate::drivers::arduino::protocol::MIN_WATCHDOG_MS;
+use crate::interlock::InterlockConfig;
+use crate::print_engine::PrintConfig;
+use crate::utilities::units::{hz_to_mhz, mhz_to_hz};
+
+/// Config file read from the working directory when `--config` is not given.
+pub const DEFAULT_CONFIG_FILE: &str = "meteorite.json";
+
+/// Runtime configuration loaded from a JSON file.
+///
+/// # Schema
+/// Every section and every field is optional; unset fields fall back to the
+/// constants in `config.rs`. Unknown sections or fields are rejected.
+///
+/// ```json
+/// {
+///   "duet":      { "ip": "192.168.10.2" },
+///   "microwave": { "serial_port": "/dev/ttyUSB0", "baud_rate": 9600, "telemetry_poll_ms": 500,
+///                  "power_ramp_w_per_s": 20.0, "band_mhz": [902.0, 928.0],
+///                  "vendor_id": "0xFFFF", "product_id": "0xFFFF",
+///                  "tracking": { "step_hz": 100000, "interval_ms": 500, "span_hz": 5000000 } },
+///   "arduino":   { "serial_port": "COM5", "baud_rate": 115200, "watchdog_ms": 1000,
+///                  "stream_hz": 50, "channels": ["material", "sheath"] },
+///   "interlock": { "max_vswr": 3.0, "max_reflected_w": 50.0, "max_pa_temp_c": 70.0, "hold_s": 0.5 },
+///   "print":     { "safe_z_mm": 10.0, "preheat_s": 5.0,
+///                  "postamble": { "z_lift_mm": 5.0, "park_xy_mm": [0.0, 0.0] } }
+/// }
+/// ```
+///
+/// USB vendor/product IDs accept a number or a hex string ("0x2341"). They pick
+/// the generator's port in the Connections panel; `serial_port` is the fallback
+/// selection when no matching device is plugged in. `band_mhz` is the frequency
+/// range of the generator model; setpoints, sweeps and tracking stay inside it.
+/// An interlock limit set to `null` disables that interlock.
+/// The `print` section takes any `PrintConfig` field (see `print_engine/types.rs`).
+#[derive(Clone, Debug, Default)]
+pub struct AppConfig {
+    pub duet: DuetConfig,
+    pub microwave: MicrowaveConfig,
+    pub arduino: ArduinoConfig,
+    pub interlock: InterlockConfig,
+    pub print: PrintConfig,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DuetConfig {
+    pub ip: String,
+}
+
+impl Default for DuetConfig {
+    fn default() -> Self {
+        Self { ip: duet_ip.to_string() }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MicrowaveConfig {
+    pub serial_port: String,
+    pub baud_rate: u32,
+    pub telemetry_poll_ms: u64,
+    pub power_ramp_w_per_s: f32,      // default slew rate for power changes and RF soft-start
+    pub band_mhz: [f64; 2],           // generator model's frequency range
+    pub tracking: TrackingSettings,   // defaults for the resonance tracking loop
+    #[serde(deserialize_with = "usb_id")]
+    pub vendor_id: u16,
+    #[serde(deserialize_with = "usb_id")]
+    pub product_id: u16,
+}
+
+impl Default for MicrowaveConfig {
+    fn default() -> Self {
+        Self {
+            serial_port: MICROWAVE_SERIAL_PORT.to_string(),
+            baud_rate: MICROWAVE_BAUD_RATE,
+            telemetry_poll_ms: MICROWAVE_TELEMETRY_POLL_MS,
+            power_ramp_w_per_s: MICROWAVE_POWER_RAMP_W_PER_S,
+            band_mhz: MICROWAVE_BAND_MHZ,
+            tracking: TrackingSettings::default(),
+            vendor_id: MICROCIRCUITS_VENDOR_ID,
+            product_id: MICROCIRCUITS_PRODUCT_ID,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArduinoConfig {
+    pub serial_port: String,
+    pub baud_rate: u32,
+    pub watchdog_ms: u32,  // firmware vents without a heartbeat for this long; 0 disables
+    pub stream_hz: u32,    // pushed telemetry rate when the firmware can stream; 0 polls
+    pub channels: Vec<String>, // pressure channel names, by firmware channel number
+}
+
+impl Default for ArduinoConfig {
+    fn default() -> Self {
+        Self {
+            serial_port: ARDUINO_SERIAL_PORT.to_string(),
+            baud_rate: ARDUINO_BAUD_RATE,
+            watchdog_ms: ARDUINO_WATCHDOG_MS,
+            stream_hz: ARDUINO_STREAM_HZ,
+            channels: ARDUINO_CHANNELS.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+}
+
+/// Errors that can occur when loading a config file.
+#[derive(Clone, Debug)]
+pub enum ConfigError {
+    /// I/O error reading file
+    Io(String),
+    /// File is not valid JSON (or not a JSON object)
+    Json(String),
+    /// One or more sections failed to parse or validate, as (section, message)
+    Sections(Vec<(String, String)>),
+    /// Bad command line arguments
+    Args(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(msg) => write!(f, "I/O error: {}", msg),
+            ConfigError::Json(msg) => write!(f, "JSON error: {}", msg),
+            ConfigError::Sections(errors) => {
+                write!(f, "invalid config:")?;
+                for (section, msg) in errors {
+                    write!(f, "\n  [{}] {}", section, msg)?;
+                }
+                Ok(())
+            }
+            ConfigError::Args(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Accept a USB ID as a JSON number or a decimal/hex string.
+fn usb_id<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Number(u16),
+        Text(String),
+    }
+    match Raw::deserialize(deserializer)? {
+        Raw::Number(n) => Ok(n),
+        Raw::Text(text) => {
+            let text = text.trim();
+            let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+                Some(hex) => u16::from_str_radix(hex, 16),
+                None => text.parse::<u16>(),
+            };
+            parsed.map_err(|_| serde::de::Error::custom(format!("invalid USB id \"{}\"", text)))
+        }
+    }
+}
+
+/// Deserialize one section, falling back to its defaults when absent.
+fn section<T: Default + serde::de::DeserializeOwned>(
+    root: &mut serde_json::Map<String, serde_json::Value>,
+    name: &str,
+    errors: &mut Vec<(String, String)>,
+) -> T {
+    match root.remove(name) {
+        None => T::default(),
+        Some(value) => serde_json::from_value(value).unwrap_or_else(|e| {
+            errors.push((name.to_string(), e.to_string()));
+            T::default()
+        }),
+    }
+}
+
+fn validate_serial(port: &str, baud_rate: u32) -> Vec<String> {
+    let mut errors = Vec::new();
+    if port.trim().is_empty() {
+        errors.push("serial_port must not be empty".to_string());
+    }
+    if baud_rate == 0 {
+        errors.push("baud_rate must be greater than 0".to_string());
+    }
+    errors
+}
+
+pub fn validate_tracking(tracking: &TrackingSettings) -> Vec<String> {
+    let mut errors = Vec::new();
+    if tracking.step_hz <= 0 {
+        errors.push(format!("tracking.step_hz must be positive, got {}", tracking.step_hz));
+    }
+    if tracking.interval_ms == 0 {
+        errors.push("tracking.interval_ms must be greater than 0".to_string());
+    }
+    if tracking.span_hz < tracking.step_hz {
+        errors.push(format!(
+            "tracking.span_hz ({}) must be at least step_hz ({})",
+            tracking.span_hz, tracking.step_hz
+        ));
+    }
+    errors
+}
+
+fn validate_channels(channels: &[String]) -> Vec<String> {
+    let mut errors = Vec::new();
+    if !(1..=8).contains(&channels.len()) {
+        errors.push(format!("channels must name 1..8 pressure channels, got {}", channels.len()));
+    }
+    for (i, name) in channels.iter().enumerate() {
+        let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid {
+            errors.push(format!("channels: {:?} must be lowercase letters, digits and '_'", name));
+        } else if channels[..i].contains(name) {
+            errors.push(format!("channels: {:?} is listed twice", name));
+        }
+    }
+    errors
+}
+
+fn validate_interlock(interlock: &InterlockConfig) -> Vec<String> {
+    let mut errors = Vec::new();
+    let limits = [
+        ("max_vswr", interlock.max_vswr),
+        ("max_reflected_w", interlock.max_reflected_w),
+        ("max_pa_temp_c", interlock.max_pa_temp_c),
+    ];
+    for (name, limit) in limits {
+        if let Some(value) = limit
+            && (!value.is_finite() || value <= 0.0)
+        {
+            errors.push(format!("{} must be positive, got {}", name, value));
+        }
+    }
+    if interlock.max_vswr.is_some_and(|v| v < 1.0) {
+        errors.push("max_vswr must be at least 1.0".to_string());
+    }
+    if !interlock.hold_s.is_finite() || interlock.hold_s < 0.0 {
+        errors.push(format!("hold_s must be a non-negative number, got {}", interlock.hold_s));
+    }
+    errors
+}
+
+fn validate_print(print: &PrintConfig) -> Vec<String> {
+    let mut errors = Vec::new();
+    let non_negative = [
+        ("safe_z_mm", print.safe_z_mm),
+        ("homing_timeout_s", print.homing_timeout_s),
+        ("travel_timeout_s", print.travel_timeout_s),
+        ("position_tolerance_mm", print.position_tolerance_mm),
+        ("preheat_s", print.preheat_s),
+        ("rf_on_timeout_s", print.rf_on_timeout_s),
+        ("pressure_tolerance_psi", print.pressure_tolerance_psi),
+        ("pressure_settle_timeout_s", print.pressure_settle_timeout_s),
+        ("rf_only_step_s", print.rf_only_step_s),
+    ];
+    for (name, value) in non_negative {
+        if !value.is_finite() || value < 0.0 {
+            errors.push(format!("{} must be a non-negative number, got {}", name, value));
+        }
+    }
+    if let Some(rate) = print.postamble.pressure_ramp_psi_per_s
+        && (!rate.is_finite() || rate <= 0.0)
+    {
+        errors.push(format!("postamble.pressure_ramp_psi_per_s must be positive, got {}", rate));
+    }
+    errors
+}
+
+impl AppConfig {
+    /// Parse and validate a config from JSON text.
+    pub fn from_json(text: &str) -> Result<Self, ConfigError> {
+        let value: serde_json::Value =
+            serde_json::from_str(text).map_err(|e| ConfigError::Json(e.to_string()))?;
+        let serde_json::Value::Object(mut root) = value else {
+            return Err(ConfigError::Json("top level must be an object".to_string()));
+        };
+
+        let mut errors = Vec::new();
+        let config = AppConfig {
+            duet: section(&mut root, "duet", &mut errors),
+            microwave: section(&mut root, "microwave", &mut errors),
+            arduino: section(&mut root, "arduino", &mut errors),
+            interlock: section(&mut root, "interlock", &mut errors),
+            print: section(&mut root, "print", &mut errors),
+        };
+        for name in root.keys() {
+            errors.push((name.clone(), "unknown section".to_string()));
+        }
+
+        // Field checks only for sections that parsed
+        let mut check = |name: &str, problems: Vec<String>| {
+            if !errors.iter().any(|(section, _)| section == name) {
+                errors.extend(problems.into_iter().map(|msg| (name.to_string(), msg)));
+            }
+        };
+        check(
+            "duet",
+            if config.duet.ip.trim().is_empty() {
+                vec!["ip must not be empty".to_string()]
+            } else {
+                Vec::new()
+            },
+        );
+        let mut microwave_errors = validate_serial(&config.microwave.serial_port, config.microwave.baud_rate);
+        if config.microwave.telemetry_poll_ms == 0 {
+            microwave_errors.push("telemetry_poll_ms must be greater than 0".to_string());
+        }
+        let ramp = config.microwave.power_ramp_w_per_s;
+        if !ramp.is_finite() || ramp <= 0.0 {
+            microwave_errors.push(format!("power_ramp_w_per_s must be positive, got {}", ramp));
+        }
+        let [band_min, band_max] = config.microwave.band_mhz;
+        match (mhz_to_hz(band_min), mhz_to_hz(band_max)) {
+            (Some(min), Some(max)) if min < max => {}
+            (Some(_), Some(_)) => microwave_errors.push(format!(
+                "band_mhz must be [min, max] with min < max, got [{}, {}]",
+                band_min, band_max
+            )),
+            _ => microwave_errors.push(format!(
+                "band_mhz must be within 0..{:.0} MHz, got [{}, {}]",
+                hz_to_mhz(i32::MAX).floor(),
+                band_min,
+                band_max
+            )),
+        }
+        microwave_errors.extend(validate_tracking(&config.microwave.tracking));
+        check("microwave", microwave_errors);
+        let mut arduino_errors = validate_serial(&config.arduino.serial_port, config.arduino.baud_rate);
+        let watchdog_ms = config.arduino.watchdog_ms;
+        if watchdog_ms != 0 && !(MIN_WATCHDOG_MS..=60_000).contains(&watchdog_ms) {
+            arduino_errors.push(format!("watchdog_ms must be 0 (off) or {}..60000, got {}", MIN_WATCHDOG_MS, watchdog_ms));
+        }
+        if config.arduino.stream_hz > 1000 {
+            arduino_errors.push(format!("stream_hz must be 0 (poll) or 1..1000, got {}", config.arduino.stream_hz));
+        }
+        arduino_errors.extend(validate_channels(&config.arduino.channels));
+        check("arduino", arduino_errors);
+        check("interlock", validate_interlock(&config.interlock));
+        check("print", validate_print(&config.print));
+
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError::Sections(errors))
+        }
+    }
+
+    /// Load a config file from `path`.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::Io(format!("failed to read {}: {}", path.display(), e)))?;
+        Self::from_json(&text)
+    }
+
+    /// Load the config named by `--config <path>` (or `--config=<path>`) in `args`.
+    /// Without the flag, `DEFAULT_CONFIG_FILE` is used if present, otherwise the defaults.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, ConfigError> {
+        match config_path_from_args(args)? {
+            Some(path) => Self::load(&path),
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => Self::load(Path::new(DEFAULT_CONFIG_FILE)),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+/// Find the `--config` override in the program arguments (excluding argv[0]).
+fn config_path_from_args(args: impl IntoIterator<Item = String>) -> Result<Option<PathBuf>, ConfigError> {
+    let mut args = args.into_iter();
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            let value = args
+                .next()
+                .ok_or_else(|| ConfigError::Args("--config requires a file path".to_string()))?;
+            path = Some(PathBuf::from(value));
+        } else if let Some(value) = arg.strip_prefix("--config=") {
+            path = Some(PathBuf::from(value));
+        }
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section_errors(text: &str) -> Vec<(String, String)> {
+        match AppConfig::from_json(text) {
+            Err(ConfigError::Sections(errors)) => errors,
+            other => panic!("expected section errors, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = AppConfig::from_json("{}").unwrap();
+        assert_eq!(config.duet.ip, duet_ip);
+        assert_eq!(config.microwave.serial_port, MICROWAVE_SERIAL_PORT);
+        assert_eq!(config.microwave.baud_rate, MICROWAVE_BAUD_RATE);
+        assert_eq!(config.microwave.vendor_id, MICROCIRCUITS_VENDOR_ID);
+        assert_eq!(config.arduino.serial_port, ARDUINO_SERIAL_PORT);
+        assert_eq!(config.arduino.baud_rate, ARDUINO_BAUD_RATE);
+        assert_eq!(config.print.safe_z_mm, PrintConfig::default().safe_z_mm);
+    }
+
+    #[test]
+    fn test_partial_sections_fall_back_per_field() {
+        let config = AppConfig::from_json(
+            r#"{
+                "duet": { "ip": "10.0.0.7" },
+                "microwave": { "serial_port": "COM3", "vendor_id": "0x0403", "product_id": 24577 },
+                "arduino": { "baud_rate": 57600 },
+                "print": { "preheat_s": 2.5, "postamble": { "park_xy_mm": [1.0, 2.0] } }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.duet.ip, "10.0.0.7");
+        assert_eq!(config.microwave.serial_port, "COM3");
+        assert_eq!(config.microwave.baud_rate, MICROWAVE_BAUD_RATE);
+        assert_eq!(config.microwave.vendor_id, 0x0403);
+        assert_eq!(config.microwave.product_id, 24577);
+        assert_eq!(config.arduino.serial_port, ARDUINO_SERIAL_PORT);
+        assert_eq!(config.arduino.baud_rate, 57600);
+        assert_eq!(config.print.preheat_s, 2.5);
+        assert_eq!(config.print.postamble.park_xy_mm, Some([1.0, 2.0]));
+        assert!(config.print.postamble.rf_off);
+    }
+
+    #[test]
+    fn test_errors_are_reported_per_section() {
+        let errors = section_errors(
+            r#"{
+                "duet": { "ip": "" },
+                "microwave": { "baud": 9600 },
+                "arduino": { "baud_rate": 0 },
+                "plotter": {}
+            }"#,
+        );
+        let message = |name: &str| {
+            errors
+                .iter()
+                .find(|(section, _)| section == name)
+                .map(|(_, msg)| msg.clone())
+                .unwrap_or_else(|| panic!("no error for [{}] in {:?}", name, errors))
+        };
+        assert_eq!(errors.len(), 4);
+        assert!(message("duet").contains("ip"));
+        assert!(message("microwave").contains("baud"));
+        assert!(message("arduino").contains("baud_rate"));
+        assert_eq!(message("plotter"), "unknown section");
+    }
+
+    #[test]
+    fn test_invalid_values_rejected() {
+        let errors = section_errors(r#"{ "microwave": { "vendor_id": "0xZZ" } }"#);
+        assert_eq!(errors[0].0, "microwave");
+
+        let errors = section_errors(r#"{ "print": { "preheat_s": -1.0 } }"#);
+        assert_eq!(errors[0].0, "print");
+        assert!(errors[0].1.contains("preheat_s"));
+
+        let errors = section_errors(r#"{ "interlock": { "max_vswr": 0.5, "hold_s": -1.0 } }"#);
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|(section, _)| section == "interlock"));
+        let errors = section_errors(r#"{ "microwave": { "band_mhz": [2400.0, 2500.0] } }"#);
+        assert!(errors[0].1.contains("band_mhz"));
+        let errors = section_errors(r#"{ "arduino": { "watchdog_ms": 50 } }"#);
+        assert!(errors[0].1.contains("watchdog_ms"));
+        // One lost ACK must not starve the watchdog
+        let errors = section_errors(r#"{ "arduino": { "watchdog_ms": 500 } }"#);
+        assert!(errors[0].1.contains("watchdog_ms"));
+        let errors = section_errors(r#"{ "arduino": { "stream_hz": 5000 } }"#);
+        assert!(errors[0].1.contains("stream_hz"));
+        let errors = section_errors(r#"{ "arduino": { "channels": ["material", "Sheath", "material"] } }"#);
+        assert_eq!(errors.len(), 2);
+        let errors = section_errors(r#"{ "arduino": { "channels": [] } }"#);
+        assert!(errors[0].1.contains("channels"));
+
+        let config = AppConfig::from_json(r#"{ "interlock": { "max_pa_temp_c": null } }"#).unwrap();
+        assert_eq!(config.interlock.max_pa_temp_c, None);
+        assert_eq!(config.interlock.max_vswr, Some(3.0));
+
+        assert!(matches!(AppConfig::from_json("[1, 2]"), Err(ConfigError::Json(_))));
+        assert!(matches!(AppConfig::from_json("{ nope"), Err(ConfigError::Json(_))));
+    }
+
+    #[test]
+    fn test_config_path_from_args() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(config_path_from_args(args(&[])).unwrap(), None);
+        assert_eq!(
+            config_path_from_args(args(&["--config", "rig.json"])).unwrap(),
+            Some(PathBuf::from("rig.json"))
+        );
+        assert_eq!(
+            config_path_from_args(args(&["--config=lab/rig.json"])).unwrap(),
+            Some(PathBuf::from("lab/rig.json"))
+        );
+        assert!(config_path_from_args(args(&["--config"])).is_err());
+    }
+
+    #[test]
+    fn test_missing_explicit_file_is_an_error() {
+        let args = vec!["--config".to_string(), "/nonexistent/meteorite.json".to_string()];
+        assert!(matches!(AppConfig::from_args(args), Err(ConfigError::Io(_))));
+    }
+}
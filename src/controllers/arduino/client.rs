@@ -1,7 +1,7 @@
 use std::sync::{Arc, RwLock};
 use tokio::sync::mpsc;
 
-use crate::config::config::{ArduinoCommand, ArduinoState};
+use crate::config::config::{ArduinoCommand, ArduinoState, PidGains};
 use crate::controllers::ArduinoController;
 
 pub struct ArduinoClient {
@@ -20,8 +20,8 @@ impl ArduinoClient {
 }
 
 impl ArduinoController for ArduinoClient {
-    fn connect(&self) {
-        let send_res = self.cmd_tx.try_send(ArduinoCommand::Connect);
+    fn connect(&self, port: &str) {
+        let send_res = self.cmd_tx.try_send(ArduinoCommand::Connect(port.to_string()));
         let mut s = self.state.write().unwrap();
         match send_res {
             Ok(_) => {
@@ -47,13 +47,15 @@ impl ArduinoController for ArduinoClient {
         }
     }
 
-    fn enable(&self, enable: bool) {
-        let send_res = self.cmd_tx.try_send(ArduinoCommand::Enable(enable));
+    fn enable(&self, channel: usize, enable: bool) {
+        let send_res = self.cmd_tx.try_send(ArduinoCommand::Enable(channel, enable));
         let mut s = self.state.write().unwrap();
         match send_res {
             Ok(_) => {
                 s.last_error = None;
-                s.enabled = enable;
+                if let Some(c) = s.channels.get_mut(channel) {
+                    c.enabled = enable;
+                }
                 s.status = Some(if enable { "enabled" } else { "disabled" }.into());
             }
             Err(e) => {
@@ -62,13 +64,68 @@ impl ArduinoController for ArduinoClient {
         }
     }
 
-    fn set_pressure_setpoint(&self, psi: f32) {
-        let send_res = self.cmd_tx.try_send(ArduinoCommand::SetPressureSetpoint(psi));
+    fn set_pressure_setpoint(&self, channel: usize, psi: f32) {
+        let send_res = self.cmd_tx.try_send(ArduinoCommand::SetPressureSetpoint(channel, psi));
         let mut s = self.state.write().unwrap();
         match send_res {
             Ok(_) => {
                 s.last_error = None;
-                s.pressure_setpoint_psi = psi.max(0.0);
+                if let Some(c) = s.channels.get_mut(channel) {
+                    c.pressure_setpoint_psi = psi.max(0.0);
+                }
+            }
+            Err(e) => {
+                s.last_error = Some(format!("send failed: {}", e));
+            }
+        }
+    }
+
+    fn set_ramp_rate(&self, channel: usize, psi_per_s: f32) {
+        let send_res = self.cmd_tx.try_send(ArduinoCommand::SetRampRate(channel, psi_per_s));
+        let mut s = self.state.write().unwrap();
+        match send_res {
+            Ok(_) => {
+                s.last_error = None;
+            }
+            Err(e) => {
+                s.last_error = Some(format!("send failed: {}", e));
+            }
+        }
+    }
+
+    fn get_pid(&self, channel: usize) {
+        let send_res = self.cmd_tx.try_send(ArduinoCommand::GetPid(channel));
+        let mut s = self.state.write().unwrap();
+        match send_res {
+            Ok(_) => {
+                s.last_error = None;
+            }
+            Err(e) => {
+                s.last_error = Some(format!("send failed: {}", e));
+            }
+        }
+    }
+
+    fn set_pid(&self, channel: usize, gains: PidGains) {
+        let send_res = self.cmd_tx.try_send(ArduinoCommand::SetPid(channel, gains));
+        let mut s = self.state.write().unwrap();
+        match send_res {
+            Ok(_) => {
+                s.last_error = None;
+            }
+            Err(e) => {
+                s.last_error = Some(format!("send failed: {}", e));
+            }
+        }
+    }
+
+    fn autotune(&self, channel: usize, step_psi: f32) {
+        let send_res = self.cmd_tx.try_send(ArduinoCommand::Autotune(channel, step_psi));
+        let mut s = self.state.write().unwrap();
+        match send_res {
+            // the driver reports the result into the state
+            Ok(_) => {
+                s.last_error = None;
             }
             Err(e) => {
                 s.last_error = Some(format!("send failed: {}", e));
@@ -1,71 +1,267 @@
-use std::sync::Mutex;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
 
-use crate::config::config::ArduinoState;
+use crate::config::config::{
+    ARDUINO_STREAM_HZ, ArduinoState, AutotuneResult, AutotuneStatus, FirmwareInfo, PidGains,
+    PressureChannel, StepSample,
+};
 use crate::controllers::ArduinoController;
 
+// Step response of the mock regulator: second order, slightly underdamped
+const MOCK_DAMPING: f32 = 0.6;
+const MOCK_NATURAL_RAD_S: f32 = 4.0;
+const MOCK_STEP_TEST_S: f32 = 5.0;
+const MOCK_SAMPLE_S: f32 = 0.1;
+
+/// Normalised response (0 → 1) of the mock regulator `t_s` after a step.
+fn mock_step_response(t_s: f32) -> f32 {
+    let damped = MOCK_NATURAL_RAD_S * (1.0 - MOCK_DAMPING * MOCK_DAMPING).sqrt();
+    let decay = (-MOCK_DAMPING * MOCK_NATURAL_RAD_S * t_s).exp();
+    1.0 - decay * ((damped * t_s).cos() + MOCK_DAMPING * MOCK_NATURAL_RAD_S / damped * (damped * t_s).sin())
+}
+
 pub struct MockArduino {
     state: Mutex<ArduinoState>,
+    step_test_started: Mutex<Option<Instant>>,
+    connected_at: Mutex<Option<Instant>>,  // mock device clock starts here
 }
 
 impl MockArduino {
-    pub fn new() -> Self {
-        let mut s = ArduinoState::default();
-        s.connected = false;
-        s.enabled = false;
-        s.pressure_setpoint_psi = 0.0;
-        s.pressure_measured_psi = 0.0;
-        Self { state: Mutex::new(s) }
+    pub fn new(channel_names: &[String]) -> Self {
+        let s = ArduinoState::new(channel_names);
+        Self { state: Mutex::new(s), step_test_started: Mutex::new(None), connected_at: Mutex::new(None) }
+    }
+
+    // Lock the state with a running step test brought up to now
+    fn lock(&self) -> MutexGuard<'_, ArduinoState> {
+        let mut s = self.state.lock().unwrap();
+        let mut started = self.step_test_started.lock().unwrap();
+        if let Some(at) = *started
+            && advance_step_test(&mut s, at.elapsed().as_secs_f32())
+        {
+            *started = None;
+        }
+        drop(started);
+        // Streams at exactly the requested rate and never drops a sample
+        s.device_time_ms = self.connected_at.lock().unwrap().map(|at| at.elapsed().as_millis() as u32);
+        s
+    }
+}
+
+/// Channel `index` of a connected mock, or None with the reason in `last_error`.
+fn channel_mut(s: &mut ArduinoState, index: usize) -> Option<&mut PressureChannel> {
+    if !s.connected {
+        s.last_error = Some("Arduino not connected".into());
+        return None;
+    }
+    if index >= s.channels.len() {
+        s.last_error = Some(format!("no pressure channel {}", index));
+        return None;
+    }
+    s.last_error = None;
+    s.channels.get_mut(index)
+}
+
+/// Record the mock step response up to `elapsed_s`; true once the test is over.
+fn advance_step_test(s: &mut ArduinoState, elapsed_s: f32) -> bool {
+    let Some(AutotuneStatus::Running { channel, from_psi, to_psi }) = s.autotune else {
+        return true;
+    };
+    let step_psi = to_psi - from_psi;
+    let end_s = elapsed_s.min(MOCK_STEP_TEST_S);
+    while (s.step_test.len() as f32) * MOCK_SAMPLE_S <= end_s {
+        let t_s = s.step_test.len() as f32 * MOCK_SAMPLE_S;
+        let measured_psi = from_psi + step_psi * mock_step_response(t_s);
+        s.step_test.push(StepSample { t_s, setpoint_psi: to_psi, measured_psi });
     }
+    s.channels[channel].pressure_measured_psi = s.step_test.last().map_or(from_psi, |p| p.measured_psi);
+    if elapsed_s < MOCK_STEP_TEST_S {
+        return false;
+    }
+
+    // Like the firmware: report the response and return to the original setpoint
+    let rise = [0.1, 0.9].map(|share| {
+        s.step_test.iter().find(|p| mock_step_response(p.t_s) >= share).map_or(0.0, |p| p.t_s)
+    });
+    let peak = s.step_test.iter().map(|p| mock_step_response(p.t_s)).fold(0.0, f32::max);
+    s.autotune = Some(AutotuneStatus::Done(AutotuneResult {
+        gains: PidGains { kp: 1.5, ki: 0.4, kd: 0.05 },
+        rise_time_s: rise[1] - rise[0],
+        overshoot_pct: (peak - 1.0).max(0.0) * 100.0,
+    }));
+    s.channels[channel].pressure_measured_psi = from_psi;
+    true
 }
 
 impl ArduinoController for MockArduino {
-    fn connect(&self) {
+    fn connect(&self, _port: &str) {
         let mut s = self.state.lock().unwrap();
+        let channels = s.channels.len();
         s.connected = true;
         s.last_error = None;
         s.status = Some("connected (mock)".into());
-        // Simulate some reasonable pressure reading
-        s.pressure_measured_psi = 0.0;
+        s.protocol_version = Some(2);
+        s.firmware = Some(FirmwareInfo {
+            name: "meteorite-pneumatics".into(),
+            version: "1.0.0-mock".into(),
+            commands: ["SET_PSI", "ENABLE", "READ", "WDT", "HB", "RAMP", "PID", "TUNE", "STREAM"].map(String::from).to_vec(),
+            pressure_range_psi: (0.0, 100.0),
+            sensor: "simulated".into(),
+            channels,
+        });
+        s.watchdog_ms = Some(crate::config::config::ARDUINO_WATCHDOG_MS);
+        s.stream_hz = Some(ARDUINO_STREAM_HZ);
+        s.sample_rate_hz = Some(ARDUINO_STREAM_HZ as f32);
+        *self.connected_at.lock().unwrap() = Some(Instant::now());
+        for c in &mut s.channels {
+            c.pid = Some(PidGains { kp: 1.0, ki: 0.2, kd: 0.0 });
+            // Simulate some reasonable pressure reading
+            c.pressure_measured_psi = 0.0;
+        }
     }
 
     fn disconnect(&self) {
-        let mut s = self.state.lock().unwrap();
+        let mut s = self.lock();
+        if matches!(s.autotune, Some(AutotuneStatus::Running { .. })) {
+            s.autotune = Some(AutotuneStatus::Failed("link lost".into()));
+        }
         s.connected = false;
-        s.enabled = false;
         s.status = Some("disconnected".into());
-        s.pressure_measured_psi = 0.0;
+        s.protocol_version = None;
+        s.firmware = None;
+        s.watchdog_ms = None;
+        s.stream_hz = None;
+        s.sample_rate_hz = None;
+        *self.connected_at.lock().unwrap() = None;
+        for c in &mut s.channels {
+            c.enabled = false;
+            c.pid = None;
+            c.ramp_rate_psi_per_s = None;
+            c.pressure_measured_psi = 0.0;
+        }
     }
 
-    fn enable(&self, enable: bool) {
+    fn enable(&self, channel: usize, enable: bool) {
         let mut s = self.state.lock().unwrap();
-        if !s.connected {
-            s.last_error = Some("Arduino not connected".into());
+        let Some(c) = channel_mut(&mut s, channel) else {
             return;
-        }
-        s.last_error = None;
-        s.enabled = enable;
+        };
+        c.enabled = enable;
+        // Regulator vents when disabled and holds the setpoint when enabled
+        c.pressure_measured_psi = if enable { c.pressure_setpoint_psi } else { 0.0 };
         s.status = Some(if enable { "enabled" } else { "disabled" }.into());
     }
 
-    fn set_pressure_setpoint(&self, psi: f32) {
+    fn set_pressure_setpoint(&self, channel: usize, psi: f32) {
         let mut s = self.state.lock().unwrap();
-        if !s.connected {
-            s.last_error = Some("Arduino not connected".into());
+        let Some(c) = channel_mut(&mut s, channel) else {
             return;
+        };
+        c.pressure_setpoint_psi = psi.max(0.0);
+
+        // Mock behavior: simulate pressure tracking setpoint
+        if c.enabled {
+            c.pressure_measured_psi = psi.max(0.0);
+            c.loop_current_ma = Some(12.0 + psi * 0.5); // Simulate some current based on pressure
+            c.signal_ok = Some(true);
         }
-        s.last_error = None;
-        s.pressure_setpoint_psi = psi.max(0.0);
-        
-        // Mock behavior: simulate pressure tracking setpoint with some lag
-        if s.enabled {
-            // Simulate pressure approaching setpoint
-            s.pressure_measured_psi = psi * 0.9; // 90% of setpoint in mock
-            s.loop_current_ma = Some(12.0 + psi * 0.5); // Simulate some current based on pressure
-            s.signal_ok = Some(true);
+    }
+
+    fn set_ramp_rate(&self, channel: usize, psi_per_s: f32) {
+        let mut s = self.state.lock().unwrap();
+        if let Some(c) = channel_mut(&mut s, channel) {
+            c.ramp_rate_psi_per_s = Some(psi_per_s.max(0.0));
+        }
+    }
+
+    fn get_pid(&self, channel: usize) {
+        let mut s = self.state.lock().unwrap();
+        channel_mut(&mut s, channel);
+    }
+
+    fn set_pid(&self, channel: usize, gains: PidGains) {
+        let mut s = self.state.lock().unwrap();
+        if channel_mut(&mut s, channel).is_none() {
+            return;
+        }
+        let problems = gains.validate();
+        if !problems.is_empty() {
+            s.last_error = Some(problems.join("; "));
+            return;
+        }
+        s.channels[channel].pid = Some(gains);
+    }
+
+    fn autotune(&self, channel: usize, step_psi: f32) {
+        let mut s = self.lock();
+        let Some(from_psi) = channel_mut(&mut s, channel).filter(|c| c.enabled).map(|c| c.pressure_setpoint_psi) else {
+            if s.last_error.is_none() {
+                s.last_error = Some("refusing autotune: enable pressure control first".into());
+            }
+            return;
+        };
+        if matches!(s.autotune, Some(AutotuneStatus::Running { .. })) {
+            s.last_error = Some("refusing autotune: autotune already running".into());
+            return;
         }
+        s.autotune = Some(AutotuneStatus::Running { channel, from_psi, to_psi: from_psi + step_psi });
+        s.step_test.clear();
+        *self.step_test_started.lock().unwrap() = Some(Instant::now());
     }
 
     fn state(&self) -> ArduinoState {
-        self.state.lock().unwrap().clone()
+        self.lock().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_autotune_records_step_response() {
+        let arduino = MockArduino::new(&["material".into()]);
+        arduino.connect("mock");
+        arduino.autotune(0, 10.0);
+        assert!(arduino.state().autotune.is_none());
+
+        arduino.set_pressure_setpoint(0, 20.0);
+        arduino.enable(0, true);
+        arduino.autotune(0, 10.0);
+        assert_eq!(arduino.state().autotune, Some(AutotuneStatus::Running { channel: 0, from_psi: 20.0, to_psi: 30.0 }));
+
+        // Pretend the step test has run its course
+        *arduino.step_test_started.lock().unwrap() = Some(Instant::now() - Duration::from_secs(6));
+        let s = arduino.state();
+        let Some(AutotuneStatus::Done(result)) = s.autotune else {
+            panic!("autotune did not complete: {:?}", s.autotune);
+        };
+        // Underdamped at ζ = 0.6: about 9.5 % overshoot, settling on the new setpoint
+        assert!((result.overshoot_pct - 9.5).abs() < 1.0);
+        assert!(result.rise_time_s > 0.0);
+        assert_eq!(s.step_test.len(), 51);
+        let last = s.step_test.last().unwrap();
+        assert_eq!(last.setpoint_psi, 30.0);
+        assert!((last.measured_psi - 30.0).abs() < 0.1);
+        assert_eq!(s.channels[0].pressure_measured_psi, 20.0);
+    }
+
+    #[test]
+    fn test_channels_are_independent() {
+        let arduino = MockArduino::new(&["material".into(), "sheath".into()]);
+        arduino.connect("mock");
+        arduino.set_pressure_setpoint(0, 30.0);
+        arduino.set_pressure_setpoint(1, 5.0);
+        arduino.enable(1, true);
+        let s = arduino.state();
+        assert_eq!(s.channel_index("sheath"), Some(1));
+        assert_eq!(s.firmware.unwrap().channels, 2);
+        assert!(!s.channels[0].enabled && s.channels[1].enabled);
+        assert_eq!(s.channels[0].pressure_measured_psi, 0.0);
+        assert_eq!(s.channels[1].pressure_measured_psi, 5.0);
+
+        arduino.enable(2, true);
+        assert_eq!(arduino.state().last_error.as_deref(), Some("no pressure channel 2"));
     }
 }
@@ -1,5 +1,5 @@
-#[cfg(feature = "mock")]
-mod mock;
+#[cfg(any(feature = "mock", test))]
+pub mod mock;
 #[cfg(feature = "mock")]
 pub use mock::MockArduino;
 
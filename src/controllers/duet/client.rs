@@ -53,14 +53,16 @@ impl DuetController for DuetClient {
 
     fn send_gcode(&self, gcode: &str) {
         let msg = DuetCommand::SendGcode(gcode.to_owned());
-        let send_res = self.cmd_tx.try_send(msg);
+        // Count it before the driver can see it; the driver decrements when it answers
         let mut s = self.state.write().unwrap();
-        match send_res {
+        s.gcode_in_flight += 1;
+        match self.cmd_tx.try_send(msg) {
             Ok(_) => {
                 s.last_error = None;
                 s.last_command = Some(gcode.to_owned());
             }
             Err(e) => {
+                s.gcode_in_flight -= 1;
                 s.last_error = Some(format!("send failed: {}", e));
             }
         }
@@ -72,16 +74,58 @@ impl DuetController for DuetClient {
 
     fn send_m_cmd(&self, m_cmd: &str) {
         let msg = DuetCommand::SendMCommand(m_cmd.to_owned());
-        let send_res = self.cmd_tx.try_send(msg);
+        // Count it before the driver can see it; the driver decrements when it answers
         let mut s = self.state.write().unwrap();
-        match send_res {
+        s.gcode_in_flight += 1;
+        match self.cmd_tx.try_send(msg) {
             Ok(_) => {
                 s.last_error = None;
                 s.last_command = Some(m_cmd.to_owned());
             }
             Err(e) => {
+                s.gcode_in_flight -= 1;
                 s.last_error = Some(format!("send failed: {}", e));
             }
         }
     }
 }
+
+// The client is only wired up in the real build
+#[cfg(all(test, feature = "real"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_flight_counted_before_driver_answers() {
+        let (cmd_tx, mut cmd_rx) = mpsc::channel::<DuetCommand>(4);
+        let state = Arc::new(RwLock::new(DuetState::default()));
+        let client = DuetClient::new(cmd_tx, Arc::clone(&state));
+
+        // Driver that answers "not connected" as soon as the command arrives
+        let driver_state = Arc::clone(&state);
+        let driver = std::thread::spawn(move || {
+            for _ in 0..2 {
+                cmd_rx.blocking_recv().unwrap();
+                let mut s = driver_state.write().unwrap();
+                assert!(s.gcode_in_flight > 0, "driver saw a command that was not counted");
+                s.gcode_in_flight = s.gcode_in_flight.saturating_sub(1);
+            }
+        });
+        client.send_gcode("G28");
+        client.send_m_cmd("M400");
+        driver.join().unwrap();
+        assert_eq!(state.read().unwrap().gcode_in_flight, 0);
+    }
+
+    #[test]
+    fn test_failed_send_is_not_counted() {
+        let (cmd_tx, cmd_rx) = mpsc::channel::<DuetCommand>(1);
+        drop(cmd_rx);
+        let state = Arc::new(RwLock::new(DuetState::default()));
+        let client = DuetClient::new(cmd_tx, Arc::clone(&state));
+        client.send_gcode("G28");
+        let s = state.read().unwrap();
+        assert_eq!(s.gcode_in_flight, 0);
+        assert!(s.last_error.is_some());
+    }
+}
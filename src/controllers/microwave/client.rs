@@ -1,7 +1,7 @@
 use std::sync::{Arc, RwLock};
 use tokio::sync::mpsc;
 
-use crate::config::config::{MicrowaveCommand, MicrowaveState};
+use crate::config::config::{MicrowaveCommand, MicrowaveState, PulseSettings, TrackingSettings};
 use crate::controllers::MicrowaveController;
 
 pub struct MicrowaveClient {
@@ -20,10 +20,10 @@ impl MicrowaveClient {
 }
 
 impl MicrowaveController for MicrowaveClient {
-    fn connect(&self) {
+    fn connect(&self, port: &str) {
         let send_res = self
             .cmd_tx
-            .try_send(MicrowaveCommand::Connect);
+            .try_send(MicrowaveCommand::Connect(port.to_string()));
         let mut s = self.state.write().unwrap();
         match send_res {
             Ok(_) => {
@@ -51,14 +51,14 @@ impl MicrowaveController for MicrowaveClient {
         }
     }
 
-    fn set_power(&self, watts: f32) {
-        let msg = MicrowaveCommand::SetPowerWatts(watts);
+    fn set_power(&self, watts: f32, ramp_w_per_s: Option<f32>) {
+        let msg = MicrowaveCommand::SetPowerWatts { watts, ramp_w_per_s };
         let send_res = self.cmd_tx.try_send(msg);
         let mut s = self.state.write().unwrap();
         match send_res {
+            // target/commanded power are updated by the driver as it ramps
             Ok(_) => {
                 s.last_error = None;
-                s.power_watts = watts.max(0.0);
             }
             Err(e) => {
                 s.last_error = Some(format!("send failed: {}", e));
@@ -80,14 +80,70 @@ impl MicrowaveController for MicrowaveClient {
         }
     }
 
+    fn sweep(&self, start_hz: i32, stop_hz: i32, step_hz: i32, power_w: f32) {
+        let msg = MicrowaveCommand::Sweep { start_hz, stop_hz, step_hz, power_w };
+        let send_res = self.cmd_tx.try_send(msg);
+        let mut s = self.state.write().unwrap();
+        match send_res {
+            Ok(_) => {
+                s.last_error = None;
+                s.status = Some("sweep requested".into());
+            }
+            Err(e) => {
+                s.last_error = Some(format!("send failed: {}", e));
+            }
+        }
+    }
+
+    fn set_tracking(&self, settings: Option<TrackingSettings>) {
+        let send_res = self.cmd_tx.try_send(MicrowaveCommand::SetTracking(settings));
+        let mut s = self.state.write().unwrap();
+        match send_res {
+            Ok(_) => {
+                s.last_error = None;
+            }
+            Err(e) => {
+                s.last_error = Some(format!("send failed: {}", e));
+            }
+        }
+    }
+
+    fn set_pulse(&self, settings: Option<PulseSettings>) {
+        let send_res = self.cmd_tx.try_send(MicrowaveCommand::SetPulse(settings));
+        let mut s = self.state.write().unwrap();
+        match send_res {
+            // pulse is updated once the driver has sent the command
+            Ok(_) => {
+                s.last_error = None;
+            }
+            Err(e) => {
+                s.last_error = Some(format!("send failed: {}", e));
+            }
+        }
+    }
+
+    fn clear_errors(&self) {
+        let send_res = self.cmd_tx.try_send(MicrowaveCommand::ClearErrors);
+        let mut s = self.state.write().unwrap();
+        match send_res {
+            // faults are cleared once the driver has sent the command
+            Ok(_) => {
+                s.last_error = None;
+            }
+            Err(e) => {
+                s.last_error = Some(format!("send failed: {}", e));
+            }
+        }
+    }
+
     fn rf_on(&self) {
         let send_res = self.cmd_tx.try_send(MicrowaveCommand::RfOn);
         let mut s = self.state.write().unwrap();
         match send_res {
+            // enabled is set when the generator confirms RF on
             Ok(_) => {
                 s.last_error = None;
-                s.enabled = true;
-                s.status = Some("RF on".into());
+                s.status = Some("RF on requested".into());
             }
             Err(e) => {
                 s.last_error = Some(format!("send failed: {}", e));
@@ -101,8 +157,7 @@ impl MicrowaveController for MicrowaveClient {
         match send_res {
             Ok(_) => {
                 s.last_error = None;
-                s.enabled = false;
-                s.status = Some("RF off".into());
+                s.status = Some("RF off requested".into());
             }
             Err(e) => {
                 s.last_error = Some(format!("send failed: {}", e));
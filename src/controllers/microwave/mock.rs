@@ -1,10 +1,26 @@
-use std::sync::Mutex;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
 
-use crate::config::config::MicrowaveState;
+use crate::config::config::{MicrowaveState, PulseSettings, SweepPoint, TrackingSettings, TrackingStatus, MICROWAVE_BAND_MHZ};
 use crate::controllers::MicrowaveController;
+use crate::controllers::microwave::sim::SimulatedGenerator;
+use crate::drivers::microwave::faults::decode_status;
+use crate::drivers::microwave::sweep::{best_match, sweep_frequencies};
+use crate::drivers::microwave::tracking::Tracker;
+use crate::drivers::microwave::{tracking_status, vswr};
+use crate::utilities::units::mhz_to_hz;
+
+// Simulated hardware behind the mock, advanced on every access
+struct Physics {
+    generator: SimulatedGenerator,
+    last_update: Instant,
+    tracker: Option<Tracker>,
+    tracking_elapsed_s: f32,  // time since the last tracking step
+}
 
 pub struct MockMicrowave {
     state: Mutex<MicrowaveState>,
+    physics: Mutex<Physics>,
 }
 
 impl MockMicrowave {
@@ -13,68 +29,373 @@ impl MockMicrowave {
         s.connected = false;
         s.enabled = false;
         s.power_watts = 0.0;
-        Self { state: Mutex::new(s) }
+        // Load resonates a little below mid-band, so the power-up frequency is detuned
+        let (min_hz, max_hz) = Self::band_hz();
+        let resonance_hz = min_hz + (max_hz - min_hz) / 5 * 2;
+        let physics = Physics {
+            generator: SimulatedGenerator::new(resonance_hz),
+            last_update: Instant::now(),
+            tracker: None,
+            tracking_elapsed_s: 0.0,
+        };
+        Self { state: Mutex::new(s), physics: Mutex::new(physics) }
+    }
+
+    // Mock generator band, from the default config
+    fn band_hz() -> (i32, i32) {
+        let [min_mhz, max_mhz] = MICROWAVE_BAND_MHZ;
+        (mhz_to_hz(min_mhz).unwrap_or(0), mhz_to_hz(max_mhz).unwrap_or(i32::MAX))
+    }
+
+    // Frequency the generator powers up at
+    fn power_up_hz() -> i32 {
+        let (min_hz, max_hz) = Self::band_hz();
+        min_hz + (max_hz - min_hz) / 2
+    }
+
+    // Lock the state with the simulation brought up to now
+    fn lock(&self) -> MutexGuard<'_, MicrowaveState> {
+        let mut s = self.state.lock().unwrap();
+        let mut physics = self.physics.lock().unwrap();
+        let now = Instant::now();
+        let dt = now.duration_since(physics.last_update).as_secs_f32();
+        physics.last_update = now;
+        physics.advance(&mut s, dt);
+        drop(physics);
+        s
+    }
+}
+
+impl Physics {
+    // Integrate the load and PA over `dt` seconds, step tracking, refresh the readings
+    fn advance(&mut self, s: &mut MicrowaveState, dt: f32) {
+        let rf_on = s.connected && s.enabled;
+        let power_w = if rf_on { s.commanded_power_w } else { 0.0 };
+        let duty = s.pulse.map(|p| p.duty_pct / 100.0).unwrap_or(1.0);
+        let mut frequency_hz = s.frequency_readback_hz.unwrap_or_else(MockMicrowave::power_up_hz);
+
+        let mut remaining = dt.max(0.0);
+        while remaining > 0.0 {
+            let Some(tracker) = self.tracker.as_mut().filter(|_| rf_on) else {
+                self.generator.advance(remaining, frequency_hz, power_w, duty);
+                break;
+            };
+            // Advance to the next tracking step, then move the frequency
+            let interval_s = tracker.settings.interval_ms as f32 / 1000.0;
+            let chunk = remaining.min((interval_s - self.tracking_elapsed_s).max(0.0));
+            self.generator.advance(chunk, frequency_hz, power_w, duty);
+            remaining -= chunk;
+            self.tracking_elapsed_s += chunk;
+            if self.tracking_elapsed_s >= interval_s {
+                self.tracking_elapsed_s = 0.0;
+                let ratio = self.generator.reflection(frequency_hz);
+                frequency_hz = tracker.update(ratio);
+                s.frequency_hz = Some(frequency_hz);
+                s.tracking = tracking_status(tracker, true, Some(ratio));
+            }
+        }
+        if let Some(ref tracker) = self.tracker
+            && s.tracking.active != rf_on
+        {
+            s.tracking = tracking_status(tracker, rf_on, s.tracking.reflection_ratio);
+        }
+
+        if !s.connected {
+            return;
+        }
+        s.frequency_readback_hz = Some(frequency_hz);
+        let readings = self.generator.readings(frequency_hz, power_w);
+        s.forward_power_w = Some(readings.forward_w);
+        s.reflected_power_w = Some(readings.reflected_w);
+        s.vswr = vswr(readings.forward_w, readings.reflected_w);
+        s.forward_ratio = (readings.forward_w > 0.0)
+            .then(|| ((readings.forward_w - readings.reflected_w) / readings.forward_w).clamp(0.0, 1.0));
+        s.temperature_c = Some(readings.pa_temperature_c);
+        s.pa_current_a = Some(readings.pa_current_a);
+        s.pa_voltage_v = Some(readings.pa_voltage_v);
+        // Protection flags latch until cleared; a shutdown cuts RF like the real generator
+        let word = readings.status_word() | s.status_word.unwrap_or(0);
+        s.status_word = Some(word);
+        for fault in decode_status(word) {
+            if !s.faults.contains(&fault) {
+                s.faults.push(fault);
+            }
+            if fault.shuts_down_rf() && s.enabled {
+                s.enabled = false;
+                s.commanded_power_w = 0.0;
+                s.status = Some("RF shut down by generator".into());
+                s.last_error = Some(format!("generator fault: {}", fault.description()));
+            }
+        }
+    }
+
+    fn recenter(&mut self, hz: i32) {
+        if let Some(ref mut tracker) = self.tracker {
+            tracker.recenter(hz);
+            self.tracking_elapsed_s = 0.0;
+        }
     }
 }
 
 impl MicrowaveController for MockMicrowave {
-    fn connect(&self) {
-        let mut s = self.state.lock().unwrap();
+    fn connect(&self, _port: &str) {
+        let mut s = self.lock();
         s.connected = true;
+        s.band_hz = Some(Self::band_hz());
+        s.frequency_readback_hz = Some(Self::power_up_hz());
         s.last_error = None;
         s.status = Some("connected".into());
     }
 
     fn disconnect(&self) {
-        let mut s = self.state.lock().unwrap();
+        let mut s = self.lock();
         s.connected = false;
         s.enabled = false;
+        s.commanded_power_w = 0.0;
+        s.tracking = TrackingStatus::default();
+        s.pulse = None;
+        s.frequency_hz = None;
+        s.frequency_readback_hz = None;
+        s.band_hz = None;
+        s.vswr = None;
+        s.forward_ratio = None;
+        s.temperature_c = None;
+        s.forward_power_w = None;
+        s.reflected_power_w = None;
+        s.pa_current_a = None;
+        s.pa_voltage_v = None;
+        s.status_word = None;
+        s.faults.clear();
         s.status = Some("disconnected".into());
+        self.physics.lock().unwrap().tracker = None;
     }
 
-    fn set_power(&self, watts: f32) {
-        let mut s = self.state.lock().unwrap();
+    fn set_power(&self, watts: f32, _ramp_w_per_s: Option<f32>) {
+        let mut s = self.lock();
         if !s.connected {
             s.last_error = Some("Microwave not connected".into());
             return;
         }
+        // Mock has no ramp: the commanded power reaches the target at once
         s.last_error = None;
-        s.power_watts = watts.max(0.0);
+        s.target_power_w = watts.max(0.0);
+        if s.enabled {
+            s.commanded_power_w = s.target_power_w;
+            s.power_watts = s.target_power_w;
+        }
     }
 
-    fn set_frequency(&self, _hz: i32) {
-        let mut s = self.state.lock().unwrap();
+    fn set_frequency(&self, hz: i32) {
+        let mut s = self.lock();
+        if !s.connected {
+            s.last_error = Some("Microwave not connected".into());
+            return;
+        }
+        let (min_hz, max_hz) = Self::band_hz();
+        if hz < min_hz || hz > max_hz {
+            s.last_error = Some(format!("{} Hz is outside the generator band", hz));
+            return;
+        }
+        // Mock generator lands exactly on the commanded frequency
+        s.last_error = None;
+        s.frequency_hz = Some(hz);
+        s.frequency_readback_hz = Some(hz);
+        let mut physics = self.physics.lock().unwrap();
+        physics.recenter(hz);
+        if let Some(ref tracker) = physics.tracker {
+            s.tracking = tracking_status(tracker, s.tracking.active, None);
+        }
+    }
+
+    fn sweep(&self, start_hz: i32, stop_hz: i32, step_hz: i32, power_w: f32) {
+        let mut s = self.lock();
+        if !s.connected {
+            s.last_error = Some("Microwave not connected".into());
+            return;
+        }
+        if s.enabled {
+            s.last_error = Some("sweep: RF is on; switch it off first".into());
+            return;
+        }
+        let frequencies = match sweep_frequencies(start_hz, stop_hz, step_hz) {
+            Ok(f) => f,
+            Err(e) => {
+                s.last_error = Some(e);
+                return;
+            }
+        };
+        // Sweep power is low and brief: measure the load as it is, without heating it
+        let mut physics = self.physics.lock().unwrap();
+        s.sweep = frequencies
+            .into_iter()
+            .map(|frequency_hz| {
+                let r = physics.generator.readings(frequency_hz, power_w);
+                SweepPoint { frequency_hz, forward_w: r.forward_w, reflected_w: r.reflected_w }
+            })
+            .collect();
+        s.sweep_best_hz = best_match(&s.sweep).map(|p| p.frequency_hz);
+        if let Some(hz) = s.sweep_best_hz {
+            s.frequency_hz = Some(hz);
+            s.frequency_readback_hz = Some(hz);
+            physics.recenter(hz);
+        }
+        s.sweep_in_progress = false;
+        s.last_error = None;
+        s.status = Some("sweep complete".into());
+    }
+
+    fn set_tracking(&self, settings: Option<TrackingSettings>) {
+        let mut s = self.lock();
         if !s.connected {
             s.last_error = Some("Microwave not connected".into());
             return;
         }
+        let mut physics = self.physics.lock().unwrap();
+        let Some(settings) = settings else {
+            physics.tracker = None;
+            s.tracking = TrackingStatus::default();
+            s.last_error = None;
+            return;
+        };
+        let Some(center_hz) = s.frequency_hz else {
+            s.last_error = Some("tracking: set a frequency first".into());
+            return;
+        };
+        let tracker = Tracker::new(settings, center_hz, Self::band_hz());
+        s.tracking = tracking_status(&tracker, s.enabled, None);
+        s.last_error = None;
+        physics.tracker = Some(tracker);
+        physics.tracking_elapsed_s = 0.0;
+    }
+
+    fn set_pulse(&self, settings: Option<PulseSettings>) {
+        let mut s = self.lock();
+        if !s.connected {
+            s.last_error = Some("Microwave not connected".into());
+            return;
+        }
+        if let Some(problems) = settings.map(|p| p.validate()).filter(|p| !p.is_empty()) {
+            s.last_error = Some(format!("pulse: {}", problems.join(", ")));
+            return;
+        }
+        s.last_error = None;
+        s.pulse = settings;
+    }
+
+    fn clear_errors(&self) {
+        let mut s = self.lock();
+        if !s.connected {
+            s.last_error = Some("Microwave not connected".into());
+            return;
+        }
+        s.faults.clear();
+        s.status_word = Some(0);
         s.last_error = None;
-        // Mock doesn't actually track frequency
     }
 
     fn rf_on(&self) {
-        let mut s = self.state.lock().unwrap();
+        let mut s = self.lock();
         if !s.connected {
             s.last_error = Some("Microwave not connected".into());
             return;
         }
+        if let Some(fault) = s.faults.iter().find(|f| f.shuts_down_rf()) {
+            s.last_error = Some(format!("{}; clear generator errors first", fault.description()));
+            return;
+        }
         s.last_error = None;
         s.enabled = true;
+        s.commanded_power_w = s.target_power_w;
+        s.power_watts = s.target_power_w;
         s.status = Some("RF on".into());
     }
 
     fn rf_off(&self) {
-        let mut s = self.state.lock().unwrap();
+        let mut s = self.lock();
         if !s.connected {
             s.last_error = Some("Microwave not connected".into());
             return;
         }
         s.last_error = None;
         s.enabled = false;
+        s.commanded_power_w = 0.0;
         s.status = Some("RF off".into());
     }
 
     fn state(&self) -> MicrowaveState {
-        self.state.lock().unwrap().clone()
+        self.lock().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::config::GeneratorFault;
+
+    #[test]
+    fn test_sweep_then_track_holds_match_while_load_heats() {
+        let mw = MockMicrowave::new();
+        mw.connect("mock");
+        // Power-up frequency is off resonance
+        mw.set_power(100.0, None);
+        mw.rf_on();
+        let s = mw.state();
+        assert!(s.vswr.unwrap() > 2.0);
+        assert_eq!(s.faults, vec![GeneratorFault::ReflectedPower { shutdown: false }]);
+        mw.rf_off();
+        mw.clear_errors();
+
+        mw.sweep(902_000_000, 928_000_000, 250_000, 5.0);
+        let best_hz = mw.state().sweep_best_hz.unwrap();
+        mw.set_tracking(Some(TrackingSettings { step_hz: 50_000, interval_ms: 500, span_hz: 5_000_000 }));
+        mw.rf_on();
+        assert!(mw.state().vswr.unwrap() < 1.5);
+
+        // Ten simulated minutes at 100 W: the resonance drifts and tracking follows it
+        let mut s = mw.state.lock().unwrap();
+        let mut physics = mw.physics.lock().unwrap();
+        for _ in 0..600 {
+            physics.advance(&mut s, 1.0);
+        }
+        assert!(physics.generator.load_temperature_c > 100.0);
+        assert!(s.frequency_readback_hz.unwrap() < best_hz);
+        assert!(s.vswr.unwrap() < 1.5, "vswr {:?}", s.vswr);
+        assert!(s.forward_ratio.unwrap() > 0.9);
+        assert!(s.tracking.active);
+        assert!(s.temperature_c.unwrap() > 25.0);
+        assert!(s.faults.is_empty());
+    }
+
+    #[test]
+    fn test_sweep_refused_while_rf_on() {
+        let mw = MockMicrowave::new();
+        mw.connect("mock");
+        mw.set_power(5.0, None);
+        mw.rf_on();
+        mw.sweep(902_000_000, 928_000_000, 250_000, 5.0);
+        let s = mw.state();
+        assert!(s.sweep.is_empty());
+        assert!(s.last_error.unwrap().contains("RF is on"));
+    }
+
+    #[test]
+    fn test_reflected_power_protection_latches_until_cleared() {
+        let mw = MockMicrowave::new();
+        mw.connect("mock");
+        mw.set_frequency(927_000_000);
+        mw.set_power(200.0, None);
+        mw.rf_on();
+        let s = mw.state();
+        assert!(!s.enabled);
+        assert_eq!(s.faults, vec![GeneratorFault::ReflectedPower { shutdown: true }]);
+
+        // Refused until the fault is cleared
+        mw.rf_on();
+        assert!(!mw.state().enabled);
+        mw.clear_errors();
+        mw.set_power(20.0, None);
+        mw.rf_on();
+        let s = mw.state();
+        assert!(s.enabled && s.faults.is_empty());
     }
 }
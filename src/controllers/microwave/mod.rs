@@ -1,4 +1,6 @@
-pub mod mock;
+// The mock carries a simulated generator; only build it where it is used
+#[cfg(any(feature="mock", test))] pub mod mock;
+#[cfg(any(feature="mock", test))] pub mod sim;
 #[cfg(feature="mock")] pub use mock::*;
 pub mod client;
 #[cfg(feature="real")] pub use client::*;
@@ -0,0 +1,179 @@
+/*
+ * Simulated generator and load behind the mock microwave controller.
+ *
+ * The load is a single resonance: reflection is lowest at the resonant frequency
+ * and rises with a Lorentzian profile either side of it. Power that is not
+ * reflected heats the load, and the resonance drifts down as the load warms, so a
+ * fixed frequency slowly detunes at high power. The PA heats with its own
+ * dissipation plus the reflected power it has to absorb, and cools towards
+ * ambient when RF is off.
+ *
+ * The generator's protection flags over-temperature and high reflected power,
+ * and shuts RF down past the hard limits.
+ *
+ * Deterministic and free of I/O; `mock.rs` advances it with wall-clock time.
+ */
+
+use crate::drivers::microwave::faults::{
+    STATUS_PA_TEMP_HIGH, STATUS_PA_TEMP_SHUTDOWN, STATUS_REFLECTED_HIGH, STATUS_REFLECTED_SHUTDOWN,
+};
+
+pub const AMBIENT_C: f32 = 25.0;
+/// Supply voltage the simulated PA reports.
+pub const PA_SUPPLY_V: f32 = 28.0;
+
+// Load
+const RESONANCE_HALF_WIDTH_HZ: f32 = 2_000_000.0;
+const RESONANCE_SHIFT_HZ_PER_C: f32 = -20_000.0;
+const MIN_REFLECTION: f32 = 0.02;             // reflected / forward at resonance
+const LOAD_HEAT_CAPACITY_J_PER_C: f32 = 50.0;
+const LOAD_LOSS_W_PER_C: f32 = 0.5;           // time constant 100 s
+// PA
+const PA_EFFICIENCY: f32 = 0.5;
+const PA_IDLE_A: f32 = 0.5;
+const PA_HEAT_CAPACITY_J_PER_C: f32 = 200.0;
+const PA_LOSS_W_PER_C: f32 = 5.0;             // time constant 40 s
+
+// Protection thresholds of the simulated generator
+pub const PA_TEMP_WARNING_C: f32 = 60.0;
+pub const PA_TEMP_SHUTDOWN_C: f32 = 80.0;
+pub const REFLECTED_WARNING_W: f32 = 50.0;
+pub const REFLECTED_SHUTDOWN_W: f32 = 100.0;
+
+/// Longest integration step; larger intervals are split.
+pub const MAX_STEP_S: f32 = 0.1;
+
+/// What the generator's read-back commands would report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Readings {
+    pub forward_w: f32,
+    pub reflected_w: f32,
+    pub pa_temperature_c: f32,
+    pub pa_current_a: f32,
+    pub pa_voltage_v: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulatedGenerator {
+    pub cold_resonance_hz: f32,  // resonance of the load at ambient
+    pub load_temperature_c: f32,
+    pub pa_temperature_c: f32,
+}
+
+impl Readings {
+    /// Status bits the generator's protection would raise for these readings.
+    pub fn status_word(&self) -> u32 {
+        let mut word = 0;
+        if self.pa_temperature_c >= PA_TEMP_SHUTDOWN_C {
+            word |= STATUS_PA_TEMP_SHUTDOWN;
+        } else if self.pa_temperature_c >= PA_TEMP_WARNING_C {
+            word |= STATUS_PA_TEMP_HIGH;
+        }
+        if self.reflected_w >= REFLECTED_SHUTDOWN_W {
+            word |= STATUS_REFLECTED_SHUTDOWN;
+        } else if self.reflected_w >= REFLECTED_WARNING_W {
+            word |= STATUS_REFLECTED_HIGH;
+        }
+        word
+    }
+}
+
+impl SimulatedGenerator {
+    pub fn new(cold_resonance_hz: i32) -> Self {
+        Self {
+            cold_resonance_hz: cold_resonance_hz as f32,
+            load_temperature_c: AMBIENT_C,
+            pa_temperature_c: AMBIENT_C,
+        }
+    }
+
+    /// Current resonant frequency, shifted by the load temperature.
+    pub fn resonance_hz(&self) -> f32 {
+        self.cold_resonance_hz + RESONANCE_SHIFT_HZ_PER_C * (self.load_temperature_c - AMBIENT_C)
+    }
+
+    /// Share of forward power reflected at `frequency_hz`.
+    pub fn reflection(&self, frequency_hz: i32) -> f32 {
+        let detune = (frequency_hz as f32 - self.resonance_hz()) / RESONANCE_HALF_WIDTH_HZ;
+        1.0 - (1.0 - MIN_REFLECTION) / (1.0 + detune * detune)
+    }
+
+    /// Read-back values while driving `forward_w` at `frequency_hz`.
+    pub fn readings(&self, frequency_hz: i32, forward_w: f32) -> Readings {
+        let forward_w = forward_w.max(0.0);
+        let idle = if forward_w > 0.0 { PA_IDLE_A } else { 0.0 };
+        Readings {
+            forward_w,
+            reflected_w: forward_w * self.reflection(frequency_hz),
+            pa_temperature_c: self.pa_temperature_c,
+            pa_current_a: idle + forward_w / PA_EFFICIENCY / PA_SUPPLY_V,
+            pa_voltage_v: PA_SUPPLY_V,
+        }
+    }
+
+    /// Integrate `dt_s` seconds of driving `forward_w` at `frequency_hz`.
+    /// `duty` is the share of time RF is on (1.0 for continuous wave).
+    pub fn advance(&mut self, dt_s: f32, frequency_hz: i32, forward_w: f32, duty: f32) {
+        let mut remaining = dt_s.max(0.0);
+        while remaining > 0.0 {
+            let dt = remaining.min(MAX_STEP_S);
+            remaining -= dt;
+
+            let average_w = forward_w.max(0.0) * duty.clamp(0.0, 1.0);
+            let reflected_w = average_w * self.reflection(frequency_hz);
+            let absorbed_w = average_w - reflected_w;
+            let pa_dissipation_w = average_w / PA_EFFICIENCY - average_w + reflected_w;
+
+            self.load_temperature_c += dt
+                * (absorbed_w - LOAD_LOSS_W_PER_C * (self.load_temperature_c - AMBIENT_C))
+                / LOAD_HEAT_CAPACITY_J_PER_C;
+            self.pa_temperature_c += dt
+                * (pa_dissipation_w - PA_LOSS_W_PER_C * (self.pa_temperature_c - AMBIENT_C))
+                / PA_HEAT_CAPACITY_J_PER_C;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reflection_minimum_at_resonance() {
+        let sim = SimulatedGenerator::new(915_000_000);
+        assert!((sim.reflection(915_000_000) - MIN_REFLECTION).abs() < 1e-6);
+        // One half-width off resonance: half the absorbed share is lost
+        let off = sim.reflection(917_000_000);
+        assert!((off - (1.0 - (1.0 - MIN_REFLECTION) / 2.0)).abs() < 1e-3);
+        assert!(sim.reflection(925_000_000) > off);
+
+        let r = sim.readings(915_000_000, 100.0);
+        assert!((r.reflected_w - 2.0).abs() < 1e-3);
+        assert_eq!(sim.readings(915_000_000, 0.0).pa_current_a, 0.0);
+
+        // Far off resonance at high power the reflected-power protection trips
+        assert_eq!(r.status_word(), 0);
+        assert_eq!(sim.readings(925_000_000, 200.0).status_word(), STATUS_REFLECTED_SHUTDOWN);
+    }
+
+    #[test]
+    fn test_heating_detunes_load() {
+        let mut sim = SimulatedGenerator::new(915_000_000);
+        let cold = sim.reflection(915_000_000);
+        sim.advance(60.0, 915_000_000, 100.0, 1.0);
+        assert!(sim.load_temperature_c > 50.0);
+        assert!(sim.pa_temperature_c > AMBIENT_C);
+        assert!(sim.resonance_hz() < 915_000_000.0);
+        assert!(sim.reflection(915_000_000) > cold);
+
+        // Pulsed at 10 % heats far less than CW
+        let mut pulsed = SimulatedGenerator::new(915_000_000);
+        pulsed.advance(60.0, 915_000_000, 100.0, 0.1);
+        assert!(pulsed.load_temperature_c < sim.load_temperature_c);
+
+        // RF off: both cool back towards ambient
+        let hot = (sim.load_temperature_c, sim.pa_temperature_c);
+        sim.advance(600.0, 915_000_000, 0.0, 1.0);
+        assert!(sim.load_temperature_c < hot.0 && sim.pa_temperature_c < hot.1);
+    }
+}
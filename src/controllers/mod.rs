@@ -4,7 +4,7 @@ pub mod arduino;
 
 // Trait interfaces for non-blocking UI calls
 // Command methods enqueue work; query methods return cached state.
-use crate::config::config::{DuetState, MicrowaveState, ArduinoState};
+use crate::config::config::{DuetState, MicrowaveState, ArduinoState, PidGains, PulseSettings, TrackingSettings};
 
 /// Controllers must be non-blocking:
 /// - Command methods enqueue work and return immediately.
@@ -23,13 +23,22 @@ pub trait DuetController: Send + Sync {
 
 /// Same non-blocking rules apply to the Microwave controller.
 pub trait MicrowaveController: Send + Sync {
-	// Toggle connection state (no I/O in mock)
-	fn connect(&self);
+	// Open the given serial port (no I/O in mock)
+	fn connect(&self, port: &str);
 	fn disconnect(&self);
-	// Fire-and-forget: set microwave power in watts; returns immediately.
-	fn set_power(&self, watts: f32);
+	// Fire-and-forget: set the target power in watts; returns immediately.
+	// The driver slews to it at `ramp_w_per_s` (None: the configured default rate).
+	fn set_power(&self, watts: f32, ramp_w_per_s: Option<f32>);
 	// Fire-and-forget: set microwave frequency in Hz; returns immediately.
 	fn set_frequency(&self, hz: i32);
+	// Fire-and-forget: sweep frequency at low power and park at the best match.
+	fn sweep(&self, start_hz: i32, stop_hz: i32, step_hz: i32, power_w: f32);
+	// Fire-and-forget: enable (Some) or disable (None) resonance tracking while RF is on.
+	fn set_tracking(&self, settings: Option<TrackingSettings>);
+	// Fire-and-forget: pulsed RF with the given period and duty cycle (Some) or continuous wave (None).
+	fn set_pulse(&self, settings: Option<PulseSettings>);
+	// Fire-and-forget: clear the generator's latched error flags.
+	fn clear_errors(&self);
 	// RF control: turn RF on/off
 	fn rf_on(&self);
 	fn rf_off(&self);
@@ -39,13 +48,23 @@ pub trait MicrowaveController: Send + Sync {
 
 /// Arduino pneumatic pressure controller (non-blocking)
 pub trait ArduinoController: Send + Sync {
-	// Toggle connection state (no I/O in mock)
-	fn connect(&self);
+	// Open the given serial port (no I/O in mock)
+	fn connect(&self, port: &str);
 	fn disconnect(&self);
+	// The rest address one pressure channel, by index into `ArduinoState::channels`.
 	// Enable/disable pressure control
-	fn enable(&self, enable: bool);
+	fn enable(&self, channel: usize, enable: bool);
 	// Fire-and-forget: set pressure setpoint in PSI; returns immediately.
-	fn set_pressure_setpoint(&self, psi: f32);
+	fn set_pressure_setpoint(&self, channel: usize, psi: f32);
+	// Fire-and-forget: slew setpoint changes at `psi_per_s` (0: step at once).
+	fn set_ramp_rate(&self, channel: usize, psi_per_s: f32);
+	// Fire-and-forget: read the PID gains back into the state.
+	fn get_pid(&self, channel: usize);
+	// Fire-and-forget: write the PID gains.
+	fn set_pid(&self, channel: usize, gains: PidGains);
+	// Fire-and-forget: step the setpoint by `step_psi`, record the response and
+	// report suggested gains in the state.
+	fn autotune(&self, channel: usize, step_psi: f32);
 	// Snapshot of cached Arduino state.
 	fn state(&self) -> ArduinoState;
 }
@@ -0,0 +1,114 @@
+/*
+ * Firmware identification for the pneumatic controller.
+ *
+ * After the protocol version is settled the driver sends "ID". The firmware
+ * answers with one line of KEY=VALUE fields (in a v2 ACK payload, or as a plain
+ * line on v1):
+ *
+ *   "ID NAME=meteorite-pneumatics VER=1.2.0 CMDS=SET_PSI,ENABLE,READ RANGE=0-100 SENSOR=4-20mA"
+ *
+ * Firmware driving more than one regulator adds "CH=<n>"; without it there is one.
+ *
+ * Only our regulator firmware with a supported major version and the commands
+ * the driver relies on may be enabled; anything else on the port is refused.
+ */
+
+use crate::config::config::FirmwareInfo;
+
+/// Firmware name our regulator reports.
+pub const FIRMWARE_NAME: &str = "meteorite-pneumatics";
+/// Major firmware version this driver speaks.
+pub const SUPPORTED_MAJOR: u32 = 1;
+/// Commands the driver sends once enabled.
+pub const REQUIRED_COMMANDS: [&str; 3] = ["SET_PSI", "ENABLE", "READ"];
+
+/// Parse an identify reply.
+pub fn parse_identity(line: &str) -> Result<FirmwareInfo, String> {
+    let fields = line.trim().strip_prefix("ID ").ok_or("not an identify reply")?;
+    let field = |key: &str| {
+        fields
+            .split_whitespace()
+            .find_map(|token| token.strip_prefix(key)?.strip_prefix('='))
+            .ok_or(format!("identify reply has no {}", key))
+    };
+
+    let range = field("RANGE")?;
+    let (min, max) = range.split_once('-').ok_or(format!("bad RANGE {:?}", range))?;
+    let pressure_range_psi = match (min.parse::<f32>(), max.parse::<f32>()) {
+        (Ok(min), Ok(max)) if min < max => (min, max),
+        _ => return Err(format!("bad RANGE {:?}", range)),
+    };
+
+    let channels = match field("CH") {
+        Ok(count) => count.parse::<usize>().ok().filter(|n| *n > 0).ok_or(format!("bad CH {:?}", count))?,
+        Err(_) => 1,
+    };
+
+    Ok(FirmwareInfo {
+        name: field("NAME")?.to_string(),
+        version: field("VER")?.to_string(),
+        commands: field("CMDS")?.split(',').filter(|c| !c.is_empty()).map(str::to_string).collect(),
+        pressure_range_psi,
+        sensor: field("SENSOR")?.to_string(),
+        channels,
+    })
+}
+
+/// Whether the driver may enable this firmware with `channels` configured
+/// pressure channels; Err says why not.
+pub fn check_compatible(info: &FirmwareInfo, channels: usize) -> Result<(), String> {
+    if info.name != FIRMWARE_NAME {
+        return Err(format!("unknown firmware {:?}", info.name));
+    }
+    let major = info.version.split('.').next().and_then(|m| m.parse::<u32>().ok());
+    if major != Some(SUPPORTED_MAJOR) {
+        return Err(format!("firmware version {} not supported (need {}.x)", info.version, SUPPORTED_MAJOR));
+    }
+    let missing: Vec<&str> = REQUIRED_COMMANDS
+        .into_iter()
+        .filter(|required| !info.commands.iter().any(|c| c == required))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("firmware lacks {}", missing.join(", ")));
+    }
+    if channels > info.channels {
+        return Err(format!("{} pressure channels configured, firmware has {}", channels, info.channels));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_check_identity() {
+        let info = parse_identity(
+            "ID NAME=meteorite-pneumatics VER=1.2.0 CMDS=SET_PSI,ENABLE,READ,WDT RANGE=0-100 SENSOR=4-20mA",
+        )
+        .unwrap();
+        assert_eq!(info.name, FIRMWARE_NAME);
+        assert_eq!(info.version, "1.2.0");
+        assert_eq!(info.commands.len(), 4);
+        assert_eq!(info.pressure_range_psi, (0.0, 100.0));
+        assert_eq!(info.sensor, "4-20mA");
+        assert_eq!(info.channels, 1);
+        assert_eq!(check_compatible(&info, 1), Ok(()));
+        assert_eq!(check_compatible(&info, 2), Err("2 pressure channels configured, firmware has 1".into()));
+
+        let other = FirmwareInfo { name: "grbl".into(), ..info.clone() };
+        assert!(check_compatible(&other, 1).is_err());
+        let newer = FirmwareInfo { version: "2.0.0".into(), ..info.clone() };
+        assert!(check_compatible(&newer, 1).is_err());
+        let limited = FirmwareInfo { commands: vec!["READ".into()], ..info };
+        assert_eq!(check_compatible(&limited, 1), Err("firmware lacks SET_PSI, ENABLE".into()));
+
+        let dual = parse_identity("ID NAME=x VER=1.0 CMDS=READ RANGE=0-100 SENSOR=s CH=2").unwrap();
+        assert_eq!(dual.channels, 2);
+        assert!(parse_identity("ID NAME=x VER=1.0 CMDS=READ RANGE=0-100 SENSOR=s CH=0").is_err());
+
+        assert!(parse_identity("ID NAME=x VER=1.0 CMDS=READ RANGE=100-0 SENSOR=s").is_err());
+        assert!(parse_identity("ID NAME=x VER=1.0").is_err());
+        assert!(parse_identity("PSI=12.0").is_err());
+    }
+}
@@ -1 +1,7 @@
 pub mod task;
+pub mod protocol;
+pub mod identity;
+pub mod tuning;
+pub mod stream;
+#[cfg(test)]
+pub mod sim_firmware;
@@ -0,0 +1,409 @@
+/*
+ * Framed Arduino protocol (v2) for the pneumatic controller.
+ *
+ * v1 is bare newline-terminated ASCII: a corrupted line is acted on or silently
+ * dropped. v2 wraps the same commands in a checked frame:
+ *
+ *   host:   "@<seq> <command>*<crc>\n"              e.g. "@7 SET_PSI 30.00*1A2B"
+ *   device: "@<seq> ACK[ <payload>]*<crc>\n"         command applied (READ: payload is telemetry)
+ *           "@<seq> NAK[ <reason>]*<crc>\n"          command rejected; reason "CRC" means it arrived corrupted
+ *           "@0 <payload>*<crc>\n"                   unsolicited telemetry or events ("WDT TRIP")
+ *
+ * <seq> is 1..=65535 (0 is reserved for unsolicited frames) and <crc> is the
+ * CRC-16/CCITT-FALSE of the text between '@' and '*', as four hex digits.
+ *
+ * The host keeps one frame in flight (stop-and-wait). A frame is resent with the
+ * same sequence number when no ACK arrives in time or the device NAKs it as
+ * corrupted, so firmware can ignore a duplicate it has already applied.
+ *
+ * Version negotiation: right after connecting the host sends "PROTO 2" framed.
+ * v2 firmware ACKs it; v1 firmware ignores it, and after the retries run out
+ * the host falls back to v1 for the rest of the session.
+ *
+ * Watchdog: "WDT <ms>" arms the firmware watchdog (0 disarms) and "HB" is the
+ * host heartbeat. Armed firmware that hears nothing from the host for <ms>
+ * disables pressure control, vents, and reports "WDT TRIP".
+ *
+ * Tuning: "RAMP <psi/s>" sets the setpoint slew rate, "PID?" reads the gains
+ * (ACK payload "PID KP=.. KI=.. KD=..") and "PID <kp> <ki> <kd>" writes them.
+ * "TUNE <step psi>" starts a step-response autotune; the result arrives later as
+ * an event (see tuning.rs).
+ *
+ * Streaming: "STREAM <hz>" makes the firmware push stamped telemetry as events
+ * (0 stops it; see stream.rs).
+ *
+ * Channels: firmware with more than one regulator reports "CH=<n>" in its
+ * identify reply. Per-channel commands (ENABLE, SET_PSI, READ, RAMP, PID, TUNE)
+ * then take a trailing "CH=<n>" field, and telemetry and PID replies carry one.
+ * Channel 0 goes without it, so single-channel firmware sees the v1 commands.
+ *
+ * This module holds the framing and the retry bookkeeping; `task.rs` does the I/O.
+ */
+
+use std::collections::VecDeque;
+
+use tokio::time::{Duration, Instant};
+
+use crate::config::config::PidGains;
+
+pub const PROTOCOL_V1: u8 = 1;
+pub const PROTOCOL_V2: u8 = 2;
+
+/// Time to wait for an ACK before resending.
+pub const ACK_TIMEOUT: Duration = Duration::from_millis(250);
+/// Shortest firmware watchdog timeout. The link is stop-and-wait, so a lost ACK
+/// holds every other frame, heartbeats included, back for `ACK_TIMEOUT`.
+pub const MIN_WATCHDOG_MS: u32 = 4 * ACK_TIMEOUT.as_millis() as u32;
+
+/// CRC-16/CCITT-FALSE (poly 0x1021, init 0xFFFF), cheap to compute on the Arduino.
+pub fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in bytes {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// Frame `body` with sequence number `seq`, newline included.
+pub fn encode(seq: u16, body: &str) -> String {
+    let inner = format!("{} {}", seq, body);
+    format!("@{}*{:04X}\n", inner, crc16(inner.as_bytes()))
+}
+
+/// A checked frame received from the device.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Frame {
+    Ack { seq: u16, payload: String },
+    Nak { seq: u16, reason: String },
+    Event { payload: String },
+}
+
+/// Check a frame and split it into sequence number and body.
+pub fn unwrap_frame(line: &str) -> Result<(u16, &str), String> {
+    let line = line.trim();
+    let inner_and_crc = line.strip_prefix('@').ok_or("not a frame")?;
+    let (inner, crc_text) = inner_and_crc.rsplit_once('*').ok_or("frame without CRC")?;
+    let crc = u16::from_str_radix(crc_text, 16).map_err(|_| format!("bad CRC field {:?}", crc_text))?;
+    if crc != crc16(inner.as_bytes()) {
+        return Err(format!("CRC mismatch in {:?}", line));
+    }
+    let (seq_text, body) = inner.split_once(' ').unwrap_or((inner, ""));
+    let seq: u16 = seq_text.parse().map_err(|_| format!("bad sequence number {:?}", seq_text))?;
+    Ok((seq, body))
+}
+
+/// Decode one received line; Err for lines that are not intact v2 frames.
+pub fn decode(line: &str) -> Result<Frame, String> {
+    let (seq, rest) = unwrap_frame(line)?;
+    let (kind, text) = rest.split_once(' ').unwrap_or((rest, ""));
+    match kind {
+        _ if seq == 0 => Ok(Frame::Event { payload: rest.to_string() }),
+        "ACK" => Ok(Frame::Ack { seq, payload: text.to_string() }),
+        "NAK" => Ok(Frame::Nak { seq, reason: text.to_string() }),
+        _ => Err(format!("unknown reply {:?}", kind)),
+    }
+}
+
+/// Channel a telemetry line or reply is about; lines without "CH=" are channel 0.
+pub fn line_channel(line: &str) -> usize {
+    line.split_whitespace()
+        .find_map(|token| token.strip_prefix("CH=")?.parse::<usize>().ok())
+        .unwrap_or(0)
+}
+
+/// A command to the pneumatic controller. The first field of per-channel
+/// requests is the channel number.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Request {
+    Negotiate,
+    Identify,
+    Enable(usize, bool),
+    SetPressure(usize, f32),
+    Read(usize),
+    SetWatchdog(u32),
+    Heartbeat,
+    SetRampRate(usize, f32),
+    GetPid(usize),
+    SetPid(usize, PidGains),
+    Autotune(usize, f32),
+    Stream(u32),
+}
+
+impl Request {
+    /// Command text, identical in v1 and inside a v2 frame.
+    pub fn command(&self) -> String {
+        let command = match self {
+            Request::Negotiate => format!("PROTO {}", PROTOCOL_V2),
+            Request::Identify => "ID".to_string(),
+            Request::Enable(_, enable) => format!("ENABLE {}", if *enable { 1 } else { 0 }),
+            Request::SetPressure(_, psi) => format!("SET_PSI {:.2}", psi),
+            Request::Read(_) => "READ".to_string(),
+            Request::SetWatchdog(ms) => format!("WDT {}", ms),
+            Request::Heartbeat => "HB".to_string(),
+            Request::SetRampRate(_, psi_per_s) => format!("RAMP {:.2}", psi_per_s),
+            Request::GetPid(_) => "PID?".to_string(),
+            Request::SetPid(_, gains) => format!("PID {:.4} {:.4} {:.4}", gains.kp, gains.ki, gains.kd),
+            Request::Autotune(_, step_psi) => format!("TUNE {:.2}", step_psi),
+            Request::Stream(hz) => format!("STREAM {}", hz),
+        };
+        match self.channel() {
+            Some(channel) if channel > 0 => format!("{} CH={}", command, channel),
+            _ => command,
+        }
+    }
+
+    /// Pressure channel the request addresses; None for controller-wide ones.
+    pub fn channel(&self) -> Option<usize> {
+        match *self {
+            Request::Enable(channel, _)
+            | Request::SetPressure(channel, _)
+            | Request::Read(channel)
+            | Request::SetRampRate(channel, _)
+            | Request::GetPid(channel)
+            | Request::SetPid(channel, _)
+            | Request::Autotune(channel, _) => Some(channel),
+            Request::Negotiate
+            | Request::Identify
+            | Request::SetWatchdog(_)
+            | Request::Heartbeat
+            | Request::Stream(_) => None,
+        }
+    }
+
+    /// Sends before giving up. A lost READ or heartbeat is replaced by the next one.
+    fn max_attempts(&self) -> u32 {
+        match self {
+            Request::Read(_) | Request::Heartbeat => 1,
+            Request::Negotiate => 2,
+            Request::Identify
+            | Request::Enable(..)
+            | Request::SetPressure(..)
+            | Request::SetWatchdog(_)
+            | Request::SetRampRate(..)
+            | Request::GetPid(_)
+            | Request::SetPid(..)
+            | Request::Autotune(..)
+            | Request::Stream(_) => 3,
+        }
+    }
+}
+
+/// How a framed request ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Acked(Request, String),   // with the ACK payload
+    Failed(Request, String),  // with the reason
+}
+
+/// What the task has to do after feeding the framing an event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Idle,
+    Send(String),
+    Done(Outcome),
+}
+
+#[derive(Debug, Clone)]
+struct InFlight {
+    seq: u16,
+    request: Request,
+    attempts: u32,
+    deadline: Instant,
+}
+
+/// Stop-and-wait sender: one frame in flight, the rest queued behind it.
+#[derive(Debug, Default)]
+pub struct Framing {
+    last_seq: u16,
+    queue: VecDeque<Request>,
+    in_flight: Option<InFlight>,
+}
+
+impl Framing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a request. A newer setpoint replaces a queued one for the same
+    /// channel and a READ or heartbeat is never queued twice, so a slow link
+    /// does not build up a backlog.
+    pub fn push(&mut self, request: Request) {
+        let duplicate = self.queue.iter_mut().find(|queued| match (**queued, request) {
+            (Request::SetPressure(a, _), Request::SetPressure(b, _)) | (Request::Read(a), Request::Read(b)) => a == b,
+            (Request::Heartbeat, Request::Heartbeat) => true,
+            _ => false,
+        });
+        match duplicate {
+            Some(queued) => *queued = request,
+            None => self.queue.push_back(request),
+        }
+    }
+
+    /// Whether `request` is queued or waiting for its ACK.
+    pub fn is_pending(&self, request: Request) -> bool {
+        self.is_pending_where(|r| r == request)
+    }
+
+    /// Whether any queued or unacknowledged request matches.
+    pub fn is_pending_where(&self, predicate: impl Fn(Request) -> bool) -> bool {
+        self.queue.iter().any(|r| predicate(*r)) || self.in_flight.as_ref().is_some_and(|f| predicate(f.request))
+    }
+
+    /// Requests not yet sent, oldest first.
+    pub fn drain_queue(&mut self) -> Vec<Request> {
+        self.queue.drain(..).collect()
+    }
+
+    /// ACK deadline of the frame in flight.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.in_flight.as_ref().map(|f| f.deadline)
+    }
+
+    /// Next frame to write, if the line is free and something is queued.
+    pub fn next_frame(&mut self, now: Instant) -> Option<String> {
+        if self.in_flight.is_some() {
+            return None;
+        }
+        let request = self.queue.pop_front()?;
+        self.last_seq = self.last_seq.checked_add(1).unwrap_or(1);
+        let seq = self.last_seq;
+        self.in_flight = Some(InFlight { seq, request, attempts: 1, deadline: now + ACK_TIMEOUT });
+        Some(encode(seq, &request.command()))
+    }
+
+    /// The ACK deadline passed: resend, or give up.
+    pub fn on_timeout(&mut self, now: Instant) -> Step {
+        self.retry_or_fail(now, "no ACK")
+    }
+
+    /// A frame arrived from the device.
+    pub fn on_frame(&mut self, frame: Frame, now: Instant) -> Step {
+        let Some(ref in_flight) = self.in_flight else {
+            return Step::Idle;
+        };
+        match frame {
+            Frame::Ack { seq, payload } if seq == in_flight.seq => {
+                let request = in_flight.request;
+                self.in_flight = None;
+                Step::Done(Outcome::Acked(request, payload))
+            }
+            Frame::Nak { seq, reason } if seq == in_flight.seq => {
+                if reason == "CRC" {
+                    return self.retry_or_fail(now, "corrupted in transit");
+                }
+                let request = in_flight.request;
+                self.in_flight = None;
+                Step::Done(Outcome::Failed(request, format!("rejected: {}", reason)))
+            }
+            // Late reply to an earlier attempt, or an event
+            _ => Step::Idle,
+        }
+    }
+
+    fn retry_or_fail(&mut self, now: Instant, reason: &str) -> Step {
+        let Some(ref mut in_flight) = self.in_flight else {
+            return Step::Idle;
+        };
+        if in_flight.attempts < in_flight.request.max_attempts() {
+            in_flight.attempts += 1;
+            in_flight.deadline = now + ACK_TIMEOUT;
+            return Step::Send(encode(in_flight.seq, &in_flight.request.command()));
+        }
+        let failed = Outcome::Failed(
+            in_flight.request,
+            format!("{} after {} attempt(s)", reason, in_flight.attempts),
+        );
+        self.in_flight = None;
+        Step::Done(failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Device-side encoding of a reply.
+    fn reply(seq: u16, body: &str) -> String {
+        encode(seq, body)
+    }
+
+    #[test]
+    fn test_frame_round_trip_and_corruption() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+        let frame = encode(7, "SET_PSI 30.00");
+        assert!(frame.starts_with("@7 SET_PSI 30.00*") && frame.ends_with('\n'));
+
+        assert_eq!(decode(&reply(7, "ACK")), Ok(Frame::Ack { seq: 7, payload: String::new() }));
+        assert_eq!(
+            decode(&reply(3, "ACK P PSI=12.5 MA=8.0 OK=1")),
+            Ok(Frame::Ack { seq: 3, payload: "P PSI=12.5 MA=8.0 OK=1".into() })
+        );
+        assert_eq!(decode(&reply(4, "NAK CRC")), Ok(Frame::Nak { seq: 4, reason: "CRC".into() }));
+        assert_eq!(decode(&reply(0, "P PSI=1.0")), Ok(Frame::Event { payload: "P PSI=1.0".into() }));
+
+        // One flipped digit in the payload is caught
+        let corrupted = reply(7, "ACK P PSI=12.5").replace("12.5", "42.5");
+        assert!(decode(&corrupted).is_err());
+        assert!(decode("PSI=12.5").is_err());
+    }
+
+    #[test]
+    fn test_retry_then_fail_and_nak() {
+        let t0 = Instant::now();
+        let mut framing = Framing::new();
+        framing.push(Request::Enable(0, true));
+        framing.push(Request::SetPressure(0, 10.0));
+        framing.push(Request::SetPressure(0, 20.0));  // replaces the queued 10.0
+
+        let first = framing.next_frame(t0).unwrap();
+        assert_eq!(first, encode(1, "ENABLE 1"));
+        assert_eq!(framing.next_frame(t0), None, "one frame in flight");
+
+        // Lost twice, resent with the same sequence number, then ACKed
+        assert_eq!(framing.on_timeout(t0 + ACK_TIMEOUT), Step::Send(first.clone()));
+        assert_eq!(framing.on_timeout(t0 + ACK_TIMEOUT * 2), Step::Send(first));
+        assert_eq!(framing.on_frame(Frame::Ack { seq: 9, payload: String::new() }, t0), Step::Idle);
+        assert_eq!(
+            framing.on_frame(Frame::Ack { seq: 1, payload: String::new() }, t0),
+            Step::Done(Outcome::Acked(Request::Enable(0, true), String::new()))
+        );
+
+        // Corrupted in transit: resent; never acknowledged: fails after 3 attempts
+        assert_eq!(framing.next_frame(t0), Some(encode(2, "SET_PSI 20.00")));
+        assert!(matches!(framing.on_frame(Frame::Nak { seq: 2, reason: "CRC".into() }, t0), Step::Send(_)));
+        assert!(matches!(framing.on_timeout(t0), Step::Send(_)));
+        assert!(matches!(
+            framing.on_timeout(t0),
+            Step::Done(Outcome::Failed(Request::SetPressure(0, _), _))
+        ));
+
+        // Any other NAK fails at once
+        framing.push(Request::Enable(0, false));
+        framing.next_frame(t0).unwrap();
+        assert!(matches!(
+            framing.on_frame(Frame::Nak { seq: 3, reason: "RANGE".into() }, t0),
+            Step::Done(Outcome::Failed(Request::Enable(0, false), _))
+        ));
+        assert_eq!(framing.deadline(), None);
+    }
+
+    #[test]
+    fn test_channel_addressing() {
+        assert_eq!(Request::SetPressure(0, 30.0).command(), "SET_PSI 30.00");
+        assert_eq!(Request::SetPressure(1, 5.0).command(), "SET_PSI 5.00 CH=1");
+        assert_eq!(Request::GetPid(2).command(), "PID? CH=2");
+        assert_eq!(Request::Stream(50).command(), "STREAM 50");
+        assert_eq!(line_channel("P T=10 N=3 PSI=5.00 MA=8.0 OK=1 CH=1"), 1);
+        assert_eq!(line_channel("P PSI=30.00"), 0);
+
+        // Setpoints coalesce per channel only
+        let mut framing = Framing::new();
+        framing.push(Request::SetPressure(0, 10.0));
+        framing.push(Request::SetPressure(1, 5.0));
+        framing.push(Request::SetPressure(0, 20.0));
+        assert_eq!(framing.drain_queue(), vec![Request::SetPressure(0, 20.0), Request::SetPressure(1, 5.0)]);
+    }
+}
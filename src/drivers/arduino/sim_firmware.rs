@@ -0,0 +1,207 @@
+/*
+ * Simulated pneumatic controller firmware (protocol v2), for tests.
+ *
+ * Plays the device side of protocol.rs: answers framed commands with ACK/NAK,
+ * identifies itself, and runs the host watchdog. Once armed, any intact frame
+ * from the host feeds the watchdog; when it starves while pressure control is
+ * enabled the firmware disables control, vents to 0 psi and reports "WDT TRIP".
+ * After "STREAM <hz>" it pushes stamped telemetry on every due tick.
+ *
+ * Time is passed in, so tests run without sleeping.
+ */
+
+use tokio::time::{Duration, Instant};
+
+use super::protocol::{encode, unwrap_frame, PROTOCOL_V2};
+
+pub const IDENTITY: &str =
+    "ID NAME=meteorite-pneumatics VER=1.1.0 CMDS=SET_PSI,ENABLE,READ,WDT,HB,STREAM RANGE=0-100 SENSOR=4-20mA";
+
+#[derive(Debug)]
+pub struct SimulatedFirmware {
+    pub enabled: bool,
+    pub setpoint_psi: f32,
+    pub watchdog: Option<Duration>,
+    pub vented: bool,
+    pub stream_period: Option<Duration>,
+    booted: Instant,
+    last_host_frame: Instant,
+    next_sample: Instant,
+    sample_counter: u32,
+}
+
+impl SimulatedFirmware {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            enabled: false,
+            setpoint_psi: 0.0,
+            watchdog: None,
+            vented: false,
+            stream_period: None,
+            booted: now,
+            last_host_frame: now,
+            next_sample: now,
+            sample_counter: 0,
+        }
+    }
+
+    /// Handle one line from the host; returns the reply lines.
+    pub fn receive(&mut self, line: &str, now: Instant) -> Vec<String> {
+        let Ok((seq, command)) = unwrap_frame(line) else {
+            // Corrupted: NAK it if the sequence number is still readable
+            let seq = line.trim().strip_prefix('@').and_then(|l| l.split(' ').next()?.parse::<u16>().ok());
+            return seq.map(|seq| vec![encode(seq, "NAK CRC")]).unwrap_or_default();
+        };
+        self.last_host_frame = now;
+
+        let (name, arg) = command.split_once(' ').unwrap_or((command, ""));
+        let reply = match (name, arg.parse::<f32>()) {
+            ("PROTO", Ok(version)) if version as u8 == PROTOCOL_V2 => format!("ACK PROTO {}", PROTOCOL_V2),
+            ("ID", _) => format!("ACK {}", IDENTITY),
+            ("ENABLE", Ok(flag)) => {
+                self.enabled = flag != 0.0;
+                self.vented = false;
+                "ACK".to_string()
+            }
+            ("SET_PSI", Ok(psi)) if (0.0..=100.0).contains(&psi) => {
+                self.setpoint_psi = psi;
+                "ACK".to_string()
+            }
+            ("SET_PSI", _) => "NAK RANGE".to_string(),
+            ("READ", _) => format!("ACK P PSI={:.2} MA=12.0 OK=1", self.pressure_psi()),
+            ("WDT", Ok(ms)) => {
+                self.watchdog = (ms > 0.0).then(|| Duration::from_millis(ms as u64));
+                "ACK".to_string()
+            }
+            ("HB", _) => "ACK".to_string(),
+            ("STREAM", Ok(hz)) if (0.0..=1000.0).contains(&hz) => {
+                self.stream_period = (hz > 0.0).then(|| Duration::from_secs_f32(1.0 / hz));
+                self.next_sample = now;
+                "ACK".to_string()
+            }
+            _ => "NAK UNKNOWN".to_string(),
+        };
+        vec![encode(seq, &reply)]
+    }
+
+    /// Advance to `now`; returns unsolicited lines (streamed samples, the watchdog trip).
+    pub fn tick(&mut self, now: Instant) -> Vec<String> {
+        let mut lines = Vec::new();
+        while let Some(period) = self.stream_period.filter(|_| self.next_sample <= now) {
+            let device_ms = self.next_sample.duration_since(self.booted).as_millis() as u32;
+            let sample = format!("P T={} N={} PSI={:.2} MA=12.0 OK=1", device_ms, self.sample_counter, self.pressure_psi());
+            lines.push(encode(0, &sample));
+            self.sample_counter = self.sample_counter.wrapping_add(1);
+            self.next_sample += period;
+        }
+
+        let starved = self.watchdog.is_some_and(|timeout| now.duration_since(self.last_host_frame) > timeout);
+        if starved && self.enabled {
+            self.enabled = false;
+            self.setpoint_psi = 0.0;
+            self.vented = true;
+            lines.push(encode(0, "WDT TRIP"));
+        }
+        lines
+    }
+
+    pub fn pressure_psi(&self) -> f32 {
+        if self.enabled { self.setpoint_psi } else { 0.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drivers::arduino::protocol::{decode, Frame, Framing, Outcome, Request, Step};
+    use crate::drivers::arduino::stream::{parse_stamp, StreamStats};
+
+    /// Deliver everything the host has queued and feed the replies back.
+    fn exchange(host: &mut Framing, fw: &mut SimulatedFirmware, now: Instant) -> Vec<Outcome> {
+        let mut outcomes = Vec::new();
+        while let Some(frame) = host.next_frame(now) {
+            for reply in fw.receive(&frame, now) {
+                if let Step::Done(outcome) = host.on_frame(decode(&reply).unwrap(), now) {
+                    outcomes.push(outcome);
+                }
+            }
+        }
+        outcomes
+    }
+
+    #[test]
+    fn test_watchdog_vents_when_heartbeat_stops() {
+        let t0 = Instant::now();
+        let mut host = Framing::new();
+        let mut fw = SimulatedFirmware::new(t0);
+        for request in [Request::Negotiate, Request::Identify, Request::SetWatchdog(1000), Request::Enable(0, true)] {
+            host.push(request);
+        }
+        host.push(Request::SetPressure(0, 40.0));
+        let outcomes = exchange(&mut host, &mut fw, t0);
+        assert_eq!(outcomes.len(), 5);
+        assert!(outcomes.iter().all(|o| matches!(o, Outcome::Acked(..))));
+        assert_eq!(fw.watchdog, Some(Duration::from_millis(1000)));
+
+        // Heartbeats every 250 ms keep it enabled
+        let mut now = t0;
+        for _ in 0..20 {
+            now += Duration::from_millis(250);
+            assert!(fw.tick(now).is_empty());
+            host.push(Request::Heartbeat);
+            exchange(&mut host, &mut fw, now);
+        }
+        assert!(fw.enabled);
+        assert_eq!(fw.pressure_psi(), 40.0);
+
+        // Host goes silent: vented after the timeout, not before
+        assert!(fw.tick(now + Duration::from_millis(900)).is_empty());
+        let events = fw.tick(now + Duration::from_millis(1100));
+        assert_eq!(decode(&events[0]), Ok(Frame::Event { payload: "WDT TRIP".into() }));
+        assert!(fw.vented && !fw.enabled);
+        assert_eq!(fw.pressure_psi(), 0.0);
+    }
+
+    #[test]
+    fn test_disarmed_watchdog_never_trips() {
+        let t0 = Instant::now();
+        let mut host = Framing::new();
+        let mut fw = SimulatedFirmware::new(t0);
+        host.push(Request::SetWatchdog(0));
+        host.push(Request::Enable(0, true));
+        exchange(&mut host, &mut fw, t0);
+        assert!(fw.tick(t0 + Duration::from_secs(60)).is_empty());
+        assert!(fw.enabled);
+
+        // Corrupted frames are NAKed, not acted on
+        let corrupted = crate::drivers::arduino::protocol::encode(9, "ENABLE 0").replace("ENABLE 0", "ENABLE 1");
+        assert_eq!(fw.receive(&corrupted, t0), vec![encode(9, "NAK CRC")]);
+    }
+
+    #[test]
+    fn test_streamed_samples_report_rate_and_drops() {
+        let t0 = Instant::now();
+        let mut host = Framing::new();
+        let mut fw = SimulatedFirmware::new(t0);
+        host.push(Request::Stream(100));
+        assert!(matches!(exchange(&mut host, &mut fw, t0)[..], [Outcome::Acked(Request::Stream(100), _)]));
+
+        // One second of samples, read in bursts; every 25th frame arrives corrupted
+        let mut stats = StreamStats::new();
+        let mut received = 0;
+        for ms in (50..=1000).step_by(50) {
+            for (i, line) in fw.tick(t0 + Duration::from_millis(ms)).into_iter().enumerate() {
+                let line = if (received + i) % 25 == 24 { line.replace("OK=1", "OK=0") } else { line };
+                let Ok(Frame::Event { payload }) = decode(&line) else {
+                    continue;
+                };
+                stats.record(parse_stamp(&payload).unwrap());
+            }
+            received = fw.sample_counter as usize;
+        }
+        assert_eq!(fw.sample_counter, 101);
+        assert_eq!(stats.dropped, 4);
+        // Rate comes from the device clock, not from when the bursts were read
+        assert!((stats.rate_hz().unwrap() - 100.0).abs() < 0.5);
+    }
+}
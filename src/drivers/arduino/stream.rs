@@ -0,0 +1,106 @@
+/*
+ * Push-mode telemetry from the pneumatic controller.
+ *
+ * "STREAM <hz>" makes the firmware push telemetry on its own (0 stops it);
+ * nothing else changes, so READ keeps working. Streamed lines (v2: "@0" event
+ * frames, v1: plain lines) carry the device clock and a sample counter:
+ *
+ *   "P T=<device ms> N=<sample #> PSI=<float> MA=<float> OK=<0|1>"
+ *
+ * Both wrap at u32. The counter shows samples lost on the way (a corrupted frame
+ * is discarded, so it shows up as a gap) and the device clock gives the real
+ * sample rate, independent of when the host got around to reading the line.
+ */
+
+/// Gaps larger than this mean the firmware restarted its counter, not lost samples.
+const MAX_GAP: u32 = 10_000;
+/// Weight of the newest interval in the smoothed sample period.
+const PERIOD_SMOOTHING: f32 = 0.1;
+
+/// Device stamp of a streamed sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stamp {
+    pub device_ms: u32,
+    pub counter: u32,
+}
+
+/// Stamp of a streamed telemetry line; None for polled replies without one.
+pub fn parse_stamp(line: &str) -> Option<Stamp> {
+    let field = |key: &str| {
+        line.split_whitespace()
+            .find_map(|token| token.strip_prefix(key)?.strip_prefix('='))?
+            .parse::<u32>()
+            .ok()
+    };
+    Some(Stamp { device_ms: field("T")?, counter: field("N")? })
+}
+
+/// Sample rate and loss of the stream.
+#[derive(Debug, Clone, Default)]
+pub struct StreamStats {
+    last: Option<Stamp>,
+    period_ms: Option<f32>,
+    pub dropped: u64,
+}
+
+impl StreamStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Account for the next sample; returns how many were lost just before it.
+    pub fn record(&mut self, stamp: Stamp) -> u32 {
+        let Some(last) = self.last.replace(stamp) else {
+            return 0;
+        };
+        let step = stamp.counter.wrapping_sub(last.counter);
+        if step == 0 || step > MAX_GAP {
+            // Duplicate, or the firmware restarted: start measuring afresh
+            self.period_ms = None;
+            return 0;
+        }
+        let period_ms = stamp.device_ms.wrapping_sub(last.device_ms) as f32 / step as f32;
+        self.period_ms = Some(match self.period_ms {
+            Some(smoothed) => smoothed + PERIOD_SMOOTHING * (period_ms - smoothed),
+            None => period_ms,
+        });
+        let lost = step - 1;
+        self.dropped += lost as u64;
+        lost
+    }
+
+    /// Sample rate by the device clock, once two samples have arrived.
+    pub fn rate_hz(&self) -> Option<f32> {
+        self.period_ms.filter(|ms| *ms > 0.0).map(|ms| 1000.0 / ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_rate_and_drops() {
+        assert_eq!(
+            parse_stamp("P T=1200 N=7 PSI=30.10 MA=12.0 OK=1"),
+            Some(Stamp { device_ms: 1200, counter: 7 })
+        );
+        assert_eq!(parse_stamp("P PSI=30.10 MA=12.0 OK=1"), None);
+
+        // 50 Hz by the device clock, with samples 3 and 4 lost, across a clock wrap
+        let mut stats = StreamStats::new();
+        let start = u32::MAX - 50;
+        let lost: Vec<u32> = [0, 1, 2, 5, 6]
+            .into_iter()
+            .map(|n| stats.record(Stamp { device_ms: start.wrapping_add(n * 20), counter: n }))
+            .collect();
+        assert_eq!(lost, vec![0, 0, 0, 2, 0]);
+        assert_eq!(stats.dropped, 2);
+        assert!((stats.rate_hz().unwrap() - 50.0).abs() < 0.01);
+
+        // Counter restart is not counted as loss
+        assert_eq!(stats.record(Stamp { device_ms: 5, counter: 0 }), 0);
+        assert_eq!(stats.rate_hz(), None);
+        assert_eq!(stats.dropped, 2);
+    }
+}
@@ -3,32 +3,84 @@
  *
  * Task owns all serial I/O to Arduino for pneumatic pressure control.
  * - Starts disconnected
- * - On Connect: opens serial port at specified baud rate
- * - Polls pressure telemetry at 10 Hz when connected
+ * - On Connect(port): opens the chosen serial port at the configured baud rate
+ *   as an async tokio-serial stream
+ * - Polls pressure telemetry at 10 Hz when connected; replies (and any unsolicited
+ *   telemetry) are read by a persistent line reader, independent of the poll
  * - Applies pressure setpoints and enable/disable commands
  * - Updates ArduinoState in Arc<RwLock<ArduinoState>> for UI snapshots
  *
- * Protocol (newline-delimited ASCII):
+ * Protocol v1 (newline-delimited ASCII, no replies to commands):
  * - Outgoing commands:
  *   "SET_PSI <float>\n"  - Set pressure setpoint
  *   "ENABLE <0|1>\n"     - Enable/disable pressure control
  *   "READ\n"             - Request telemetry (optional, can also just listen)
+ *   "ID\n"               - Identify firmware; enabling waits for a compatible reply (identity.rs)
+ *   "WDT <ms>\n"         - Arm the firmware watchdog (0 disarms)
+ *   "HB\n"               - Host heartbeat
  * - Incoming telemetry (expected formats):
  *   "P PSI=<float> MA=<float> OK=<0|1>\n"  - Full telemetry
  *   "PSI=<float>\n"                         - Minimal telemetry
  *
+ * Protocol v2 wraps the same commands and telemetry in frames with sequence
+ * numbers, a CRC and ACK/NAK replies; commands are retried until acknowledged.
+ * It is offered on connect and used when the firmware accepts it, otherwise the
+ * driver stays on v1 (see protocol.rs).
+ *
+ * Tuning: the setpoint ramp rate and PID gains can be set (and the gains read
+ * back); an autotune steps the setpoint and the driver records the response at
+ * the poll rate until the firmware reports the suggested gains (see tuning.rs).
+ *
+ * Streaming: firmware that supports it is asked on connect to push stamped
+ * telemetry at the configured rate (see stream.rs). Every pushed line is parsed
+ * as it arrives; the READ poll pauses while samples keep coming and resumes if
+ * the stream stalls.
+ *
+ * Watchdog: once identified firmware that supports it is found, "WDT <ms>" arms
+ * its watchdog with the configured timeout and "HB" heartbeats go out at four
+ * times that rate. If the app dies or the link drops, the firmware disables
+ * control and vents, and reports "WDT TRIP" if it can.
+ *
+ * Channels: each configured pressure channel has its own setpoint, enable and
+ * telemetry, addressed by "CH=<n>" (see protocol.rs). The READ poll takes the
+ * channels in turn, except during a step test, which polls only its channel.
+ * Firmware reporting fewer channels than configured is refused.
+ *
  * TODO: Adjust protocol parsing if Arduino firmware differs from above.
  *
  * UI must never block; commands arrive via mpsc channel and
  * state updates write into `Arc<RwLock<ArduinoState>>` for fast snapshots.
  */
 
-use tokio::time::{sleep, Duration, Instant};
 use std::sync::{Arc, RwLock};
-use std::io::{BufRead, BufReader, Write};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, Lines, ReadHalf, WriteHalf};
 use tokio::sync::mpsc;
+use tokio::time::{interval, sleep_until, Duration, Instant, MissedTickBehavior};
+use tokio_serial::SerialPortBuilderExt;
+
+use crate::config::config::{ArduinoCommand, ArduinoState, AutotuneStatus, PressureChannel, StepSample};
+use crate::config::file::ArduinoConfig;
+use super::stream::{parse_stamp, Stamp, StreamStats};
+use super::identity::{check_compatible, parse_identity};
+use super::tuning::{parse_autotune, parse_pid};
+use super::protocol::{decode, line_channel, Frame, Framing, Outcome, Request, Step, PROTOCOL_V1, PROTOCOL_V2};
 
-use crate::config::config::{ArduinoCommand, ArduinoState};
+/// Telemetry poll period (10 Hz).
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Time v1 firmware gets to answer "ID"; v2 uses the ACK timeout and retries.
+const IDENTIFY_TIMEOUT: Duration = Duration::from_secs(1);
+/// Shortest heartbeat period, however short the watchdog timeout.
+const MIN_HEARTBEAT_INTERVAL: Duration = Duration::from_millis(50);
+/// Firmware event sent when its watchdog vented the regulator.
+const WATCHDOG_TRIP_EVENT: &str = "WDT TRIP";
+/// Longest step-test recording kept for the tuning plot.
+const MAX_STEP_TEST: Duration = Duration::from_secs(120);
+/// Closest spacing of step-test samples: streamed telemetry is thinned to about
+/// the poll rate, so a recording is the same size whichever way it was read.
+/// Just under `POLL_INTERVAL` so polled samples are never skipped.
+const STEP_TEST_SPACING: Duration = Duration::from_millis(90);
+/// Without a streamed sample for this long the READ poll takes over again.
+const STREAM_STALL_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// Parse Arduino telemetry line
 /// Accepts formats:
@@ -71,205 +123,904 @@ fn parse_telemetry(line: &str) -> Option<(f32, Option<f32>, Option<bool>)> {
     Some((psi, ma, ok))
 }
 
+/// Byte stream to the controller: the serial port, or an in-memory pipe in tests.
+trait Port: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Port for T {}
+
+/// Open the named serial port as an async stream.
+fn open_serial(port_name: &str, baud: u32) -> std::io::Result<Box<dyn Port>> {
+    let stream = tokio_serial::new(port_name, baud).open_native_async()?;
+    Ok(Box::new(stream))
+}
+
+/// Open serial link. Incoming bytes go through one persistent line buffer, so a
+/// line that arrives between polls (or split across reads) is kept, never dropped.
+struct Link {
+    lines: Lines<BufReader<ReadHalf<Box<dyn Port>>>>,
+    writer: WriteHalf<Box<dyn Port>>,
+    protocol: Option<u8>,  // None while negotiating
+    framing: Framing,
+    identify_deadline: Option<Instant>,  // v1 identify reply still expected
+    step_test_started: Option<Instant>,  // autotune step commanded at
+    step_test_origin: Option<(u32, f32)>,  // device ms of the first streamed step sample, and its t_s
+    stream: Vec<StreamStats>,  // per channel
+    last_sample: Option<Instant>,  // latest streamed sample arrived at
+    next_read: usize,  // channel the next READ poll asks for
+}
+
+impl Link {
+    fn new(port: Box<dyn Port>, channels: usize) -> Self {
+        let (reader, writer) = tokio::io::split(port);
+        Self {
+            lines: BufReader::new(reader).lines(),
+            writer,
+            protocol: None,
+            framing: Framing::new(),
+            identify_deadline: None,
+            step_test_started: None,
+            step_test_origin: None,
+            stream: vec![StreamStats::new(); channels],
+            last_sample: None,
+            next_read: 0,
+        }
+    }
+
+    /// Whether streamed samples are still arriving.
+    fn streaming(&self, now: Instant) -> bool {
+        self.last_sample.is_some_and(|at| now.duration_since(at) < STREAM_STALL_TIMEOUT)
+    }
+
+    /// Earliest pending ACK or identify deadline.
+    fn deadline(&self) -> Option<Instant> {
+        [self.framing.deadline(), self.identify_deadline].into_iter().flatten().min()
+    }
+
+    /// Write one line. Only the task calls this, so commands and READ polls go
+    /// out one at a time in the order they were issued.
+    async fn send(&mut self, line: &str) -> std::io::Result<()> {
+        self.writer.write_all(line.as_bytes()).await?;
+        self.writer.flush().await
+    }
+
+    /// Write the next queued frame if none is waiting for its ACK.
+    async fn send_next(&mut self) -> std::io::Result<()> {
+        if let Some(frame) = self.framing.next_frame(Instant::now()) {
+            self.send(&frame).await?;
+        }
+        Ok(())
+    }
+
+    /// Send a request the negotiated way. v1 writes the bare command and returns
+    /// true: there is no reply, so it counts as accepted. v2 (and requests made
+    /// while negotiating) are queued for framing and complete on their ACK.
+    async fn submit(&mut self, request: Request) -> std::io::Result<bool> {
+        if self.protocol == Some(PROTOCOL_V1) {
+            self.send(&format!("{}\n", request.command())).await?;
+            return Ok(true);
+        }
+        self.framing.push(request);
+        self.send_next().await?;
+        Ok(false)
+    }
+}
+
+/// Next line from the open link; pends forever while disconnected.
+async fn next_line(link: &mut Option<Link>) -> std::io::Result<Option<String>> {
+    match link {
+        Some(link) => link.lines.next_line().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Heartbeat period for a watchdog timeout: four beats per timeout.
+fn heartbeat_interval(watchdog_ms: u32) -> Duration {
+    (Duration::from_millis(watchdog_ms as u64) / 4).max(MIN_HEARTBEAT_INTERVAL)
+}
+
+/// Requests that follow identification: arming the firmware watchdog and
+/// starting the telemetry stream, when configured and the compatible firmware
+/// supports them.
+fn setup_requests(s: &ArduinoState, config: &ArduinoConfig) -> Vec<Request> {
+    let Some(firmware) = s.firmware.as_ref().filter(|_| s.firmware_problem.is_none()) else {
+        return Vec::new();
+    };
+    let supports = |commands: &[&str]| commands.iter().all(|c| firmware.commands.iter().any(|cmd| cmd == c));
+    let mut requests = Vec::new();
+    if config.watchdog_ms > 0 && supports(&["WDT", "HB"]) {
+        requests.push(Request::SetWatchdog(config.watchdog_ms));
+    }
+    if config.stream_hz > 0 && supports(&["STREAM"]) {
+        requests.push(Request::Stream(config.stream_hz));
+    }
+    requests
+}
+
+/// Submit requests in order, applying the ones v1 takes at once.
+async fn submit_all(link: &mut Link, requests: Vec<Request>, state: &RwLock<ArduinoState>) -> std::io::Result<()> {
+    for request in requests {
+        if link.submit(request).await? {
+            apply_accepted(&mut state.write().unwrap(), request, "");
+        }
+    }
+    Ok(())
+}
+
+/// Pressure channel a command addresses; None for the whole controller.
+fn command_channel(command: &ArduinoCommand) -> Option<usize> {
+    match *command {
+        ArduinoCommand::Enable(channel, _)
+        | ArduinoCommand::SetPressureSetpoint(channel, _)
+        | ArduinoCommand::SetRampRate(channel, _)
+        | ArduinoCommand::GetPid(channel)
+        | ArduinoCommand::SetPid(channel, _)
+        | ArduinoCommand::Autotune(channel, _) => Some(channel),
+        ArduinoCommand::Connect(_) | ArduinoCommand::Disconnect => None,
+    }
+}
+
+/// Why the identified firmware cannot take `command`, when it does not list it.
+fn unsupported(s: &ArduinoState, command: &str) -> Option<String> {
+    let firmware = s.firmware.as_ref()?;
+    (!firmware.commands.iter().any(|c| c == command))
+        .then(|| format!("firmware {} {} does not support {}", firmware.name, firmware.version, command))
+}
+
+/// Unsolicited line from the controller: a watchdog trip, an autotune report,
+/// a v1 PID reply or telemetry.
+fn apply_event(s: &mut ArduinoState, line: &str) {
+    if line.trim() == WATCHDOG_TRIP_EVENT {
+        // The firmware vents every channel
+        for c in &mut s.channels {
+            c.enabled = false;
+        }
+        s.watchdog_tripped = true;
+        s.status = Some("disabled".into());
+        s.last_error = Some("controller watchdog expired; pressure vented".into());
+    } else if let Some(status) = parse_autotune(line) {
+        if let AutotuneStatus::Failed(ref reason) = status {
+            s.last_error = Some(format!("autotune failed: {}", reason));
+        }
+        s.autotune = Some(status);
+    } else if line.trim_start().starts_with("PID ") {
+        apply_pid(s, line_channel(line), line);
+    } else {
+        apply_telemetry(s, line_channel(line), line);
+    }
+}
+
+/// Channel of the running autotune.
+fn autotune_channel(s: &ArduinoState) -> Option<usize> {
+    match s.autotune {
+        Some(AutotuneStatus::Running { channel, .. }) => Some(channel),
+        _ => None,
+    }
+}
+
+/// Unsolicited line including streamed telemetry: also accounts for the sample
+/// and records it against the device clock during a step test.
+fn apply_unsolicited(link: &mut Link, s: &mut ArduinoState, line: &str) {
+    apply_event(s, line);
+    let channel = line_channel(line);
+    let Some(stamp) = parse_stamp(line).filter(|_| s.stream_hz.is_some() && channel < link.stream.len()) else {
+        return;
+    };
+    apply_stamp(link, s, channel, stamp);
+    if let Some(started) = link.step_test_started
+        && autotune_channel(s).is_none_or(|c| c == channel)
+    {
+        let (origin_ms, origin_s) = *link
+            .step_test_origin
+            .get_or_insert((stamp.device_ms, started.elapsed().as_secs_f32()));
+        let t_s = origin_s + stamp.device_ms.wrapping_sub(origin_ms) as f32 / 1000.0;
+        if !record_step_sample(s, t_s) {
+            link.step_test_started = None;
+        }
+    }
+}
+
+/// Account for a streamed sample; the state shows the slowest channel's rate
+/// and the samples lost on all of them.
+fn apply_stamp(link: &mut Link, s: &mut ArduinoState, channel: usize, stamp: Stamp) {
+    link.stream[channel].record(stamp);
+    link.last_sample = Some(Instant::now());
+    s.sample_rate_hz = link.stream.iter().filter_map(StreamStats::rate_hz).reduce(f32::min);
+    s.dropped_samples = link.stream.iter().map(|stats| stats.dropped).sum();
+    s.device_time_ms = Some(stamp.device_ms);
+}
+
+/// Add a step-test sample at `t_s` while an autotune runs; false once it is over.
+fn record_step_sample(s: &mut ArduinoState, t_s: f32) -> bool {
+    let Some(AutotuneStatus::Running { channel, to_psi, .. }) = s.autotune else {
+        return false;
+    };
+    let spaced = s.step_test.last().is_none_or(|last| t_s - last.t_s >= STEP_TEST_SPACING.as_secs_f32());
+    if spaced && t_s <= MAX_STEP_TEST.as_secs_f32() {
+        let measured_psi = s.channels.get(channel).map_or(0.0, |c| c.pressure_measured_psi);
+        let sample = StepSample { t_s, setpoint_psi: to_psi, measured_psi };
+        s.step_test.push(sample);
+    }
+    true
+}
+
+/// Configured channel `index`, or None for one the firmware made up.
+fn channel_mut(s: &mut ArduinoState, index: usize) -> Option<&mut PressureChannel> {
+    s.channels.get_mut(index)
+}
+
+fn apply_pid(s: &mut ArduinoState, channel: usize, line: &str) {
+    match parse_pid(line) {
+        Ok(gains) => {
+            if let Some(c) = channel_mut(s, channel) {
+                c.pid = Some(gains);
+            }
+        }
+        Err(e) => s.last_error = Some(e),
+    }
+}
+
+fn apply_telemetry(s: &mut ArduinoState, channel: usize, line: &str) {
+    if let Some((psi, ma, ok)) = parse_telemetry(line)
+        && let Some(c) = channel_mut(s, channel)
+    {
+        c.pressure_measured_psi = psi;
+        c.loop_current_ma = ma;
+        c.signal_ok = ok;
+        s.last_error = None;
+    }
+}
+
+/// Record the identify reply and whether the firmware may be enabled.
+fn apply_identity(s: &mut ArduinoState, line: &str) {
+    match parse_identity(line) {
+        Ok(info) => {
+            s.firmware_problem = check_compatible(&info, s.channels.len()).err();
+            s.firmware = Some(info);
+        }
+        Err(e) => {
+            s.firmware = None;
+            s.firmware_problem = Some(format!("bad identify reply: {}", e));
+        }
+    }
+}
+
+/// Apply a request the controller has accepted (ACKed in v2, written in v1).
+fn apply_accepted(s: &mut ArduinoState, request: Request, payload: &str) {
+    match request {
+        Request::Negotiate => {}
+        Request::Identify => apply_identity(s, payload),
+        Request::Enable(channel, enable) => {
+            if let Some(c) = channel_mut(s, channel) {
+                c.enabled = enable;
+            }
+            if enable {
+                s.watchdog_tripped = false;
+            }
+            s.last_error = None;
+            s.status = Some(if enable { "enabled" } else { "disabled" }.into());
+        }
+        Request::SetPressure(..) => s.last_error = None,
+        Request::Read(channel) => apply_telemetry(s, channel, payload),
+        Request::SetWatchdog(ms) => {
+            s.watchdog_ms = (ms > 0).then_some(ms);
+            s.heartbeat_misses = 0;
+        }
+        Request::Heartbeat => s.heartbeat_misses = 0,
+        Request::SetRampRate(channel, psi_per_s) => {
+            if let Some(c) = channel_mut(s, channel) {
+                c.ramp_rate_psi_per_s = Some(psi_per_s);
+            }
+        }
+        // v1 answers with a plain line, picked up by `apply_event`
+        Request::GetPid(_) if payload.is_empty() => {}
+        Request::GetPid(channel) => apply_pid(s, channel, payload),
+        Request::SetPid(channel, gains) => {
+            if let Some(c) = channel_mut(s, channel) {
+                c.pid = Some(gains);
+            }
+        }
+        // Running from the moment it was sent; the result arrives as an event
+        Request::Autotune(..) => {}
+        Request::Stream(hz) => s.stream_hz = (hz > 0).then_some(hz),
+    }
+}
+
+/// Carry out what the framing asked for after an ACK, NAK or timeout.
+async fn handle_step(link: &mut Link, step: Step, state: &RwLock<ArduinoState>, config: &ArduinoConfig) -> std::io::Result<()> {
+    match step {
+        Step::Idle => {}
+        Step::Send(frame) => link.send(&frame).await?,
+        Step::Done(Outcome::Acked(Request::Negotiate, _)) => {
+            link.protocol = Some(PROTOCOL_V2);
+            state.write().unwrap().protocol_version = Some(PROTOCOL_V2);
+            link.framing.push(Request::Identify);
+        }
+        Step::Done(Outcome::Failed(Request::Negotiate, _)) => {
+            // No framed reply: v1 firmware. Requests queued meanwhile go out as plain lines.
+            link.protocol = Some(PROTOCOL_V1);
+            state.write().unwrap().protocol_version = Some(PROTOCOL_V1);
+            for request in link.framing.drain_queue() {
+                link.send(&format!("{}\n", request.command())).await?;
+                apply_accepted(&mut state.write().unwrap(), request, "");
+            }
+            link.send(&format!("{}\n", Request::Identify.command())).await?;
+            link.identify_deadline = Some(Instant::now() + IDENTIFY_TIMEOUT);
+        }
+        Step::Done(Outcome::Acked(Request::Identify, payload)) => {
+            let mut s = state.write().unwrap();
+            apply_identity(&mut s, &payload);
+            for request in setup_requests(&s, config) {
+                link.framing.push(request);
+            }
+        }
+        Step::Done(Outcome::Failed(Request::Identify, reason)) => {
+            state.write().unwrap().firmware_problem = Some(format!("no identify reply ({}); unknown firmware", reason));
+        }
+        Step::Done(Outcome::Failed(Request::Heartbeat, _)) => {
+            // Shown as heartbeat health; the next beat replaces it
+            state.write().unwrap().heartbeat_misses += 1;
+        }
+        Step::Done(Outcome::Acked(request, payload)) => {
+            apply_accepted(&mut state.write().unwrap(), request, &payload);
+        }
+        Step::Done(Outcome::Failed(request, reason)) => {
+            let mut s = state.write().unwrap();
+            // The client shows enable/disable at once; undo it when the controller never took it
+            if let Request::Enable(channel, enable) = request {
+                if let Some(c) = channel_mut(&mut s, channel) {
+                    c.enabled = !enable;
+                }
+                s.status = Some(if enable { "disabled" } else { "enabled" }.into());
+            }
+            if let Request::Autotune(..) = request {
+                s.autotune = Some(AutotuneStatus::Failed(reason.clone()));
+            }
+            s.last_error = Some(format!("{}: {}", request.command(), reason));
+        }
+    }
+    link.send_next().await
+}
+
+fn mark_disconnected(s: &mut ArduinoState) {
+    s.connected = false;
+    s.protocol_version = None;
+    s.firmware = None;
+    s.firmware_problem = None;
+    s.watchdog_ms = None;
+    s.heartbeat_misses = 0;
+    s.watchdog_tripped = false;
+    s.stream_hz = None;
+    s.sample_rate_hz = None;
+    s.dropped_samples = 0;
+    s.device_time_ms = None;
+    if matches!(s.autotune, Some(AutotuneStatus::Running { .. })) {
+        s.autotune = Some(AutotuneStatus::Failed("link lost".into()));
+    }
+    // Names and setpoints stay: they are configuration, not link state
+    for c in &mut s.channels {
+        c.enabled = false;
+        c.ramp_rate_psi_per_s = None;
+        c.pid = None;
+        c.pressure_measured_psi = 0.0;
+        c.loop_current_ma = None;
+        c.signal_ok = None;
+    }
+}
+
+/// Close the link after an I/O error.
+fn drop_link(link: &mut Option<Link>, state: &RwLock<ArduinoState>, error: String) {
+    *link = None;
+    let mut s = state.write().unwrap();
+    mark_disconnected(&mut s);
+    s.last_error = Some(error);
+    s.status = Some("error".into());
+}
+
 pub async fn arduino_control(
-    port: &str,
-    baud: u32,
+    config: ArduinoConfig,
+    arduino_rx: mpsc::Receiver<ArduinoCommand>,
+    state: Arc<RwLock<ArduinoState>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    control_loop(config, arduino_rx, state, open_serial).await
+}
+
+/// The task proper; `open_port` opens the port named by Connect.
+async fn control_loop(
+    config: ArduinoConfig,
     mut arduino_rx: mpsc::Receiver<ArduinoCommand>,
     state: Arc<RwLock<ArduinoState>>,
+    mut open_port: impl FnMut(&str, u32) -> std::io::Result<Box<dyn Port>>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use serialport::SerialPort;
-    
-    let mut serial_port: Option<Box<dyn SerialPort>> = None;
-    let mut connected = false;
-    let mut last_poll = Instant::now();
-    let poll_interval = Duration::from_millis(100); // 10 Hz
-    
+    let mut link: Option<Link> = None;
+    let mut poll = interval(POLL_INTERVAL);
+    poll.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut heartbeat = interval(heartbeat_interval(config.watchdog_ms));
+    heartbeat.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
     loop {
-        // Check if it's time to poll
-        let should_poll = connected && last_poll.elapsed() >= poll_interval;
-        
-        // Select between command and poll timing
+        let deadline = link.as_ref().and_then(Link::deadline);
+
         tokio::select! {
             Some(command) = arduino_rx.recv() => {
-                match command {
-                    ArduinoCommand::Connect => {
-                        // Open serial port in blocking task
-                        let port_name = port.to_string();
-                        let baud_rate = baud;
-                        
-                        match tokio::task::spawn_blocking(move || {
-                            serialport::new(&port_name, baud_rate)
-                                .timeout(Duration::from_millis(100))
-                                .open()
-                        }).await {
-                            Ok(Ok(port)) => {
-                                serial_port = Some(port);
-                                connected = true;
-                                last_poll = Instant::now();
-                                let mut s = state.write().unwrap();
-                                s.connected = true;
-                                s.enabled = false;
-                                s.last_error = None;
-                                s.status = Some("connected".into());
-                                s.pressure_measured_psi = 0.0;
-                                s.loop_current_ma = None;
-                                s.signal_ok = None;
-                            }
-                            Ok(Err(e)) => {
-                                let mut s = state.write().unwrap();
-                                s.connected = false;
-                                s.last_error = Some(format!("serial open failed: {}", e));
-                                s.status = Some("error".into());
+                if let Some(channel) = command_channel(&command) {
+                    let mut s = state.write().unwrap();
+                    if channel >= s.channels.len() {
+                        s.last_error = Some(format!("no pressure channel {}", channel));
+                        continue;
+                    }
+                }
+                let request = match command {
+                    ArduinoCommand::Connect(port_name) => {
+                        link = None;
+                        match open_port(&port_name, config.baud_rate) {
+                            Ok(port) => {
+                                let mut opened = Link::new(port, config.channels.len());
+                                {
+                                    let mut s = state.write().unwrap();
+                                    mark_disconnected(&mut s);
+                                    s.connected = true;
+                                    s.last_error = None;
+                                    s.status = Some(format!("connected ({})", port_name));
+                                }
+                                poll.reset();
+                                // Offer v2; v1 firmware stays silent and we fall back
+                                let result = opened.submit(Request::Negotiate).await;
+                                link = Some(opened);
+                                if let Err(e) = result {
+                                    drop_link(&mut link, &state, format!("write failed: {}", e));
+                                }
                             }
                             Err(e) => {
                                 let mut s = state.write().unwrap();
-                                s.connected = false;
-                                s.last_error = Some(format!("spawn_blocking failed: {}", e));
+                                mark_disconnected(&mut s);
+                                s.last_error = Some(format!("serial open {} failed: {}", port_name, e));
                                 s.status = Some("error".into());
                             }
                         }
+                        continue;
                     }
                     ArduinoCommand::Disconnect => {
-                        serial_port = None;
-                        connected = false;
+                        link = None;
                         let mut s = state.write().unwrap();
-                        s.connected = false;
-                        s.enabled = false;
+                        mark_disconnected(&mut s);
                         s.status = Some("disconnected".into());
-                        s.pressure_measured_psi = 0.0;
-                        s.loop_current_ma = None;
-                        s.signal_ok = None;
+                        continue;
                     }
-                    ArduinoCommand::Enable(enable) => {
-                        if connected {
-                            if let Some(ref mut port) = serial_port {
-                                let cmd = format!("ENABLE {}\n", if enable { 1 } else { 0 });
-                                let port_clone = port.try_clone();
-                                
-                                match port_clone {
-                                    Ok(mut p) => {
-                                        match tokio::task::spawn_blocking(move || {
-                                            p.write_all(cmd.as_bytes())
-                                        }).await {
-                                            Ok(Ok(_)) => {
-                                                let mut s = state.write().unwrap();
-                                                s.enabled = enable;
-                                                s.last_error = None;
-                                                s.status = Some(if enable { "enabled" } else { "disabled" }.into());
-                                            }
-                                            Ok(Err(e)) => {
-                                                let mut s = state.write().unwrap();
-                                                s.last_error = Some(format!("write failed: {}", e));
-                                                s.status = Some("error".into());
-                                                connected = false;
-                                                s.connected = false;
-                                            }
-                                            Err(e) => {
-                                                let mut s = state.write().unwrap();
-                                                s.last_error = Some(format!("spawn failed: {}", e));
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        let mut s = state.write().unwrap();
-                                        s.last_error = Some(format!("port clone failed: {}", e));
-                                    }
-                                }
-                            }
-                        } else {
-                            let mut s = state.write().unwrap();
-                            s.last_error = Some("not connected".into());
+                    ArduinoCommand::Enable(channel, enable) => {
+                        // Only identified, compatible firmware is enabled
+                        let mut s = state.write().unwrap();
+                        let refusal = match s.firmware_problem {
+                            Some(ref problem) => Some(problem.clone()),
+                            None if s.connected && s.firmware.is_none() => Some("firmware not identified yet".to_string()),
+                            None => None,
+                        };
+                        if enable && let Some(reason) = refusal {
+                            s.channels[channel].enabled = false;
+                            s.status = Some("disabled".into());
+                            s.last_error = Some(format!("refusing to enable: {}", reason));
+                            continue;
                         }
+                        Request::Enable(channel, enable)
                     }
-                    ArduinoCommand::SetPressureSetpoint(psi) => {
-                        // Update setpoint in state
+                    ArduinoCommand::SetPressureSetpoint(channel, psi) => {
+                        let mut s = state.write().unwrap();
+                        if let Some((min, max)) = s.firmware.as_ref().map(|f| f.pressure_range_psi)
+                            && !(min..=max).contains(&psi)
                         {
-                            let mut s = state.write().unwrap();
-                            s.pressure_setpoint_psi = psi.max(0.0);
+                            s.last_error = Some(format!("setpoint {:.2} psi outside the controller range {}–{} psi", psi, min, max));
+                            continue;
                         }
-                        
-                        // If enabled and connected, send command to Arduino
-                        let is_enabled = state.read().unwrap().enabled;
-                        if connected && is_enabled {
-                            if let Some(ref mut port) = serial_port {
-                                let cmd = format!("SET_PSI {:.2}\n", psi);
-                                let port_clone = port.try_clone();
-                                
-                                match port_clone {
-                                    Ok(mut p) => {
-                                        match tokio::task::spawn_blocking(move || {
-                                            p.write_all(cmd.as_bytes())
-                                        }).await {
-                                            Ok(Ok(_)) => {
-                                                let mut s = state.write().unwrap();
-                                                s.last_error = None;
-                                            }
-                                            Ok(Err(e)) => {
-                                                let mut s = state.write().unwrap();
-                                                s.last_error = Some(format!("write failed: {}", e));
-                                                s.status = Some("error".into());
-                                                connected = false;
-                                                s.connected = false;
-                                            }
-                                            Err(e) => {
-                                                let mut s = state.write().unwrap();
-                                                s.last_error = Some(format!("spawn failed: {}", e));
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        let mut s = state.write().unwrap();
-                                        s.last_error = Some(format!("port clone failed: {}", e));
-                                    }
-                                }
-                            }
+                        let c = &mut s.channels[channel];
+                        c.pressure_setpoint_psi = psi.max(0.0);
+                        // Only an enabled channel is sent the setpoint
+                        if !c.enabled {
+                            continue;
+                        }
+                        Request::SetPressure(channel, c.pressure_setpoint_psi)
+                    }
+                    ArduinoCommand::SetRampRate(channel, psi_per_s) => {
+                        let mut s = state.write().unwrap();
+                        let mut problems: Vec<String> = unsupported(&s, "RAMP").into_iter().collect();
+                        if !(psi_per_s.is_finite() && psi_per_s >= 0.0) {
+                            problems.push(format!("ramp rate must be 0 or more psi/s, got {}", psi_per_s));
+                        }
+                        if !problems.is_empty() {
+                            s.last_error = Some(problems.join("; "));
+                            continue;
+                        }
+                        Request::SetRampRate(channel, psi_per_s)
+                    }
+                    ArduinoCommand::GetPid(channel) => {
+                        let mut s = state.write().unwrap();
+                        if let Some(reason) = unsupported(&s, "PID") {
+                            s.last_error = Some(reason);
+                            continue;
                         }
+                        Request::GetPid(channel)
                     }
+                    ArduinoCommand::SetPid(channel, gains) => {
+                        let mut s = state.write().unwrap();
+                        let mut problems: Vec<String> = unsupported(&s, "PID").into_iter().collect();
+                        problems.extend(gains.validate());
+                        if !problems.is_empty() {
+                            s.last_error = Some(problems.join("; "));
+                            continue;
+                        }
+                        Request::SetPid(channel, gains)
+                    }
+                    ArduinoCommand::Autotune(channel, step_psi) => {
+                        let mut s = state.write().unwrap();
+                        let to_psi = s.channels[channel].pressure_setpoint_psi + step_psi;
+                        let refusal = if let Some(reason) = unsupported(&s, "TUNE") {
+                            Some(reason)
+                        } else if !s.channels[channel].enabled {
+                            Some("enable pressure control first".to_string())
+                        } else if matches!(s.autotune, Some(AutotuneStatus::Running { .. })) {
+                            Some("autotune already running".to_string())
+                        } else if step_psi == 0.0 || !step_psi.is_finite() {
+                            Some(format!("step must be a non-zero number of psi, got {}", step_psi))
+                        } else {
+                            s.firmware.as_ref()
+                                .map(|f| f.pressure_range_psi)
+                                .filter(|(min, max)| !(*min..=*max).contains(&to_psi))
+                                .map(|(min, max)| format!("step to {:.2} psi leaves the controller range {}–{} psi", to_psi, min, max))
+                        };
+                        if let Some(reason) = refusal {
+                            s.last_error = Some(format!("refusing autotune: {}", reason));
+                            continue;
+                        }
+                        Request::Autotune(channel, step_psi)
+                    }
+                };
+                let Some(open) = link.as_mut() else {
+                    state.write().unwrap().last_error = Some("not connected".into());
+                    continue;
+                };
+                if let Request::Autotune(channel, step_psi) = request {
+                    let mut s = state.write().unwrap();
+                    let from_psi = s.channels[channel].pressure_setpoint_psi;
+                    s.autotune = Some(AutotuneStatus::Running { channel, from_psi, to_psi: from_psi + step_psi });
+                    s.step_test.clear();
+                    open.step_test_started = Some(Instant::now());
+                    open.step_test_origin = None;
+                }
+                match open.submit(request).await {
+                    Ok(true) => apply_accepted(&mut state.write().unwrap(), request, ""),
+                    Ok(false) => {}
+                    Err(e) => drop_link(&mut link, &state, format!("write failed: {}", e)),
                 }
             }
-            _ = sleep(poll_interval), if should_poll => {
-                // Time to poll for telemetry
-                if let Some(ref mut port) = serial_port {
-                    last_poll = Instant::now();
-                    
-                    // Send READ command
-                    let port_clone = port.try_clone();
-                    if let Ok(mut p) = port_clone {
-                        // Send READ and read response in blocking task
-                        match tokio::task::spawn_blocking(move || -> Result<String, std::io::Error> {
-                            p.write_all(b"READ\n")?;
-                            p.flush()?;
-                            
-                            let mut reader = BufReader::new(p);
-                            let mut line = String::new();
-                            reader.read_line(&mut line)?;
-                            Ok(line)
-                        }).await {
-                            Ok(Ok(line)) => {
-                                // Parse telemetry
-                                if let Some((psi, ma, ok)) = parse_telemetry(&line) {
-                                    let mut s = state.write().unwrap();
-                                    s.pressure_measured_psi = psi;
-                                    s.loop_current_ma = ma;
-                                    s.signal_ok = ok;
-                                    s.last_error = None;
-                                }
-                            }
-                            Ok(Err(e)) => {
-                                let mut s = state.write().unwrap();
-                                s.last_error = Some(format!("read failed: {}", e));
-                                s.status = Some("error".into());
-                                connected = false;
-                                s.connected = false;
-                            }
-                            Err(e) => {
+
+            // Request telemetry; the reply is picked up by the line reader below
+            _ = poll.tick(), if link.is_some() => {
+                let Some(open) = link.as_mut() else {
+                    continue;
+                };
+                // Streamed samples arriving: those are the telemetry
+                if open.streaming(Instant::now()) {
+                    continue;
+                }
+                let tuning = {
+                    let mut s = state.write().unwrap();
+                    if s.stream_hz.is_some() && open.last_sample.is_some() {
+                        s.sample_rate_hz = None;  // stalled
+                    }
+                    // Record the step response from the latest polled telemetry
+                    if let Some(started) = open.step_test_started
+                        && !record_step_sample(&mut s, started.elapsed().as_secs_f32())
+                    {
+                        open.step_test_started = None;
+                    }
+                    autotune_channel(&s)
+                };
+                // Not while negotiating, and never more than one READ outstanding
+                if open.protocol.is_none() || open.framing.is_pending_where(|r| matches!(r, Request::Read(_))) {
+                    continue;
+                }
+                let channel = tuning.unwrap_or(open.next_read);
+                open.next_read = (channel + 1) % config.channels.len().max(1);
+                if let Err(e) = open.submit(Request::Read(channel)).await {
+                    drop_link(&mut link, &state, format!("write failed: {}", e));
+                }
+            }
+
+            // Keep an armed firmware watchdog fed
+            _ = heartbeat.tick(), if link.is_some() && state.read().unwrap().watchdog_ms.is_some() => {
+                let Some(open) = link.as_mut() else {
+                    continue;
+                };
+                if open.framing.is_pending(Request::Heartbeat) {
+                    continue;
+                }
+                match open.submit(Request::Heartbeat).await {
+                    Ok(true) => apply_accepted(&mut state.write().unwrap(), Request::Heartbeat, ""),
+                    Ok(false) => {}
+                    Err(e) => drop_link(&mut link, &state, format!("write failed: {}", e)),
+                }
+            }
+
+            _ = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                let Some(open) = link.as_mut() else {
+                    continue;
+                };
+                let now = Instant::now();
+                if open.identify_deadline.is_some_and(|d| d <= now) {
+                    open.identify_deadline = None;
+                    state.write().unwrap().firmware_problem = Some("no identify reply; unknown firmware".into());
+                }
+                if open.framing.deadline().is_none_or(|d| d > now) {
+                    continue;
+                }
+                let step = open.framing.on_timeout(now);
+                if let Err(e) = handle_step(open, step, &state, &config).await {
+                    drop_link(&mut link, &state, format!("write failed: {}", e));
+                }
+            }
+
+            line = next_line(&mut link) => {
+                let line = match line {
+                    Ok(Some(line)) => line,
+                    Ok(None) => {
+                        drop_link(&mut link, &state, "serial port closed".into());
+                        continue;
+                    }
+                    Err(e) => {
+                        drop_link(&mut link, &state, format!("read failed: {}", e));
+                        continue;
+                    }
+                };
+                let Some(open) = link.as_mut() else {
+                    continue;
+                };
+                let result = match decode(&line) {
+                    Ok(Frame::Event { payload }) => {
+                        apply_unsolicited(open, &mut state.write().unwrap(), &payload);
+                        Ok(())
+                    }
+                    Ok(frame) => {
+                        let step = open.framing.on_frame(frame, Instant::now());
+                        handle_step(open, step, &state, &config).await
+                    }
+                    // Plain lines are v1 replies; unchecked, so ignored once v2 is agreed
+                    Err(_) if open.protocol != Some(PROTOCOL_V2) => {
+                        if open.identify_deadline.is_some() && line.trim_start().starts_with("ID ") {
+                            open.identify_deadline = None;
+                            let setup = {
                                 let mut s = state.write().unwrap();
-                                s.last_error = Some(format!("spawn failed: {}", e));
-                            }
+                                apply_identity(&mut s, &line);
+                                setup_requests(&s, &config)
+                            };
+                            submit_all(open, setup, &state).await
+                        } else {
+                            apply_unsolicited(open, &mut state.write().unwrap(), &line);
+                            Ok(())
                         }
                     }
+                    Err(e) => {
+                        state.write().unwrap().last_error = Some(format!("discarded frame: {}", e));
+                        Ok(())
+                    }
+                };
+                if let Err(e) = result {
+                    drop_link(&mut link, &state, format!("write failed: {}", e));
                 }
             }
         }
     }
 }
+
+// The driver task only runs in the real build
+#[cfg(all(test, feature = "real"))]
+mod tests {
+    use super::*;
+    use tokio::io::DuplexStream;
+    use tokio::task::JoinHandle;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use crate::drivers::arduino::protocol::MIN_WATCHDOG_MS;
+    use crate::drivers::arduino::sim_firmware::SimulatedFirmware;
+
+    /// Run the task with a Connect that opens `port`.
+    fn start(config: ArduinoConfig, port: DuplexStream) -> (mpsc::Sender<ArduinoCommand>, Arc<RwLock<ArduinoState>>, JoinHandle<()>) {
+        let (tx, rx) = mpsc::channel(8);
+        let state = Arc::new(RwLock::new(ArduinoState::new(&config.channels)));
+        let mut port = Some(port);
+        let open = move |_: &str, _: u32| -> std::io::Result<Box<dyn Port>> {
+            let port = port.take().ok_or_else(|| std::io::Error::other("port already open"))?;
+            Ok(Box::new(port))
+        };
+        let task_state = Arc::clone(&state);
+        let task = tokio::spawn(async move {
+            let _ = control_loop(config, rx, task_state, open).await;
+        });
+        (tx, state, task)
+    }
+
+    /// Play the firmware on the device end until the host closes the port.
+    async fn serve(port: DuplexStream, fw: Arc<Mutex<SimulatedFirmware>>) {
+        serve_lossy(port, fw, |_| false).await
+    }
+
+    /// `serve`, but the replies to host lines `lose` picks never make it back.
+    async fn serve_lossy(port: DuplexStream, fw: Arc<Mutex<SimulatedFirmware>>, mut lose: impl FnMut(&str) -> bool) {
+        let (reader, mut writer) = tokio::io::split(port);
+        let mut lines = BufReader::new(reader).lines();
+        let mut tick = interval(Duration::from_millis(10));
+        loop {
+            let replies = tokio::select! {
+                line = lines.next_line() => match line {
+                    Ok(Some(line)) => {
+                        let replies = fw.lock().unwrap().receive(&line, Instant::now());
+                        if lose(&line) { Vec::new() } else { replies }
+                    }
+                    _ => return,
+                },
+                _ = tick.tick() => fw.lock().unwrap().tick(Instant::now()),
+            };
+            for reply in replies {
+                if writer.write_all(reply.as_bytes()).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Wait up to three seconds for the state to satisfy `done`.
+    async fn wait_for(state: &RwLock<ArduinoState>, done: impl Fn(&ArduinoState) -> bool) -> bool {
+        for _ in 0..300 {
+            if done(&state.read().unwrap()) {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        false
+    }
+
+    fn config(watchdog_ms: u32) -> ArduinoConfig {
+        ArduinoConfig { watchdog_ms, stream_hz: 0, ..ArduinoConfig::default() }
+    }
+
+    #[tokio::test]
+    async fn test_heartbeats_keep_armed_firmware_enabled() {
+        let (host, device) = tokio::io::duplex(4096);
+        let fw = Arc::new(Mutex::new(SimulatedFirmware::new(Instant::now())));
+        let firmware = tokio::spawn(serve(device, Arc::clone(&fw)));
+        let (tx, state, task) = start(config(MIN_WATCHDOG_MS), host);
+
+        tx.send(ArduinoCommand::Connect("sim".into())).await.unwrap();
+        assert!(wait_for(&state, |s| s.watchdog_ms == Some(MIN_WATCHDOG_MS)).await, "watchdog never armed");
+        assert_eq!(fw.lock().unwrap().watchdog, Some(Duration::from_millis(MIN_WATCHDOG_MS as u64)));
+        tx.send(ArduinoCommand::Enable(0, true)).await.unwrap();
+        assert!(wait_for(&state, |s| s.channels[0].enabled).await, "enable not acknowledged");
+
+        // Several watchdog periods on heartbeats alone
+        tokio::time::sleep(Duration::from_millis(2500)).await;
+        assert!(fw.lock().unwrap().enabled);
+        {
+            let s = state.read().unwrap();
+            assert_eq!(s.heartbeat_misses, 0);
+            assert!(!s.watchdog_tripped);
+        }
+
+        // The app goes away: no more heartbeats, so the firmware vents
+        task.abort();
+        firmware.await.unwrap();
+        tokio::time::sleep(Duration::from_millis(MIN_WATCHDOG_MS as u64 + 200)).await;
+        let mut fw = fw.lock().unwrap();
+        let events: Vec<_> = fw.tick(Instant::now()).iter().map(|line| decode(line)).collect();
+        assert_eq!(events, vec![Ok(Frame::Event { payload: WATCHDOG_TRIP_EVENT.into() })]);
+        assert!(fw.vented && !fw.enabled);
+    }
+
+    #[tokio::test]
+    async fn test_lost_ack_does_not_trip_watchdog() {
+        let (host, device) = tokio::io::duplex(4096);
+        let fw = Arc::new(Mutex::new(SimulatedFirmware::new(Instant::now())));
+        // Once armed, the ACK to the next heartbeat is lost; the host waits out ACK_TIMEOUT
+        let lose_next = Arc::new(AtomicBool::new(false));
+        let lose = Arc::clone(&lose_next);
+        tokio::spawn(serve_lossy(device, Arc::clone(&fw), move |line| {
+            line.contains(" HB*") && lose.swap(false, Ordering::SeqCst)
+        }));
+        let (tx, state, _task) = start(config(MIN_WATCHDOG_MS), host);
+
+        tx.send(ArduinoCommand::Connect("sim".into())).await.unwrap();
+        assert!(wait_for(&state, |s| s.watchdog_ms.is_some()).await, "watchdog never armed");
+        tx.send(ArduinoCommand::Enable(0, true)).await.unwrap();
+        assert!(wait_for(&state, |s| s.channels[0].enabled).await, "enable not acknowledged");
+
+        lose_next.store(true, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(2 * MIN_WATCHDOG_MS as u64)).await;
+        assert!(!lose_next.load(Ordering::SeqCst), "no heartbeat ACK was lost");
+        assert!(fw.lock().unwrap().enabled);
+        let s = state.read().unwrap();
+        assert!(!s.watchdog_tripped);
+        assert!(s.channels[0].enabled);
+    }
+
+    #[tokio::test]
+    async fn test_watchdog_left_disarmed_when_not_configured() {
+        let (host, device) = tokio::io::duplex(4096);
+        let fw = Arc::new(Mutex::new(SimulatedFirmware::new(Instant::now())));
+        tokio::spawn(serve(device, Arc::clone(&fw)));
+        let (tx, state, _task) = start(config(0), host);
+
+        tx.send(ArduinoCommand::Connect("sim".into())).await.unwrap();
+        assert!(wait_for(&state, |s| s.firmware.is_some()).await, "firmware never identified");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(state.read().unwrap().watchdog_ms, None);
+        assert_eq!(fw.lock().unwrap().watchdog, None);
+    }
+
+    /// Ask to enable channel 0 and return the refusal, if any.
+    async fn try_enable(tx: &mpsc::Sender<ArduinoCommand>, state: &RwLock<ArduinoState>) -> Option<String> {
+        state.write().unwrap().last_error = None;
+        tx.send(ArduinoCommand::Enable(0, true)).await.unwrap();
+        wait_for(state, |s| s.last_error.is_some() || s.channels[0].enabled).await;
+        let s = state.read().unwrap();
+        assert!(!s.channels[0].enabled);
+        s.last_error.clone()
+    }
+
+    #[tokio::test]
+    async fn test_enable_refused_before_identify() {
+        // A device that never answers
+        let (host, _device) = tokio::io::duplex(4096);
+        let (tx, state, _task) = start(config(0), host);
+
+        tx.send(ArduinoCommand::Connect("silent".into())).await.unwrap();
+        assert!(wait_for(&state, |s| s.connected).await);
+        assert_eq!(try_enable(&tx, &state).await.as_deref(), Some("refusing to enable: firmware not identified yet"));
+    }
+
+    #[tokio::test]
+    async fn test_enable_refused_after_identify_timeout() {
+        let (host, _device) = tokio::io::duplex(4096);
+        let (tx, state, _task) = start(config(0), host);
+
+        // No v2 ACK, then no reply to the v1 "ID"
+        tx.send(ArduinoCommand::Connect("silent".into())).await.unwrap();
+        assert!(wait_for(&state, |s| s.firmware_problem.is_some()).await, "identify never timed out");
+        assert_eq!(state.read().unwrap().protocol_version, Some(PROTOCOL_V1));
+        assert_eq!(
+            try_enable(&tx, &state).await.as_deref(),
+            Some("refusing to enable: no identify reply; unknown firmware")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_enable_refused_for_incompatible_firmware() {
+        let (host, device) = tokio::io::duplex(4096);
+        let fw = Arc::new(Mutex::new(SimulatedFirmware::new(Instant::now())));
+        tokio::spawn(serve(device, Arc::clone(&fw)));
+        // The simulated firmware drives one regulator
+        let two_channels = ArduinoConfig { channels: vec!["material".into(), "sheath".into()], ..config(0) };
+        let (tx, state, _task) = start(two_channels, host);
+
+        tx.send(ArduinoCommand::Connect("sim".into())).await.unwrap();
+        assert!(wait_for(&state, |s| s.firmware.is_some()).await, "firmware never identified");
+        let problem = "2 pressure channels configured, firmware has 1";
+        assert_eq!(state.read().unwrap().firmware_problem.as_deref(), Some(problem));
+        assert_eq!(try_enable(&tx, &state).await, Some(format!("refusing to enable: {}", problem)));
+        assert!(!fw.lock().unwrap().enabled);
+    }
+
+    #[test]
+    fn test_streamed_step_test_covers_two_minutes() {
+        let mut s = ArduinoState::new(&["material".into()]);
+        s.autotune = Some(AutotuneStatus::Running { channel: 0, from_psi: 10.0, to_psi: 15.0 });
+        // Three minutes of 50 Hz samples
+        for i in 0..9000 {
+            assert!(record_step_sample(&mut s, i as f32 * 0.02));
+        }
+        let last = s.step_test.last().unwrap().t_s;
+        assert!(last > 119.0 && last <= 120.0, "recording ends at {} s", last);
+        assert!(s.step_test.len() <= 1201, "{} samples", s.step_test.len());
+
+        // Polled samples are all kept
+        s.step_test.clear();
+        for i in 0..100 {
+            record_step_sample(&mut s, i as f32 * POLL_INTERVAL.as_secs_f32());
+        }
+        assert_eq!(s.step_test.len(), 100);
+    }
+}
@@ -0,0 +1,77 @@
+/*
+ * PID gains and step-response autotune replies of the pneumatic controller.
+ *
+ * "PID?" is answered (ACK payload on v2, plain line on v1) with:
+ *
+ *   "PID KP=1.2000 KI=0.3000 KD=0.0500"
+ *
+ * "TUNE <step psi>" is acknowledged at once; the firmware then steps its
+ * setpoint, records the response, restores the setpoint and reports:
+ *
+ *   "TUNE DONE KP=.. KI=.. KD=.. RISE=<s> OVERSHOOT=<%>"
+ *   "TUNE FAIL <reason>"
+ *
+ * The suggested gains are only reported; the operator applies them with "PID".
+ */
+
+use crate::config::config::{AutotuneResult, AutotuneStatus, PidGains};
+
+/// Value of a `KEY=<float>` field.
+fn field(fields: &str, key: &str) -> Result<f32, String> {
+    let value = fields
+        .split_whitespace()
+        .find_map(|token| token.strip_prefix(key)?.strip_prefix('='))
+        .ok_or(format!("no {}", key))?;
+    value.parse::<f32>().map_err(|_| format!("bad {} {:?}", key, value))
+}
+
+fn gains(fields: &str) -> Result<PidGains, String> {
+    Ok(PidGains { kp: field(fields, "KP")?, ki: field(fields, "KI")?, kd: field(fields, "KD")? })
+}
+
+/// Parse a "PID KP= KI= KD=" reply.
+pub fn parse_pid(line: &str) -> Result<PidGains, String> {
+    let fields = line.trim().strip_prefix("PID ").ok_or("not a PID reply")?;
+    gains(fields).map_err(|e| format!("bad PID reply: {}", e))
+}
+
+/// Parse an autotune report; None for lines that are not one.
+pub fn parse_autotune(line: &str) -> Option<AutotuneStatus> {
+    let report = line.trim().strip_prefix("TUNE ")?;
+    if let Some(reason) = report.strip_prefix("FAIL") {
+        return Some(AutotuneStatus::Failed(reason.trim().to_string()));
+    }
+    let fields = report.strip_prefix("DONE ")?;
+    let result = gains(fields).and_then(|gains| {
+        Ok(AutotuneResult {
+            gains,
+            rise_time_s: field(fields, "RISE")?,
+            overshoot_pct: field(fields, "OVERSHOOT")?,
+        })
+    });
+    Some(match result {
+        Ok(result) => AutotuneStatus::Done(result),
+        Err(e) => AutotuneStatus::Failed(format!("bad autotune report: {}", e)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pid_and_autotune() {
+        let gains = PidGains { kp: 1.2, ki: 0.3, kd: 0.05 };
+        assert_eq!(parse_pid("PID KP=1.2000 KI=0.3000 KD=0.0500\r"), Ok(gains));
+        assert!(parse_pid("PID KP=1.2 KI=x KD=0").unwrap_err().contains("KI"));
+        assert!(parse_pid("P PSI=3.0").is_err());
+
+        assert_eq!(
+            parse_autotune("TUNE DONE KP=1.2 KI=0.3 KD=0.05 RISE=0.8 OVERSHOOT=12.5"),
+            Some(AutotuneStatus::Done(AutotuneResult { gains, rise_time_s: 0.8, overshoot_pct: 12.5 }))
+        );
+        assert_eq!(parse_autotune("TUNE FAIL no response"), Some(AutotuneStatus::Failed("no response".into())));
+        assert!(matches!(parse_autotune("TUNE DONE KP=1"), Some(AutotuneStatus::Failed(_))));
+        assert_eq!(parse_autotune("P PSI=3.0 MA=12 OK=1"), None);
+    }
+}
@@ -4,7 +4,8 @@
  * Task owns all hardware I/O. Uses HTTP RRF3 endpoints instead of TCP/Telnet.
  * - Uses reqwest::Client for HTTP requests
  * - Polls rr_status for position and connection status
- * - Sends G-code via rr_gcode endpoint
+ * - Sends G-code via rr_gcode endpoint and records the "buff" reply
+ *   (free G-code buffer space) so the print engine can apply back-pressure
  *
  * UI must never block; commands arrive via an mpsc channel and
  * state updates write into `Arc<RwLock<DuetState>>` for fast snapshots.
@@ -19,7 +20,7 @@ use tokio::sync::mpsc;
 use tokio::time::{interval, Duration};
 use serde::Deserialize;
 
-use crate::config::config::{DuetCommand, DuetState};
+use crate::config::config::{DuetCommand, DuetState, DUET_MIN_BUFFER_FREE};
 
 #[derive(Deserialize, Debug)]
 struct RrStatus {
@@ -27,6 +28,11 @@ struct RrStatus {
     coords: Coords,
 }
 
+#[derive(Deserialize, Debug)]
+struct RrGcodeReply {
+    buff: u32,
+}
+
 #[derive(Deserialize, Debug)]
 struct Coords {
     xyz: [f32; 3],
@@ -34,6 +40,18 @@ struct Coords {
     machine: [f32; 3],
 }
 
+/// Send one line via rr_gcode and return the free buffer space reported by the Duet.
+/// A reply that cannot be parsed yields `Ok(None)`; only transport errors are `Err`.
+async fn send_rr_gcode(
+    client: &reqwest::Client,
+    duet_ip: &str,
+    gcode: &str,
+) -> Result<Option<u32>, reqwest::Error> {
+    let url = crate::utilities::utils::rr_gcode_url(duet_ip, gcode);
+    let resp = client.get(&url).send().await?;
+    Ok(resp.json::<RrGcodeReply>().await.ok().map(|r| r.buff))
+}
+
 pub async fn duet_control(
     duet_ip: &str,
     mut rx: mpsc::Receiver<DuetCommand>,
@@ -56,6 +74,7 @@ pub async fn duet_control(
                                 s.connected = true;
                                 s.status = Some("connected".to_string());
                                 s.last_error = None;
+                                s.buffer_free = None;
                             }
                             Err(e) => {
                                 connected = false;
@@ -75,18 +94,20 @@ pub async fn duet_control(
                     DuetCommand::SendGcode(gcode) => {
                         if !connected {
                             let mut s = state.write().unwrap();
+                            s.gcode_in_flight = s.gcode_in_flight.saturating_sub(1);
                             s.last_error = Some("not connected".to_string());
                         } else {
-                            let url = crate::utilities::utils::rr_gcode_url(duet_ip, &gcode);
-                            match client.get(&url).send().await {
-                                Ok(_) => {
-                                    let mut s = state.write().unwrap();
+                            let result = send_rr_gcode(&client, duet_ip, &gcode).await;
+                            let mut s = state.write().unwrap();
+                            s.gcode_in_flight = s.gcode_in_flight.saturating_sub(1);
+                            match result {
+                                Ok(buff) => {
+                                    s.buffer_free = buff;
                                     s.last_command = Some(gcode.clone());
                                     s.last_error = None;
                                 }
                                 Err(e) => {
                                     connected = false;
-                                    let mut s = state.write().unwrap();
                                     s.status = Some("error".to_string());
                                     s.last_error = Some(format!("Gcode error: {}", e));
                                     s.connected = false;
@@ -97,18 +118,20 @@ pub async fn duet_control(
                     DuetCommand::SendMCommand(m_cmd) => {
                         if !connected {
                             let mut s = state.write().unwrap();
+                            s.gcode_in_flight = s.gcode_in_flight.saturating_sub(1);
                             s.last_error = Some("not connected".to_string());
                         } else {
-                            let url = crate::utilities::utils::rr_gcode_url(duet_ip, &m_cmd);
-                            match client.get(&url).send().await {
-                                Ok(_) => {
-                                    let mut s = state.write().unwrap();
+                            let result = send_rr_gcode(&client, duet_ip, &m_cmd).await;
+                            let mut s = state.write().unwrap();
+                            s.gcode_in_flight = s.gcode_in_flight.saturating_sub(1);
+                            match result {
+                                Ok(buff) => {
+                                    s.buffer_free = buff;
                                     s.last_command = Some(m_cmd.clone());
                                     s.last_error = None;
                                 }
                                 Err(e) => {
                                     connected = false;
-                                    let mut s = state.write().unwrap();
                                     s.status = Some("error".to_string());
                                     s.last_error = Some(format!("M-command error: {}", e));
                                     s.connected = false;
@@ -134,6 +157,7 @@ pub async fn duet_control(
                                         "S" => "stopped".to_string(),
                                         "H" => "halted".to_string(),
                                         "D" => "pausing".to_string(),
+                                        "B" => "busy".to_string(),
                                         other => other.to_string(),
                                     });
                                     s.last_error = None;
@@ -155,6 +179,21 @@ pub async fn duet_control(
                             s.last_error = Some(format!("Poll error: {}", e));
                         }
                     }
+
+                    // The buffer level only comes back with rr_gcode replies, so while
+                    // the queue is drained below the streaming threshold refresh it
+                    // with an empty request.
+                    let needs_refresh = {
+                        let s = state.read().unwrap();
+                        s.gcode_in_flight == 0
+                            && s.buffer_free.is_some_and(|b| b < DUET_MIN_BUFFER_FREE)
+                    };
+                    if connected
+                        && needs_refresh
+                        && let Ok(buff) = send_rr_gcode(&client, duet_ip, "").await
+                    {
+                        state.write().unwrap().buffer_free = buff;
+                    }
                 }
             }
         }
@@ -0,0 +1,94 @@
+/*
+ * Decoding of the generator's status word and error codes.
+ *
+ * The status word is a bit mask of latched error flags; the generator keeps a
+ * flag set until it is cleared with ClearErrors, so every telemetry poll reports
+ * the full set. Error replies carry a numeric code, not flags: it is reported as
+ * text only, and the faults behind it come back with the next status poll.
+ *
+ * Several flags map onto one `GeneratorFault`; flags this table does not know
+ * are reported as `GeneratorFault::Other` with their bit, never dropped.
+ */
+
+use crate::config::config::GeneratorFault;
+
+pub const STATUS_UNSPECIFIED: u32 = 0x0001;
+pub const STATUS_PA_TEMP_HIGH: u32 = 0x0002;
+pub const STATUS_PA_TEMP_SHUTDOWN: u32 = 0x0004;
+pub const STATUS_REFLECTED_HIGH: u32 = 0x0008;
+pub const STATUS_REFLECTED_SHUTDOWN: u32 = 0x0010;
+pub const STATUS_POWER_MEASUREMENT: u32 = 0x0080;
+pub const STATUS_RF_ENABLE_FAILED: u32 = 0x0100;
+pub const STATUS_EXTERNAL_SHUTDOWN: u32 = 0x0400;
+
+/// Status bits and the fault each one means.
+const STATUS_BITS: [(u32, GeneratorFault); 8] = [
+    (STATUS_UNSPECIFIED, GeneratorFault::PaFault),
+    (STATUS_PA_TEMP_HIGH, GeneratorFault::OverTemperature { shutdown: false }),
+    (STATUS_PA_TEMP_SHUTDOWN, GeneratorFault::OverTemperature { shutdown: true }),
+    (STATUS_REFLECTED_HIGH, GeneratorFault::ReflectedPower { shutdown: false }),
+    (STATUS_REFLECTED_SHUTDOWN, GeneratorFault::ReflectedPower { shutdown: true }),
+    (STATUS_POWER_MEASUREMENT, GeneratorFault::PaFault),
+    (STATUS_RF_ENABLE_FAILED, GeneratorFault::PaFault),
+    (STATUS_EXTERNAL_SHUTDOWN, GeneratorFault::InterlockOpen),
+];
+
+/// Faults flagged in a status word, each listed once; 0 means no faults.
+pub fn decode_status(word: u32) -> Vec<GeneratorFault> {
+    let mut faults = Vec::new();
+    let mut unknown = word;
+    for (bit, fault) in STATUS_BITS {
+        if word & bit != 0 && !faults.contains(&fault) {
+            faults.push(fault);
+        }
+        unknown &= !bit;
+    }
+    // Report unknown flags one bit at a time so each is recognisable
+    for shift in 0..u32::BITS {
+        let bit = 1 << shift;
+        if unknown & bit != 0 {
+            faults.push(GeneratorFault::Other(bit));
+        }
+    }
+    faults
+}
+
+/// Operator-facing text for the code in an error reply.
+pub fn describe_error(code: u16) -> String {
+    match code {
+        0 => "unspecified error".into(),
+        code => format!("error code {} (0x{:04X})", code, code),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_status() {
+        assert!(decode_status(0).is_empty());
+        assert_eq!(
+            decode_status(STATUS_PA_TEMP_SHUTDOWN | STATUS_REFLECTED_SHUTDOWN),
+            vec![
+                GeneratorFault::OverTemperature { shutdown: true },
+                GeneratorFault::ReflectedPower { shutdown: true },
+            ]
+        );
+        // Several PA flags collapse into one fault
+        assert_eq!(decode_status(STATUS_UNSPECIFIED | STATUS_RF_ENABLE_FAILED), vec![GeneratorFault::PaFault]);
+        assert_eq!(
+            decode_status(STATUS_EXTERNAL_SHUTDOWN | 0x2000),
+            vec![GeneratorFault::InterlockOpen, GeneratorFault::Other(0x2000)]
+        );
+        assert!(!GeneratorFault::OverTemperature { shutdown: false }.shuts_down_rf());
+        assert!(GeneratorFault::InterlockOpen.shuts_down_rf());
+    }
+
+    #[test]
+    fn test_describe_error() {
+        assert_eq!(describe_error(0), "unspecified error");
+        // A code is not a bit mask: 0x0410 names no status flags
+        assert_eq!(describe_error(0x0410), "error code 1040 (0x0410)");
+    }
+}
@@ -1,2 +1,6 @@
 pub mod task;
+pub mod sweep;
+pub mod tracking;
+pub mod ramp;
+pub mod faults;
 pub use task::*;
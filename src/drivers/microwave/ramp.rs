@@ -0,0 +1,127 @@
+/*
+ * Power slew limiting for the microwave driver task.
+ *
+ * Power setpoints are targets: the task moves the generator's commanded power
+ * toward them at no more than the ramp rate, sending a new SetPower every
+ * `RAMP_UPDATE`. RF on soft-starts from 0 W, and RF off drops the commanded power
+ * back to 0 so the next RF on ramps up again. Step changes in power crack parts.
+ *
+ * This module holds the slew law; `task.rs` does the I/O.
+ */
+
+use tokio::time::{Duration, Instant};
+
+/// Interval between SetPower updates while ramping.
+pub const RAMP_UPDATE: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+pub struct PowerRamp {
+    pub target_w: f32,
+    pub commanded_w: f32,
+    pub rate_w_per_s: f32,
+    active: bool,           // RF on: the commanded power follows the target
+    last_update: Instant,
+}
+
+impl PowerRamp {
+    pub fn new(rate_w_per_s: f32, now: Instant) -> Self {
+        Self {
+            target_w: 0.0,
+            commanded_w: 0.0,
+            rate_w_per_s,
+            active: false,
+            last_update: now,
+        }
+    }
+
+    /// New target power and slew rate; takes effect on the next `advance`.
+    pub fn set_target(&mut self, watts: f32, rate_w_per_s: f32, now: Instant) {
+        if !self.ramping() {
+            // Don't count idle time towards the first increment
+            self.last_update = now;
+        }
+        self.target_w = watts.max(0.0);
+        self.rate_w_per_s = rate_w_per_s;
+    }
+
+    /// RF on: ramp from `from_w` towards the target.
+    pub fn start(&mut self, from_w: f32, now: Instant) {
+        self.active = true;
+        self.commanded_w = from_w.max(0.0);
+        self.last_update = now;
+    }
+
+    /// RF off: nothing is ramped, and the next RF on starts from 0 W.
+    pub fn stop(&mut self) {
+        self.active = false;
+        self.commanded_w = 0.0;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn ramping(&self) -> bool {
+        self.active && self.commanded_w != self.target_w
+    }
+
+    /// Step the commanded power towards the target for the time since the last
+    /// update. Returns the new commanded power when it changed.
+    pub fn advance(&mut self, now: Instant) -> Option<f32> {
+        let dt = now.saturating_duration_since(self.last_update).as_secs_f32();
+        self.last_update = now;
+        if !self.ramping() {
+            return None;
+        }
+        let max_step = self.rate_w_per_s.max(0.0) * dt;
+        let remaining = self.target_w - self.commanded_w;
+        self.commanded_w = if remaining.abs() <= max_step {
+            self.target_w
+        } else {
+            self.commanded_w + max_step.copysign(remaining)
+        };
+        Some(self.commanded_w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slews_at_rate_and_lands_on_target() {
+        let t0 = Instant::now();
+        let mut ramp = PowerRamp::new(10.0, t0);
+        ramp.set_target(25.0, 10.0, t0);
+        // RF off: target is held, nothing is commanded
+        assert_eq!(ramp.advance(t0 + Duration::from_secs(1)), None);
+
+        ramp.start(0.0, t0 + Duration::from_secs(1));
+        assert_eq!(ramp.advance(t0 + Duration::from_millis(1500)), Some(5.0));
+        assert_eq!(ramp.advance(t0 + Duration::from_millis(3000)), Some(20.0));
+        assert_eq!(ramp.advance(t0 + Duration::from_millis(4000)), Some(25.0));
+        assert!(!ramp.ramping());
+        assert_eq!(ramp.advance(t0 + Duration::from_millis(5000)), None);
+
+        // Down-ramps are slewed too
+        let t1 = t0 + Duration::from_secs(10);
+        ramp.set_target(5.0, 20.0, t1);
+        assert_eq!(ramp.advance(t1 + Duration::from_millis(500)), Some(15.0));
+        assert_eq!(ramp.advance(t1 + Duration::from_secs(1)), Some(5.0));
+    }
+
+    #[test]
+    fn test_rf_off_restarts_from_zero() {
+        let t0 = Instant::now();
+        let mut ramp = PowerRamp::new(100.0, t0);
+        ramp.set_target(50.0, 100.0, t0);
+        ramp.start(0.0, t0);
+        assert_eq!(ramp.advance(t0 + Duration::from_secs(1)), Some(50.0));
+
+        ramp.stop();
+        assert_eq!(ramp.commanded_w, 0.0);
+        assert_eq!(ramp.target_w, 50.0);
+        ramp.start(0.0, t0 + Duration::from_secs(2));
+        assert_eq!(ramp.advance(t0 + Duration::from_millis(2100)), Some(10.0));
+    }
+}
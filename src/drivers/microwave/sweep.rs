@@ -0,0 +1,106 @@
+/*
+ * Frequency sweep planning for the microwave driver task.
+ *
+ * A sweep steps the generator across [start_hz, stop_hz] at a fixed low power.
+ * It only starts with RF off; RF comes on at 0 W and ramps up to the sweep power
+ * before the first point, and goes off again afterwards. At each point the task waits `SWEEP_DWELL` for the load to settle, requests a
+ * forward/reflected power reading, and records it. When every point has a reading
+ * the generator is parked at the point with the lowest reflected/forward ratio.
+ *
+ * This module holds the plan and the result evaluation; `task.rs` does the I/O.
+ */
+
+use tokio::time::{Duration, Instant};
+
+use crate::config::config::SweepPoint;
+
+/// Upper bound on points per sweep, keeps a mistyped step from running for hours.
+pub const MAX_SWEEP_POINTS: usize = 2001;
+/// Settling time after each frequency change before reading power.
+pub const SWEEP_DWELL: Duration = Duration::from_millis(200);
+/// Time allowed for the power reading at one point.
+pub const SWEEP_READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Frequencies visited by a sweep, `stop_hz` included when it falls on a step.
+pub fn sweep_frequencies(start_hz: i32, stop_hz: i32, step_hz: i32) -> Result<Vec<i32>, String> {
+    if step_hz <= 0 {
+        return Err(format!("sweep step must be positive, got {} Hz", step_hz));
+    }
+    if start_hz < 0 || stop_hz < start_hz {
+        return Err(format!("invalid sweep range {}..{} Hz", start_hz, stop_hz));
+    }
+    let count = ((stop_hz - start_hz) / step_hz) as usize + 1;
+    if count > MAX_SWEEP_POINTS {
+        return Err(format!("sweep has {} points (max {})", count, MAX_SWEEP_POINTS));
+    }
+    Ok((0..count).map(|i| start_hz + i as i32 * step_hz).collect())
+}
+
+/// Point with the lowest reflected/forward ratio; points without forward power are skipped.
+pub fn best_match(points: &[SweepPoint]) -> Option<&SweepPoint> {
+    points
+        .iter()
+        .filter(|p| p.forward_w > 0.0)
+        .min_by(|a, b| {
+            let ra = a.reflected_w / a.forward_w;
+            let rb = b.reflected_w / b.forward_w;
+            ra.total_cmp(&rb)
+        })
+}
+
+/// What the sweep is waiting for at the current point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepPhase {
+    RampingUp, // RF on, power ramping to the sweep power
+    Settling,  // frequency sent, waiting SWEEP_DWELL
+    Reading,   // power reading requested, waiting for the response
+}
+
+/// Sweep in progress inside the driver task.
+#[derive(Debug, Clone)]
+pub struct SweepRun {
+    pub frequencies: Vec<i32>,
+    pub next: usize,
+    pub phase: SweepPhase,
+    pub deadline: Instant,
+    pub power_w: f32,          // sweep power, reached through the power ramp
+    pub resume_target_w: f32,  // power ramp target to restore afterwards
+    pub resume_rate_w_per_s: f32,
+}
+
+impl SweepRun {
+    pub fn current_hz(&self) -> Option<i32> {
+        self.frequencies.get(self.next).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(frequency_hz: i32, forward_w: f32, reflected_w: f32) -> SweepPoint {
+        SweepPoint { frequency_hz, forward_w, reflected_w }
+    }
+
+    #[test]
+    fn test_sweep_frequencies() {
+        assert_eq!(sweep_frequencies(100, 130, 10).unwrap(), vec![100, 110, 120, 130]);
+        assert_eq!(sweep_frequencies(100, 125, 10).unwrap(), vec![100, 110, 120]);
+        assert_eq!(sweep_frequencies(100, 100, 10).unwrap(), vec![100]);
+        assert!(sweep_frequencies(100, 200, 0).is_err());
+        assert!(sweep_frequencies(200, 100, 10).is_err());
+        assert!(sweep_frequencies(0, 1_000_000, 1).is_err());
+    }
+
+    #[test]
+    fn test_best_match_uses_reflection_ratio() {
+        let points = vec![
+            point(100, 10.0, 2.0),
+            point(110, 5.0, 0.25),  // lowest ratio
+            point(120, 0.0, 0.0),   // no forward power: ignored
+            point(130, 1.0, 0.2),   // lowest reflected power, but a poor match
+        ];
+        assert_eq!(best_match(&points).unwrap().frequency_hz, 110);
+        assert!(best_match(&[point(100, 0.0, 0.0)]).is_none());
+    }
+}
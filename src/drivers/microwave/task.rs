@@ -1,132 +1,781 @@
 /**
- * Microwave Control module with MiniCircuit driver integration (PLACEHOLDER)
+ * Microwave Control module with MiniCircuit driver integration
  *
  * Task owns all serial I/O and MiniCircuit driver lifecycle.
  * - Starts disconnected
- * - On Connect: would initialize MiniCircuitDriver with TargetProperties
- * - Commands would map to MiniCircuit Message types with Priority
- * - Responses would update MicrowaveState (telemetry, status, errors)
+ * - On Connect(port): creates a MiniCircuitDriver from build_target_properties()
+ *   for the chosen port and keeps its command sender / response receiver
+ * - Commands map to MiniCircuit Messages with a Priority (RF on/off ahead of setpoints)
+ * - MicrowaveState (enabled, power, errors) is updated only from driver Responses;
+ *   sending a command only records that it is pending
+ * - Power setpoints are targets: the commanded power is slewed towards them at
+ *   the ramp rate (see ramp.rs). RF on soft-starts from 0 W; RF off resets the ramp
+ * - Polls telemetry (forward/reflected power, PA temperature, current, voltage,
+ *   status word) every `telemetry_poll_ms` at low priority while connected
+ * - Sweep: steps the frequency at low power, records forward/reflected power per
+ *   point, then parks at the minimum-reflection point (see sweep.rs). It is refused
+ *   while RF is on (a print or the operator owns it) and, like RF on, while an
+ *   RF-shutdown fault is latched. RF soft-starts through the ramp to the sweep
+ *   power and goes off afterwards. Setpoint commands are rejected while a sweep
+ *   runs; RF off cancels it.
+ * - Tracking: while enabled and RF is on, a perturb-and-observe loop (see
+ *   tracking.rs) reads reflection every `interval_ms` and steps the frequency
+ *   toward the minimum, within ±span of where it started. Manual frequency
+ *   changes and sweeps re-center it; it pauses while a sweep runs. Only one power
+ *   read (telemetry or tracking) is outstanding at a time, so each step acts on
+ *   the reply to its own request.
+ * - Pulse: SetPulse switches the generator between PWM (period, duty cycle) and
+ *   continuous wave. Settings are validated before anything is sent
+ * - Frequency: setpoints and sweeps outside the configured band are rejected; the
+ *   last frequency sent is kept in state and the generator's readback is polled
+ * - On Disconnect: RF off is sent first and its response awaited (briefly), then
+ *   the driver is dropped
  *
- * TODO: Fully implement MiniCircuitDriver integration once exact Command/Response
- * API is confirmed. Current implementation uses placeholders and optimistic updates.
+ * The MiniCircuits API surface used here is kept in `TELEMETRY_POLL`, `to_message`
+ * and `apply_response` so protocol changes stay local to them.
  *
  * UI must never block; commands arrive via an mpsc channel and
  * state updates write into `Arc<RwLock<MicrowaveState>>` for fast snapshots.
  */
 
-use tokio::time::{sleep, Duration};
 use std::sync::{Arc, RwLock};
-use tokio::sync::mpsc;
-// TODO: Uncomment when ready to use MiniCircuitDriver
-// use minicircuit_driver::driver::MiniCircuitDriver;
-// use minicircuit_commands::command::{Command, Message, Priority};
-// use minicircuit_commands::response::Response;
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::{interval, sleep_until, timeout_at, Duration, Instant, MissedTickBehavior};
+use minicircuit_driver::driver::MiniCircuitDriver;
+use minicircuit_commands::command::{Command, Message, Priority};
+use minicircuit_commands::response::Response;
 
-use crate::config::config::{MicrowaveCommand, MicrowaveState};
+use crate::config::config::{build_target_properties, MicrowaveCommand, MicrowaveState, SweepPoint, TrackingStatus};
+use crate::config::file::{validate_tracking, MicrowaveConfig};
+use crate::utilities::units::{hz_to_mhz, mhz_to_hz};
+use super::sweep::{best_match, sweep_frequencies, SweepPhase, SweepRun, SWEEP_DWELL, SWEEP_READ_TIMEOUT};
+use super::tracking::Tracker;
+use super::ramp::{PowerRamp, RAMP_UPDATE};
+use super::faults::{decode_status, describe_error};
+
+/// Open driver session; dropping it closes the port.
+struct Session {
+    _driver: MiniCircuitDriver,
+    cmd_tx: mpsc::UnboundedSender<Message>,
+    resp_rx: broadcast::Receiver<Response>,
+}
+
+impl Session {
+    fn send(&self, priority: Priority, command: Command) -> bool {
+        self.cmd_tx.send(Message { priority, command }).is_ok()
+    }
+}
+
+/// How long Disconnect waits for the generator to confirm RF off.
+const RF_OFF_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How long a forward/reflected power read may stay unanswered before another is sent.
+const POWER_READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Who asked for the outstanding forward/reflected power read. Replies are not
+/// tagged, so only one read is in flight at a time and its reply goes to the asker.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum PowerRead {
+    Telemetry,
+    Tracking,
+}
+
+/// True when no power read is outstanding (or the last one has expired).
+fn power_read_idle(read: &Option<(PowerRead, Instant)>) -> bool {
+    read.is_none_or(|(_, expires)| Instant::now() >= expires)
+}
+
+/// Read-back commands sent on every telemetry poll.
+const TELEMETRY_POLL: [Command; 6] = [
+    Command::GetPAPowerWatt,
+    Command::GetFrequency,
+    Command::GetPATemp,
+    Command::GetPACurrent,
+    Command::GetPAVoltage,
+    Command::GetStatus,
+];
+
+/// VSWR from forward and reflected power; None without meaningful forward power.
+pub fn vswr(forward_w: f32, reflected_w: f32) -> Option<f32> {
+    if forward_w <= 0.0 {
+        return None;
+    }
+    let gamma = (reflected_w.max(0.0) / forward_w).sqrt();
+    if gamma >= 1.0 {
+        return Some(f32::INFINITY);
+    }
+    Some((1.0 + gamma) / (1.0 - gamma))
+}
+
+/// MiniCircuits message for a command, or None for commands the task handles itself.
+/// RF on/off go ahead of setpoint changes so RF off is never queued behind them.
+fn to_message(command: &MicrowaveCommand) -> Option<Message> {
+    let (priority, command) = match command {
+        MicrowaveCommand::Connect(_)
+        | MicrowaveCommand::Disconnect
+        | MicrowaveCommand::Sweep { .. }
+        | MicrowaveCommand::SetTracking(_)
+        | MicrowaveCommand::SetPowerWatts { .. } => return None,
+        MicrowaveCommand::RfOn => (Priority::High, Command::RfOn),
+        MicrowaveCommand::RfOff => (Priority::High, Command::RfOff),
+        MicrowaveCommand::SetFrequencyHz(hz) => (Priority::Normal, Command::SetFrequency(*hz)),
+        MicrowaveCommand::SetPulse(Some(pulse)) => (
+            Priority::Normal,
+            Command::SetPWM { frequency_hz: pulse.frequency_hz(), duty_percent: pulse.duty_pct },
+        ),
+        MicrowaveCommand::SetPulse(None) => (Priority::Normal, Command::PWMOff),
+        MicrowaveCommand::ClearErrors => (Priority::High, Command::ClearErrors),
+    };
+    Some(Message { priority, command })
+}
+
+/// State change for a command that has been handed to the driver but not yet confirmed.
+fn pending_status(command: &MicrowaveCommand) -> Option<String> {
+    match command {
+        MicrowaveCommand::RfOn => Some("RF on requested".into()),
+        MicrowaveCommand::RfOff => Some("RF off requested".into()),
+        MicrowaveCommand::SetFrequencyHz(hz) => Some(format!("frequency {} Hz requested", hz)),
+        MicrowaveCommand::SetPulse(Some(pulse)) => Some(format!(
+            "pulsed {} µs at {:.0} % requested",
+            pulse.period_us, pulse.duty_pct
+        )),
+        MicrowaveCommand::SetPulse(None) => Some("continuous wave requested".into()),
+        MicrowaveCommand::ClearErrors => Some("clearing generator errors".into()),
+        MicrowaveCommand::Connect(_)
+        | MicrowaveCommand::Disconnect
+        | MicrowaveCommand::SetPowerWatts { .. }
+        | MicrowaveCommand::Sweep { .. }
+        | MicrowaveCommand::SetTracking(_) => None,
+    }
+}
+
+/// Report an error reply. Only the status word latches faults: an error code is
+/// not a status mask, and whatever caused it shows up in the next status poll.
+fn apply_error_code(s: &mut MicrowaveState, code: u16) {
+    s.last_error = Some(format!("generator error: {}", describe_error(code)));
+}
+
+/// Apply a driver response to the cached state.
+/// Returns false when the response means the serial link is gone.
+fn apply_response(s: &mut MicrowaveState, response: Response) -> bool {
+    match response {
+        Response::RfOn => {
+            s.enabled = true;
+            s.status = Some("RF on".into());
+            s.last_error = None;
+        }
+        Response::RfOff => {
+            s.enabled = false;
+            s.status = Some("RF off".into());
+            s.last_error = None;
+        }
+        Response::Power(watts) => {
+            s.power_watts = watts;
+            s.last_error = None;
+        }
+        Response::Frequency(hz) => s.frequency_readback_hz = Some(hz),
+        Response::PAPowerWatt { forward, reflected } => {
+            s.forward_power_w = Some(forward);
+            s.reflected_power_w = Some(reflected);
+            s.vswr = vswr(forward, reflected);
+            s.forward_ratio = (forward > 0.0).then(|| ((forward - reflected) / forward).clamp(0.0, 1.0));
+        }
+        Response::PATemp(celsius) => s.temperature_c = Some(celsius),
+        Response::PACurrent(amps) => s.pa_current_a = Some(amps),
+        Response::PAVoltage(volts) => s.pa_voltage_v = Some(volts),
+        Response::Status(word) => {
+            s.status_word = Some(word);
+            s.faults = decode_status(word);
+            // The generator has already cut RF; don't show it as on
+            if let Some(fault) = s.faults.iter().find(|f| f.shuts_down_rf())
+                && s.enabled
+            {
+                s.enabled = false;
+                s.status = Some("RF shut down by generator".into());
+                s.last_error = Some(format!("generator fault: {}", fault.description()));
+            }
+        }
+        Response::MWError(e) => apply_error_code(s, e.0),
+        Response::ReadWriteError(e) => {
+            s.last_error = Some(format!("serial error: {:?}", e));
+            s.status = Some("error".into());
+            return false;
+        }
+        _ => {}
+    }
+    true
+}
+
+/// Next response from the open session; pends forever while disconnected.
+async fn next_response(session: &mut Option<Session>) -> Result<Response, broadcast::error::RecvError> {
+    match session {
+        Some(session) => session.resp_rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Wait for the generator to acknowledge RF off; false if it did not within `RF_OFF_TIMEOUT`.
+async fn confirm_rf_off(session: &mut Session) -> bool {
+    let deadline = Instant::now() + RF_OFF_TIMEOUT;
+    loop {
+        match timeout_at(deadline, session.resp_rx.recv()).await {
+            Ok(Ok(Response::RfOff)) => return true,
+            Ok(Ok(_)) | Ok(Err(broadcast::error::RecvError::Lagged(_))) => {}
+            Ok(Err(broadcast::error::RecvError::Closed)) | Err(_) => return false,
+        }
+    }
+}
+
+/// Send a frequency and record it as the commanded frequency.
+fn send_frequency(session: &Session, s: &mut MicrowaveState, hz: i32) {
+    if session.send(Priority::Normal, Command::SetFrequency(hz)) {
+        s.frequency_hz = Some(hz);
+    }
+}
+
+/// Error message when `hz` lies outside the generator's band.
+fn out_of_band(hz: i32, band_hz: (i32, i32)) -> Option<String> {
+    (hz < band_hz.0 || hz > band_hz.1).then(|| {
+        format!(
+            "{:.3} MHz is outside the generator band {:.3}..{:.3} MHz",
+            hz_to_mhz(hz),
+            hz_to_mhz(band_hz.0),
+            hz_to_mhz(band_hz.1)
+        )
+    })
+}
+
+/// Mirror the ramp into the cached state.
+fn publish_ramp(s: &mut MicrowaveState, ramp: &PowerRamp) {
+    s.target_power_w = ramp.target_w;
+    s.commanded_power_w = ramp.commanded_w;
+}
+
+/// Wait for the running sweep's next deadline; pends forever without a sweep.
+async fn sweep_deadline(sweep: &Option<SweepRun>) {
+    match sweep {
+        Some(run) => sleep_until(run.deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Wait for the next tracking update; pends forever while tracking is off.
+async fn tracking_due(tracker: &Option<Tracker>, due: Instant) {
+    match tracker {
+        Some(_) => sleep_until(due).await,
+        None => std::future::pending().await,
+    }
+}
+
+pub fn tracking_status(tracker: &Tracker, active: bool, reflection_ratio: Option<f32>) -> TrackingStatus {
+    TrackingStatus {
+        enabled: true,
+        active,
+        frequency_hz: Some(tracker.frequency_hz),
+        bounds_hz: Some((tracker.min_hz, tracker.max_hz)),
+        reflection_ratio,
+        at_limit: tracker.at_limit(),
+    }
+}
+
+/// End a sweep: park at the best match (only if it completed), switch RF back off
+/// and restore the power target the sweep replaced.
+/// Returns the frequency the generator was parked at.
+fn finish_sweep(
+    session: &Session,
+    run: &SweepRun,
+    ramp: &mut PowerRamp,
+    s: &mut MicrowaveState,
+    outcome: Result<(), String>,
+) -> Option<i32> {
+    let best = match outcome {
+        Ok(()) => best_match(&s.sweep).map(|p| p.frequency_hz),
+        Err(_) => None,
+    };
+    if let Some(hz) = best {
+        send_frequency(session, s, hz);
+    }
+    session.send(Priority::High, Command::RfOff);
+    ramp.stop();
+    ramp.set_target(run.resume_target_w, run.resume_rate_w_per_s, Instant::now());
+    publish_ramp(s, ramp);
+    s.sweep_in_progress = false;
+    s.sweep_best_hz = best;
+    match outcome {
+        Ok(()) => {
+            s.status = Some(match best {
+                Some(hz) => format!("sweep done, best match {} Hz", hz),
+                None => "sweep done, no forward power measured".into(),
+            });
+        }
+        Err(e) => {
+            s.status = Some("sweep aborted".into());
+            s.last_error = Some(e);
+        }
+    }
+    best
+}
+
+fn mark_disconnected(s: &mut MicrowaveState) {
+    s.connected = false;
+    s.enabled = false;
+    s.sweep_in_progress = false;
+    s.tracking = TrackingStatus::default();
+    s.commanded_power_w = 0.0;
+    s.pulse = None;
+    s.frequency_hz = None;
+    s.frequency_readback_hz = None;
+    s.band_hz = None;
+    // Stale telemetry must not look live
+    s.vswr = None;
+    s.forward_ratio = None;
+    s.temperature_c = None;
+    s.forward_power_w = None;
+    s.reflected_power_w = None;
+    s.pa_current_a = None;
+    s.pa_voltage_v = None;
+    s.status_word = None;
+    s.faults.clear();
+}
 
 pub async fn microwave_control(
-    _serial_port: &str,
-    _baud_rate: u32,
+    config: MicrowaveConfig,
     mut microwave_rx: mpsc::Receiver<MicrowaveCommand>,
     state: Arc<RwLock<MicrowaveState>>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    // TODO: Initialize MiniCircuitDriver on Connect
-    // let mut driver: Option<MiniCircuitDriver> = None;
-    // let mut cmd_tx: Option<tokio::sync::mpsc::UnboundedSender<Message>> = None;
-    // let mut resp_rx: Option<tokio::sync::broadcast::Receiver<Response>> = None;
-    
-    let mut connected = false;
-    let tick = sleep(Duration::from_millis(100)); // ~10 Hz for response checking
-    tokio::pin!(tick);
+    let mut session: Option<Session> = None;
+    let mut sweep: Option<SweepRun> = None;
+    let mut tracker: Option<Tracker> = None;
+    let mut tracking_next = Instant::now();
+    let mut power_read: Option<(PowerRead, Instant)> = None;
+    let mut commanded_hz: Option<i32> = None;
+    let [band_min_mhz, band_max_mhz] = config.band_mhz;
+    let band_hz = (mhz_to_hz(band_min_mhz).unwrap_or(0), mhz_to_hz(band_max_mhz).unwrap_or(i32::MAX));
+    let mut ramp = PowerRamp::new(config.power_ramp_w_per_s, Instant::now());
+    let mut ramp_tick = interval(RAMP_UPDATE);
+    ramp_tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut telemetry = interval(Duration::from_millis(config.telemetry_poll_ms.max(1)));
+    telemetry.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
     loop {
+        // A sweep or tracking loop cannot outlive the driver session
+        if session.is_none() {
+            sweep = None;
+            tracker = None;
+            power_read = None;
+            commanded_hz = None;
+            ramp.stop();
+        }
+
         tokio::select! {
             Some(command) = microwave_rx.recv() => {
                 match command {
-                    MicrowaveCommand::Connect => {
-                        // TODO: Replace with actual MiniCircuitDriver::port_connect(build_target_properties())
-                        connected = true;
+                    MicrowaveCommand::Connect(port) => {
+                        session = None;
+                        let properties = build_target_properties(&MicrowaveConfig {
+                            serial_port: port.clone(),
+                            ..config.clone()
+                        });
+                        let mut driver = MiniCircuitDriver::new(properties);
+                        let result = driver.port_connect();
                         let mut s = state.write().unwrap();
-                        s.connected = true;
-                        s.enabled = false;
-                        s.last_error = None;
-                        s.status = Some("connected (placeholder)".into());
+                        match result {
+                            Ok((cmd_tx, resp_rx)) => {
+                                session = Some(Session { _driver: driver, cmd_tx, resp_rx });
+                                s.connected = true;
+                                s.enabled = false;
+                                s.band_hz = Some(band_hz);
+                                s.last_error = None;
+                                s.status = Some(format!("connected ({})", port));
+                            }
+                            Err(e) => {
+                                mark_disconnected(&mut s);
+                                s.last_error = Some(format!("connect {} failed: {}", port, e));
+                                s.status = Some("error".into());
+                            }
+                        }
                     }
                     MicrowaveCommand::Disconnect => {
-                        // TODO: Drop driver, cmd_tx, resp_rx
-                        connected = false;
+                        // Never leave the generator radiating behind a closed port
+                        let confirmed = match session.take() {
+                            Some(mut open) => open.send(Priority::High, Command::RfOff) && confirm_rf_off(&mut open).await,
+                            None => true,
+                        };
                         let mut s = state.write().unwrap();
-                        s.connected = false;
-                        s.enabled = false;
+                        mark_disconnected(&mut s);
                         s.status = Some("disconnected".into());
-                    }
-                    MicrowaveCommand::RfOn => {
-                        if connected {
-                            // TODO: Send actual RfOn command via cmd_tx
-                            // let msg = Message { priority: Priority::High, command: Command::RfOn };
-                            // cmd_tx.send(msg)?;
-                            let mut s = state.write().unwrap();
-                            s.enabled = true;
-                            s.status = Some("RF on (placeholder)".into());
-                            s.last_error = None;
-                        } else {
-                            let mut s = state.write().unwrap();
-                            s.last_error = Some("not connected".into());
+                        if !confirmed {
+                            s.last_error = Some("RF off was not confirmed before disconnecting".into());
                         }
                     }
-                    MicrowaveCommand::RfOff => {
-                        if connected {
-                            // TODO: Send actual RfOff command via cmd_tx
-                            let mut s = state.write().unwrap();
-                            s.enabled = false;
-                            s.status = Some("RF off (placeholder)".into());
-                            s.last_error = None;
-                        } else {
-                            let mut s = state.write().unwrap();
+                    MicrowaveCommand::Sweep { start_hz, stop_hz, step_hz, power_w } => {
+                        let mut s = state.write().unwrap();
+                        let Some(ref open) = session else {
                             s.last_error = Some("not connected".into());
+                            continue;
+                        };
+                        if sweep.is_some() {
+                            s.last_error = Some("sweep already running".into());
+                            continue;
+                        }
+                        if let Some(e) = out_of_band(start_hz, band_hz).or_else(|| out_of_band(stop_hz, band_hz)) {
+                            s.last_error = Some(format!("sweep: {}", e));
+                            continue;
+                        }
+                        // The sweep owns RF while it runs, so it never takes it over from a
+                        // print or the operator
+                        if s.enabled || ramp.is_active() {
+                            s.last_error = Some("sweep: RF is on; switch it off first".into());
+                            continue;
+                        }
+                        // Same gate as RF on
+                        if let Some(fault) = s.faults.iter().find(|f| f.shuts_down_rf()) {
+                            s.last_error = Some(format!("sweep: {}; clear generator errors first", fault.description()));
+                            continue;
+                        }
+                        match sweep_frequencies(start_hz, stop_hz, step_hz) {
+                            Ok(frequencies) => {
+                                let run = SweepRun {
+                                    frequencies,
+                                    next: 0,
+                                    phase: SweepPhase::RampingUp,
+                                    deadline: Instant::now() + RAMP_UPDATE,
+                                    power_w: power_w.max(0.0),
+                                    resume_target_w: ramp.target_w,
+                                    resume_rate_w_per_s: ramp.rate_w_per_s,
+                                };
+                                // First point goes ahead of RF on; power soft-starts from 0 W
+                                // and the ramp brings it to the sweep power
+                                open.send(Priority::High, Command::SetPower(0.0));
+                                if open.send(Priority::High, Command::SetFrequency(run.frequencies[0])) {
+                                    s.frequency_hz = Some(run.frequencies[0]);
+                                }
+                                open.send(Priority::High, Command::RfOn);
+                                ramp.set_target(run.power_w, config.power_ramp_w_per_s, Instant::now());
+                                ramp.start(0.0, Instant::now());
+                                publish_ramp(&mut s, &ramp);
+                                s.sweep.clear();
+                                s.sweep_in_progress = true;
+                                s.sweep_best_hz = None;
+                                s.last_error = None;
+                                s.status = Some(format!("sweeping {} points", run.frequencies.len()));
+                                sweep = Some(run);
+                            }
+                            Err(e) => {
+                                s.last_error = Some(e);
+                            }
                         }
                     }
-                    MicrowaveCommand::SetPowerWatts(watts) => {
-                        if connected {
-                            // TODO: Send actual SetPower command via cmd_tx
-                            // let msg = Message { priority: Priority::High, command: Command::SetPower(watts) };
-                            let mut s = state.write().unwrap();
-                            s.power_watts = watts.max(0.0);
-                            s.status = Some(format!("power {:.1}W (placeholder)", watts));
-                            s.last_error = None;
-                        } else {
-                            let mut s = state.write().unwrap();
-                            s.last_error = Some("not connected".into());
+                    MicrowaveCommand::SetTracking(settings) => {
+                        let mut s = state.write().unwrap();
+                        match settings {
+                            None => {
+                                tracker = None;
+                                s.tracking = TrackingStatus::default();
+                            }
+                            Some(_) if session.is_none() => {
+                                s.last_error = Some("not connected".into());
+                            }
+                            Some(settings) => {
+                                let problems = validate_tracking(&settings);
+                                match commanded_hz {
+                                    _ if !problems.is_empty() => {
+                                        s.last_error = Some(format!("tracking: {}", problems.join(", ")));
+                                    }
+                                    None => {
+                                        s.last_error = Some("tracking: set a frequency first".into());
+                                    }
+                                    Some(center_hz) => {
+                                        let t = Tracker::new(settings, center_hz, band_hz);
+                                        s.tracking = tracking_status(&t, false, None);
+                                        s.last_error = None;
+                                        tracker = Some(t);
+                                        tracking_next = Instant::now();
+                                    }
+                                }
+                            }
                         }
                     }
-                    MicrowaveCommand::SetFrequencyHz(hz) => {
-                        if connected {
-                            // TODO: Send actual SetFrequency command via cmd_tx
+                    command => {
+                        // Setpoints would corrupt a running sweep; RF off cancels it
+                        if let Some(ref run) = sweep {
+                            if !matches!(command, MicrowaveCommand::RfOff) {
+                                state.write().unwrap().last_error = Some("sweep in progress".into());
+                                continue;
+                            }
+                            if let Some(ref open) = session {
+                                let _ = finish_sweep(open, run, &mut ramp, &mut state.write().unwrap(), Err("sweep cancelled by RF off".into()));
+                            }
+                            sweep = None;
+                        }
+                        // Power is a ramp target, applied by the ramp tick while RF is on
+                        if let MicrowaveCommand::SetPowerWatts { watts, ramp_w_per_s } = command {
                             let mut s = state.write().unwrap();
-                            s.status = Some(format!("freq {} Hz (placeholder)", hz));
+                            if session.is_none() {
+                                s.last_error = Some("not connected".into());
+                                continue;
+                            }
+                            let rate = ramp_w_per_s.filter(|r| r.is_finite() && *r > 0.0).unwrap_or(config.power_ramp_w_per_s);
+                            ramp.set_target(watts, rate, Instant::now());
+                            publish_ramp(&mut s, &ramp);
                             s.last_error = None;
-                        } else {
+                            s.status = Some(format!("power target {:.1} W at {:.1} W/s", ramp.target_w, rate));
+                            continue;
+                        }
+                        if let MicrowaveCommand::SetFrequencyHz(hz) = command
+                            && let Some(e) = out_of_band(hz, band_hz)
+                        {
+                            state.write().unwrap().last_error = Some(e);
+                            continue;
+                        }
+                        if matches!(command, MicrowaveCommand::RfOn) {
                             let mut s = state.write().unwrap();
-                            s.last_error = Some("not connected".into());
+                            if let Some(fault) = s.faults.iter().find(|f| f.shuts_down_rf()) {
+                                s.last_error = Some(format!("{}; clear generator errors first", fault.description()));
+                                continue;
+                            }
+                        }
+                        if let MicrowaveCommand::SetPulse(Some(ref pulse)) = command {
+                            let problems = pulse.validate();
+                            if !problems.is_empty() {
+                                state.write().unwrap().last_error = Some(format!("pulse: {}", problems.join(", ")));
+                                continue;
+                            }
+                        }
+                        let Some(message) = to_message(&command) else {
+                            continue;
+                        };
+                        // Soft start: RF comes on at 0 W and ramps up to the target
+                        if let (MicrowaveCommand::RfOn, Some(open)) = (&command, session.as_ref())
+                            && !ramp.is_active()
+                        {
+                            open.send(Priority::High, Command::SetPower(0.0));
+                            ramp.start(0.0, Instant::now());
+                        }
+                        if matches!(command, MicrowaveCommand::RfOff) {
+                            ramp.stop();
+                        }
+                        let sent = session.as_ref().map(|session| session.cmd_tx.send(message).is_ok());
+                        let mut s = state.write().unwrap();
+                        publish_ramp(&mut s, &ramp);
+                        match sent {
+                            Some(true) => {
+                                s.status = pending_status(&command);
+                                if let MicrowaveCommand::SetPulse(pulse) = command {
+                                    s.pulse = pulse;
+                                }
+                                // Faults still present come back with the next status poll
+                                if matches!(command, MicrowaveCommand::ClearErrors) {
+                                    s.faults.clear();
+                                    s.last_error = None;
+                                }
+                                // A manual frequency is the new center for tracking
+                                if let MicrowaveCommand::SetFrequencyHz(hz) = command {
+                                    s.frequency_hz = Some(hz);
+                                    commanded_hz = Some(hz);
+                                    if let Some(ref mut t) = tracker {
+                                        t.recenter(hz);
+                                        s.tracking = tracking_status(t, s.tracking.active, None);
+                                    }
+                                }
+                            }
+                            Some(false) => {
+                                session = None;
+                                mark_disconnected(&mut s);
+                                s.last_error = Some("driver stopped".into());
+                                s.status = Some("error".into());
+                            }
+                            None => {
+                                s.last_error = Some("not connected".into());
+                            }
                         }
                     }
                 }
             }
 
-            _ = &mut tick => {
-                // TODO: Check for responses from resp_rx
-                // match resp_rx.try_recv() {
-                //     Ok(Response::RfOn) => { s.enabled = true; ... }
-                //     Ok(Response::RfOff) => { s.enabled = false; ... }
-                //     Ok(Response::Power(p)) => { s.power_watts = p; ... }
-                //     Ok(Response::MWError(e)) => { s.last_error = ...; ... }
-                //     Ok(Response::ReadWriteError(e)) => { s.last_error = ...; ... }
-                //     // Add VSWR, temperature, forward_ratio when Response types available
-                //     ...
-                // }
-                
-                tick.as_mut().reset(tokio::time::Instant::now() + Duration::from_millis(100));
+            _ = telemetry.tick(), if session.is_some() && sweep.is_none() => {
+                // Leave the power read out while tracking is waiting on its own
+                let read_power = power_read_idle(&power_read);
+                let sent = session.as_ref().is_some_and(|session| {
+                    TELEMETRY_POLL
+                        .iter()
+                        .filter(|command| read_power || !matches!(command, Command::GetPAPowerWatt))
+                        .all(|command| {
+                            session.cmd_tx.send(Message { priority: Priority::Low, command: command.clone() }).is_ok()
+                        })
+                });
+                if sent && read_power {
+                    power_read = Some((PowerRead::Telemetry, Instant::now() + POWER_READ_TIMEOUT));
+                }
+                if !sent {
+                    session = None;
+                    let mut s = state.write().unwrap();
+                    mark_disconnected(&mut s);
+                    s.last_error = Some("driver stopped".into());
+                    s.status = Some("error".into());
+                }
             }
+
+            _ = ramp_tick.tick(), if session.is_some() && ramp.ramping() => {
+                let Some(open) = session.as_ref() else {
+                    continue;
+                };
+                if let Some(watts) = ramp.advance(Instant::now()) {
+                    open.send(Priority::Normal, Command::SetPower(watts));
+                    publish_ramp(&mut state.write().unwrap(), &ramp);
+                }
+            }
+
+            _ = sweep_deadline(&sweep) => {
+                let (Some(open), Some(run)) = (session.as_ref(), sweep.as_mut()) else {
+                    continue;
+                };
+                match run.phase {
+                    // Points are only read at the sweep power
+                    SweepPhase::RampingUp if ramp.ramping() => run.deadline = Instant::now() + RAMP_UPDATE,
+                    SweepPhase::RampingUp => {
+                        run.phase = SweepPhase::Settling;
+                        run.deadline = Instant::now() + SWEEP_DWELL;
+                    }
+                    SweepPhase::Settling => {
+                        open.send(Priority::Normal, Command::GetPAPowerWatt);
+                        run.phase = SweepPhase::Reading;
+                        run.deadline = Instant::now() + SWEEP_READ_TIMEOUT;
+                    }
+                    SweepPhase::Reading => {
+                        let hz = run.current_hz().unwrap_or_default();
+                        let reason = format!("sweep: no power reading at {} Hz", hz);
+                        let _ = finish_sweep(open, run, &mut ramp, &mut state.write().unwrap(), Err(reason));
+                        sweep = None;
+                    }
+                }
+            }
+
+            _ = tracking_due(&tracker, tracking_next), if session.is_some() && sweep.is_none() && power_read_idle(&power_read) => {
+                let (Some(open), Some(t)) = (session.as_ref(), tracker.as_ref()) else {
+                    continue;
+                };
+                tracking_next = Instant::now() + Duration::from_millis(t.settings.interval_ms.max(1));
+                let mut s = state.write().unwrap();
+                if s.enabled {
+                    if open.send(Priority::Normal, Command::GetPAPowerWatt) {
+                        power_read = Some((PowerRead::Tracking, Instant::now() + POWER_READ_TIMEOUT));
+                    }
+                } else {
+                    // Hold position while RF is off
+                    s.tracking.active = false;
+                }
+            }
+
+            response = next_response(&mut session) => {
+                match response {
+                    Ok(response) => {
+                        let reading = match response {
+                            Response::PAPowerWatt { forward, reflected } => Some((forward, reflected)),
+                            _ => None,
+                        };
+                        let mut s = state.write().unwrap();
+                        if !apply_response(&mut s, response) {
+                            session = None;
+                            mark_disconnected(&mut s);
+                            continue;
+                        }
+                        // No ramping into a generator that has shut RF down
+                        if ramp.is_active() && s.faults.iter().any(|f| f.shuts_down_rf()) {
+                            ramp.stop();
+                            publish_ramp(&mut s, &ramp);
+                        }
+
+                        // Outside a sweep a power reply answers the outstanding telemetry or tracking read
+                        let asker = match (reading, &sweep) {
+                            (Some(_), None) => power_read.take().map(|(asker, _)| asker),
+                            _ => None,
+                        };
+
+                        // Record the sweep point this reading was requested for
+                        if let (Some(open), Some(run), Some((forward_w, reflected_w))) =
+                            (session.as_ref(), sweep.as_mut(), reading)
+                            && run.phase == SweepPhase::Reading
+                        {
+                            let frequency_hz = run.current_hz().unwrap_or_default();
+                            s.sweep.push(SweepPoint { frequency_hz, forward_w, reflected_w });
+                            run.next += 1;
+                            match run.current_hz() {
+                                Some(hz) => {
+                                    send_frequency(open, &mut s, hz);
+                                    run.phase = SweepPhase::Settling;
+                                    run.deadline = Instant::now() + SWEEP_DWELL;
+                                }
+                                None => {
+                                    if let Some(best_hz) = finish_sweep(open, run, &mut ramp, &mut s, Ok(())) {
+                                        commanded_hz = Some(best_hz);
+                                        if let Some(ref mut t) = tracker {
+                                            t.recenter(best_hz);
+                                        }
+                                    }
+                                    sweep = None;
+                                }
+                            }
+                        } else if let (Some(open), Some(t), Some((forward_w, reflected_w))) =
+                            (session.as_ref(), tracker.as_mut(), reading)
+                            && asker == Some(PowerRead::Tracking)
+                            && forward_w > 0.0
+                        {
+                            // Tracking update: one perturb-and-observe step
+                            let ratio = reflected_w / forward_w;
+                            let next_hz = t.update(ratio);
+                            send_frequency(open, &mut s, next_hz);
+                            commanded_hz = Some(next_hz);
+                            s.tracking = tracking_status(t, true, Some(ratio));
+                        }
+                    }
+                    // Missed some responses; later ones still reflect the generator state
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => {
+                        session = None;
+                        let mut s = state.write().unwrap();
+                        mark_disconnected(&mut s);
+                        s.last_error = Some("driver closed the connection".into());
+                        s.status = Some("error".into());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vswr_from_power() {
+        assert_eq!(vswr(0.0, 0.0), None);
+        assert_eq!(vswr(100.0, 0.0), Some(1.0));
+        // |Γ| = 0.5 -> VSWR 3
+        assert!((vswr(100.0, 25.0).unwrap() - 3.0).abs() < 1e-5);
+        assert_eq!(vswr(100.0, 100.0), Some(f32::INFINITY));
+    }
+
+    #[test]
+    fn test_error_reply_latches_no_faults() {
+        let mut s = MicrowaveState::default();
+        // 0x0410 shares bits with the external-shutdown and reflected-power flags
+        for code in [0, 0x0410] {
+            apply_error_code(&mut s, code);
+            assert!(s.faults.is_empty());
+            assert!(s.last_error.as_deref().unwrap().starts_with("generator error: "));
+            assert_ne!(s.last_error.as_deref(), Some("generator error: "));
         }
     }
+
+    #[test]
+    fn test_one_power_read_at_a_time() {
+        assert!(power_read_idle(&None));
+        let outstanding = Some((PowerRead::Tracking, Instant::now() + POWER_READ_TIMEOUT));
+        assert!(!power_read_idle(&outstanding));
+        // A lost reply must not hold off telemetry and tracking for good
+        let expired = Some((PowerRead::Telemetry, Instant::now() - Duration::from_millis(1)));
+        assert!(power_read_idle(&expired));
+    }
 }
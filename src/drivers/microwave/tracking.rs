@@ -0,0 +1,130 @@
+/*
+ * Closed-loop frequency tracking for the microwave driver task.
+ *
+ * Perturb-and-observe: after every reading the frequency moves one step; if the
+ * reflection ratio (reflected / forward) got worse than the previous reading the
+ * direction reverses. Near the minimum this dithers ±step around it, and as the
+ * load heats and its resonance drifts the dither follows.
+ *
+ * Moves are clamped to [center - span, center + span] around the frequency that
+ * was set when tracking engaged (or last re-centered by a manual setpoint/sweep),
+ * and never leave the generator's band.
+ *
+ * This module holds the control law; `task.rs` does the I/O.
+ */
+
+use crate::config::config::TrackingSettings;
+
+#[derive(Debug, Clone)]
+pub struct Tracker {
+    pub settings: TrackingSettings,
+    pub frequency_hz: i32,
+    pub min_hz: i32,
+    pub max_hz: i32,
+    band_hz: (i32, i32),
+    direction: i32,
+    last_ratio: Option<f32>,
+}
+
+impl Tracker {
+    pub fn new(settings: TrackingSettings, center_hz: i32, band_hz: (i32, i32)) -> Self {
+        let mut tracker = Self {
+            settings,
+            frequency_hz: center_hz,
+            min_hz: center_hz,
+            max_hz: center_hz,
+            band_hz,
+            direction: 1,
+            last_ratio: None,
+        };
+        tracker.recenter(center_hz);
+        tracker
+    }
+
+    /// Restart around a new frequency (manual setpoint or sweep result).
+    pub fn recenter(&mut self, center_hz: i32) {
+        let span = self.settings.span_hz.max(0);
+        self.frequency_hz = center_hz;
+        self.min_hz = center_hz.saturating_sub(span).max(self.band_hz.0).max(0);
+        self.max_hz = center_hz.saturating_add(span).min(self.band_hz.1);
+        self.last_ratio = None;
+    }
+
+    /// Whether the last move was stopped by a bound.
+    pub fn at_limit(&self) -> bool {
+        self.frequency_hz <= self.min_hz || self.frequency_hz >= self.max_hz
+    }
+
+    /// Feed the reflection ratio measured at `frequency_hz`; returns the next frequency.
+    pub fn update(&mut self, ratio: f32) -> i32 {
+        if let Some(last) = self.last_ratio
+            && ratio > last
+        {
+            self.direction = -self.direction;
+        }
+        self.last_ratio = Some(ratio);
+
+        let step = self.settings.step_hz.max(1);
+        let mut next = self.frequency_hz.saturating_add(self.direction * step);
+        if next > self.max_hz || next < self.min_hz {
+            // Bounce off the bound instead of sticking to it
+            self.direction = -self.direction;
+            next = next.clamp(self.min_hz, self.max_hz);
+        }
+        self.frequency_hz = next;
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(step_hz: i32, span_hz: i32) -> TrackingSettings {
+        TrackingSettings { step_hz, interval_ms: 500, span_hz }
+    }
+
+    /// V-shaped reflection curve with its minimum at `resonance_hz`.
+    fn ratio_at(frequency_hz: i32, resonance_hz: i32) -> f32 {
+        (frequency_hz - resonance_hz).abs() as f32 / 1_000.0
+    }
+
+    #[test]
+    fn test_follows_drifting_resonance() {
+        let mut tracker = Tracker::new(settings(100, 10_000), 100_000, (0, i32::MAX));
+        let mut resonance = 100_500;
+        for _ in 0..40 {
+            let f = tracker.frequency_hz;
+            tracker.update(ratio_at(f, resonance));
+        }
+        assert!((tracker.frequency_hz - resonance).abs() <= 200);
+
+        // Resonance drifts down; the dither follows it
+        for _ in 0..100 {
+            resonance -= 20;
+            let f = tracker.frequency_hz;
+            tracker.update(ratio_at(f, resonance));
+        }
+        assert!((tracker.frequency_hz - resonance).abs() <= 300);
+    }
+
+    #[test]
+    fn test_stays_within_bounds() {
+        let mut tracker = Tracker::new(settings(100, 300), 1_000, (0, i32::MAX));
+        // Minimum far outside the bounds: tracker hits the bound and dithers there
+        for _ in 0..20 {
+            let f = tracker.frequency_hz;
+            tracker.update(ratio_at(f, 5_000));
+            assert!((700..=1_300).contains(&tracker.frequency_hz));
+        }
+        assert!(tracker.frequency_hz >= 1_100);
+
+        tracker.recenter(2_000);
+        assert_eq!((tracker.min_hz, tracker.max_hz), (1_700, 2_300));
+        assert_eq!(tracker.frequency_hz, 2_000);
+
+        // Bounds never leave the generator's band
+        let tracker = Tracker::new(settings(100, 300), 1_000, (900, 5_000));
+        assert_eq!((tracker.min_hz, tracker.max_hz), (900, 1_300));
+    }
+}
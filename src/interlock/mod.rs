@@ -0,0 +1,6 @@
+pub mod types;
+pub mod monitor;
+pub mod task;
+
+pub use types::{InterlockCommand, InterlockConfig, InterlockKind, InterlockState, InterlockTrip};
+pub use task::interlock_task;
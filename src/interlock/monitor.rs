@@ -0,0 +1,125 @@
+use tokio::time::{Duration, Instant};
+
+use crate::config::config::MicrowaveState;
+use crate::interlock::{InterlockConfig, InterlockKind};
+
+/// VSWR is not meaningful at very low forward power, where detector noise dominates.
+const VSWR_MIN_FORWARD_W: f32 = 1.0;
+
+/// Limit exceeded at one reading: (kind, value, limit).
+pub type Violation = (InterlockKind, f32, f32);
+
+/// Tracks how long each limit has been exceeded.
+pub struct InterlockMonitor {
+    config: InterlockConfig,
+    exceeded_since: [Option<Instant>; 3],
+}
+
+fn index(kind: InterlockKind) -> usize {
+    match kind {
+        InterlockKind::Vswr => 0,
+        InterlockKind::ReflectedPower => 1,
+        InterlockKind::PaTemperature => 2,
+    }
+}
+
+fn reading(state: &MicrowaveState, kind: InterlockKind) -> Option<f32> {
+    match kind {
+        InterlockKind::Vswr => {
+            let forward = state.forward_power_w.unwrap_or(0.0);
+            state.vswr.filter(|_| forward >= VSWR_MIN_FORWARD_W)
+        }
+        InterlockKind::ReflectedPower => state.reflected_power_w,
+        InterlockKind::PaTemperature => state.temperature_c,
+    }
+}
+
+impl InterlockMonitor {
+    pub fn new(config: InterlockConfig) -> Self {
+        Self { config, exceeded_since: [None; 3] }
+    }
+
+    /// Limits exceeded right now, ignoring the hold time.
+    pub fn violations(&self, state: &MicrowaveState) -> Vec<Violation> {
+        InterlockKind::ALL
+            .into_iter()
+            .filter_map(|kind| {
+                let limit = self.config.limit(kind)?;
+                let value = reading(state, kind)?;
+                (value > limit).then_some((kind, value, limit))
+            })
+            .collect()
+    }
+
+    /// Update the hold timers; returns the first limit exceeded for at least `hold_s`.
+    pub fn check(&mut self, state: &MicrowaveState, now: Instant) -> Option<Violation> {
+        let violations = self.violations(state);
+        let hold = Duration::from_secs_f32(self.config.hold_s.max(0.0));
+        for kind in InterlockKind::ALL {
+            if !violations.iter().any(|(k, _, _)| *k == kind) {
+                self.exceeded_since[index(kind)] = None;
+            }
+        }
+        violations.into_iter().find(|(kind, _, _)| {
+            let since = self.exceeded_since[index(*kind)].get_or_insert(now);
+            now.duration_since(*since) >= hold
+        })
+    }
+
+    pub fn reset(&mut self) {
+        self.exceeded_since = [None; 3];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(forward_w: f32, reflected_w: f32, vswr: f32, temperature_c: f32) -> MicrowaveState {
+        MicrowaveState {
+            forward_power_w: Some(forward_w),
+            reflected_power_w: Some(reflected_w),
+            vswr: Some(vswr),
+            temperature_c: Some(temperature_c),
+            ..MicrowaveState::default()
+        }
+    }
+
+    #[test]
+    fn test_trips_only_after_hold_time() {
+        let mut monitor = InterlockMonitor::new(InterlockConfig { hold_s: 0.5, ..InterlockConfig::default() });
+        let t0 = Instant::now();
+        let hot = state(100.0, 10.0, 1.9, 85.0);
+        assert_eq!(monitor.check(&hot, t0), None);
+        assert_eq!(monitor.check(&hot, t0 + Duration::from_millis(400)), None);
+        let (kind, value, limit) = monitor.check(&hot, t0 + Duration::from_millis(500)).unwrap();
+        assert_eq!(kind, InterlockKind::PaTemperature);
+        assert_eq!((value, limit), (85.0, 70.0));
+    }
+
+    #[test]
+    fn test_hold_timer_resets_when_back_in_limits() {
+        let mut monitor = InterlockMonitor::new(InterlockConfig { hold_s: 0.5, ..InterlockConfig::default() });
+        let t0 = Instant::now();
+        let mismatched = state(100.0, 60.0, 4.0, 40.0);
+        let fine = state(100.0, 5.0, 1.5, 40.0);
+        assert_eq!(monitor.check(&mismatched, t0), None);
+        assert_eq!(monitor.check(&fine, t0 + Duration::from_millis(300)), None);
+        assert_eq!(monitor.check(&mismatched, t0 + Duration::from_millis(600)), None);
+        let trip = monitor.check(&mismatched, t0 + Duration::from_millis(1100)).unwrap();
+        assert_eq!(trip.0, InterlockKind::Vswr);
+    }
+
+    #[test]
+    fn test_disabled_limits_and_low_power_vswr_ignored() {
+        let config = InterlockConfig {
+            max_pa_temp_c: None,
+            hold_s: 0.0,
+            ..InterlockConfig::default()
+        };
+        let monitor = InterlockMonitor::new(config);
+        // Hot PA with temperature interlock off; infinite VSWR at 0.5 W forward
+        assert!(monitor.violations(&state(0.5, 0.5, f32::INFINITY, 120.0)).is_empty());
+        assert!(monitor.violations(&MicrowaveState::default()).is_empty());
+    }
+}
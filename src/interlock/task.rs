@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use std::sync::RwLock;
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant, interval};
+
+use crate::controllers::MicrowaveController;
+use crate::interlock::monitor::InterlockMonitor;
+use crate::interlock::{InterlockCommand, InterlockConfig, InterlockState, InterlockTrip};
+use crate::print_engine::PrintCommand;
+
+/// Minimum time between repeated RF off commands while latched.
+const RF_OFF_RETRY: Duration = Duration::from_millis(500);
+
+/// RF safety supervisor. Watches the cached microwave state; when a limit has been
+/// exceeded for the configured hold time it turns RF off, latches the trip and
+/// pauses the print engine. While latched RF is kept off. The latch clears only on
+/// operator acknowledgement, and only once every reading is back within limits.
+pub async fn interlock_task(
+    mut rx: mpsc::Receiver<InterlockCommand>,
+    state: Arc<RwLock<InterlockState>>,
+    microwave: Arc<Box<dyn MicrowaveController + Send + Sync>>,
+    print_tx: mpsc::Sender<PrintCommand>,
+    config: InterlockConfig,
+) {
+    let mut tick = interval(Duration::from_millis(50));
+    let mut monitor = InterlockMonitor::new(config);
+    let mut last_rf_off: Option<Instant> = None;
+
+    loop {
+        tokio::select! {
+            Some(cmd) = rx.recv() => {
+                match cmd {
+                    InterlockCommand::Acknowledge => {
+                        if state.read().unwrap().tripped.is_none() {
+                            continue;
+                        }
+                        let still_exceeded = monitor.violations(&microwave.state());
+                        if let Some((kind, value, limit)) = still_exceeded.first() {
+                            state.write().unwrap().last_error = Some(format!(
+                                "cannot acknowledge: {} {:.1}{} still above {:.1}{}",
+                                kind.label(), value, kind.unit(), limit, kind.unit()
+                            ));
+                            continue;
+                        }
+                        {
+                            let mut s = state.write().unwrap();
+                            s.tripped = None;
+                            s.last_error = None;
+                        }
+                        monitor.reset();
+                        last_rf_off = None;
+                        let _ = print_tx.send(PrintCommand::InterlockCleared).await;
+                    }
+                }
+            }
+
+            _ = tick.tick() => {
+                let now = Instant::now();
+                let microwave_state = microwave.state();
+                let latched = state.read().unwrap().tripped.is_some();
+
+                if latched {
+                    // Keep RF off until acknowledged, whoever turned it on
+                    if microwave_state.enabled && last_rf_off.is_none_or(|t| now.duration_since(t) >= RF_OFF_RETRY) {
+                        microwave.rf_off();
+                        last_rf_off = Some(now);
+                    }
+                    continue;
+                }
+
+                if let Some((kind, value, limit)) = monitor.check(&microwave_state, now) {
+                    microwave.rf_off();
+                    last_rf_off = Some(now);
+                    let trip = InterlockTrip { kind, value, limit, at: chrono::Local::now() };
+                    let reason = trip.to_string();
+                    state.write().unwrap().tripped = Some(trip);
+                    let _ = print_tx.send(PrintCommand::InterlockTripped(reason)).await;
+                }
+            }
+        }
+    }
+}
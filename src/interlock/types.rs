@@ -0,0 +1,96 @@
+use std::fmt;
+use serde::Deserialize;
+
+/// Quantity an interlock watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterlockKind {
+    Vswr,
+    ReflectedPower,
+    PaTemperature,
+}
+
+impl InterlockKind {
+    pub const ALL: [InterlockKind; 3] = [InterlockKind::Vswr, InterlockKind::ReflectedPower, InterlockKind::PaTemperature];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            InterlockKind::Vswr => "VSWR",
+            InterlockKind::ReflectedPower => "reflected power",
+            InterlockKind::PaTemperature => "PA temperature",
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            InterlockKind::Vswr => "",
+            InterlockKind::ReflectedPower => " W",
+            InterlockKind::PaTemperature => " °C",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum InterlockCommand {
+    Acknowledge,  // operator acknowledgement; only clears once readings are back within limits
+}
+
+/// Limits enforced by the interlock supervisor (the `interlock` config section).
+/// A limit of `None` disables that interlock.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InterlockConfig {
+    pub max_vswr: Option<f32>,
+    pub max_reflected_w: Option<f32>,
+    pub max_pa_temp_c: Option<f32>,
+    pub hold_s: f32,  // how long a limit must be exceeded before tripping
+}
+
+impl Default for InterlockConfig {
+    fn default() -> Self {
+        Self {
+            max_vswr: Some(3.0),
+            max_reflected_w: Some(50.0),
+            max_pa_temp_c: Some(70.0),
+            hold_s: 0.5,
+        }
+    }
+}
+
+impl InterlockConfig {
+    pub fn limit(&self, kind: InterlockKind) -> Option<f32> {
+        match kind {
+            InterlockKind::Vswr => self.max_vswr,
+            InterlockKind::ReflectedPower => self.max_reflected_w,
+            InterlockKind::PaTemperature => self.max_pa_temp_c,
+        }
+    }
+}
+
+/// Latched interlock trip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterlockTrip {
+    pub kind: InterlockKind,
+    pub value: f32,
+    pub limit: f32,
+    pub at: chrono::DateTime<chrono::Local>,
+}
+
+impl fmt::Display for InterlockTrip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:.1}{} exceeded limit {:.1}{}",
+            self.kind.label(),
+            self.value,
+            self.kind.unit(),
+            self.limit,
+            self.kind.unit()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InterlockState {
+    pub tripped: Option<InterlockTrip>,  // latched until acknowledged
+    pub last_error: Option<String>,      // e.g. acknowledgement refused
+}
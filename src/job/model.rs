@@ -2,12 +2,19 @@ use std::fmt;
 
 /// A single step in a motion job.
 /// Represents one row of movement commands.
+/// Process columns are optional; once a row sets a value, later rows that
+/// leave it blank inherit it, so `None` means "never set in this job".
 #[derive(Clone, Debug, PartialEq)]
 pub struct Step {
     pub x_mm: f32,
     pub y_mm: f32,
     pub z_mm: f32,
-    // Future columns can be added here (e.g., power_w, pressure_psi)
+    pub power_w: Option<f32>,      // microwave power for this segment
+    pub pressure_psi: Option<f32>, // pneumatic pressure setpoint for this segment
+    pub feed_mm_min: Option<f32>,  // feed rate for the G1 move
+    pub duty_pct: Option<f32>,     // pulsed RF duty cycle; 100 runs continuous wave
+    pub pulse_period_us: Option<f32>, // pulse period, used while duty_pct < 100
+    pub channel_pressures_psi: Vec<Option<f32>>, // setpoints of the channels in Job::pressure_channels
 }
 
 /// A motion job parsed from a CSV file.
@@ -19,6 +26,7 @@ pub struct Job {
     pub row_count: usize,        // number of parsed data rows (including header if counted)
     pub first_step: Option<Step>, // convenience preview of first step
     pub warnings: Vec<String>,   // warnings encountered during parsing (e.g., large file, many rows)
+    pub pressure_channels: Vec<String>, // pressure channels the job addresses by name
 }
 
 impl Job {
@@ -37,6 +45,7 @@ impl Job {
             row_count,
             first_step,
             warnings,
+            pressure_channels: Vec::new(),
         }
     }
 }
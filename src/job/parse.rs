@@ -26,6 +26,17 @@ const MANY_ROWS_THRESHOLD: usize = 100_000;
 /// - y or y_mm -> Step.y_mm
 /// - z or z_mm -> Step.z_mm
 ///
+/// Optional columns (same matching rules):
+/// - power_w or power -> Step.power_w
+/// - pressure_psi or pressure -> Step.pressure_psi
+/// - feed_mm_min or feed -> Step.feed_mm_min
+/// - duty_pct or duty -> Step.duty_pct (pulsed RF; 100 is continuous wave)
+/// - pulse_period_us or period_us -> Step.pulse_period_us
+/// - pressure_<channel>_psi or pressure_<channel> -> Step.channel_pressures_psi,
+///   for the pressure channel of that name (Job.pressure_channels)
+///
+/// A blank cell in an optional column inherits the value from the previous row.
+///
 /// Row numbers in errors are 1-based data row indices (excluding header).
 pub fn load_job_from_csv_path(path: &Path, max_rows: usize) -> Result<Job, JobParseError> {
     // Check file size before parsing
@@ -91,10 +102,27 @@ fn load_job_from_csv_reader<R: Read>(
     let z_idx = find_column_index(headers, &["z", "z_mm"])
         .ok_or_else(|| JobParseError::MissingColumn("z or z_mm".to_string()))?;
     
+    // Optional per-step process columns
+    let power_idx = find_column_index(headers, &["power_w", "power"]);
+    let pressure_idx = find_column_index(headers, &["pressure_psi", "pressure"]);
+    let feed_idx = find_column_index(headers, &["feed_mm_min", "feed"]);
+    let duty_idx = find_column_index(headers, &["duty_pct", "duty"]);
+    let period_idx = find_column_index(headers, &["pulse_period_us", "period_us"]);
+    let (channel_names, channel_idx) = find_pressure_channels(headers, pressure_idx);
+    let channel_columns: Vec<String> = channel_idx.iter().map(|idx| headers[*idx].trim().to_string()).collect();
+    
     // Parse data rows
     let mut steps = Vec::new();
     let mut data_row = 0; // 1-based data row index (excludes header)
     
+    // Last seen values of the optional columns, inherited by blank cells
+    let mut power_w = None;
+    let mut pressure_psi = None;
+    let mut feed_mm_min = None;
+    let mut duty_pct = None;
+    let mut pulse_period_us = None;
+    let mut channel_pressures_psi = vec![None; channel_idx.len()];
+    
     for result in reader.records() {
         data_row += 1;
         
@@ -113,7 +141,27 @@ fn load_job_from_csv_reader<R: Read>(
         let y_mm = parse_float(&record, y_idx, "y", data_row)?;
         let z_mm = parse_float(&record, z_idx, "z", data_row)?;
         
-        steps.push(Step { x_mm, y_mm, z_mm });
+        power_w = parse_optional_float(&record, power_idx, "power_w", data_row)?.or(power_w);
+        pressure_psi = parse_optional_float(&record, pressure_idx, "pressure_psi", data_row)?.or(pressure_psi);
+        feed_mm_min = parse_optional_float(&record, feed_idx, "feed_mm_min", data_row)?.or(feed_mm_min);
+        duty_pct = parse_optional_float(&record, duty_idx, "duty_pct", data_row)?.or(duty_pct);
+        pulse_period_us = parse_optional_float(&record, period_idx, "pulse_period_us", data_row)?.or(pulse_period_us);
+        for (i, idx) in channel_idx.iter().enumerate() {
+            channel_pressures_psi[i] =
+                parse_optional_float(&record, Some(*idx), &channel_columns[i], data_row)?.or(channel_pressures_psi[i]);
+        }
+        
+        steps.push(Step {
+            x_mm,
+            y_mm,
+            z_mm,
+            power_w,
+            pressure_psi,
+            feed_mm_min,
+            duty_pct,
+            pulse_period_us,
+            channel_pressures_psi: channel_pressures_psi.clone(),
+        });
     }
     
     // Check if empty
@@ -131,7 +179,32 @@ fn load_job_from_csv_reader<R: Read>(
     }
     
     // Build Job
-    Ok(Job::with_warnings(filename.to_string(), steps, warnings))
+    let mut job = Job::with_warnings(filename.to_string(), steps, warnings);
+    job.pressure_channels = channel_names;
+    Ok(job)
+}
+
+/// Find the named pressure channel columns ("pressure_<name>_psi" or
+/// "pressure_<name>"), skipping the plain pressure column.
+/// Returns the channel names (lowercase) and their column indices.
+fn find_pressure_channels(headers: &csv::StringRecord, pressure_idx: Option<usize>) -> (Vec<String>, Vec<usize>) {
+    let mut names = Vec::new();
+    let mut indices = Vec::new();
+    for (idx, header) in headers.iter().enumerate() {
+        if Some(idx) == pressure_idx {
+            continue;
+        }
+        let header_lower = header.trim().to_lowercase();
+        let Some(rest) = header_lower.strip_prefix("pressure_") else {
+            continue;
+        };
+        let name = rest.strip_suffix("_psi").unwrap_or(rest);
+        if !name.is_empty() && name != "psi" && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+            indices.push(idx);
+        }
+    }
+    (names, indices)
 }
 
 /// Find the index of a column matching one of the given names (case-insensitive, trimmed).
@@ -166,6 +239,25 @@ fn parse_float(
     })
 }
 
+/// Parse an optional float column.
+/// Returns `Ok(None)` when the column is absent or the cell is blank.
+fn parse_optional_float(
+    record: &csv::StringRecord,
+    idx: Option<usize>,
+    column_name: &str,
+    row: usize,
+) -> Result<Option<f32>, JobParseError> {
+    let Some(idx) = idx else {
+        return Ok(None);
+    };
+    match record.get(idx) {
+        Some(value) if !value.trim().is_empty() => {
+            parse_float(record, idx, column_name, row).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -349,6 +441,110 @@ mod tests {
         assert_eq!(job.steps[0].z_mm, 3.0);
     }
 
+    #[test]
+    fn test_optional_columns_absent() {
+        let csv = "x,y,z\n1.0,2.0,3.0";
+        let job = parse_csv(csv, 1000).unwrap();
+        
+        assert_eq!(job.steps[0].power_w, None);
+        assert_eq!(job.steps[0].pressure_psi, None);
+        assert_eq!(job.steps[0].feed_mm_min, None);
+        assert_eq!(job.steps[0].duty_pct, None);
+        assert_eq!(job.steps[0].pulse_period_us, None);
+        assert!(job.steps[0].channel_pressures_psi.is_empty());
+        assert!(job.pressure_channels.is_empty());
+    }
+
+    #[test]
+    fn test_optional_columns_parsed() {
+        let csv = "x,y,z,power_w,pressure_psi,feed_mm_min\n1.0,2.0,3.0,150,12.5,600";
+        let job = parse_csv(csv, 1000).unwrap();
+        
+        assert_eq!(job.steps[0].power_w, Some(150.0));
+        assert_eq!(job.steps[0].pressure_psi, Some(12.5));
+        assert_eq!(job.steps[0].feed_mm_min, Some(600.0));
+    }
+
+    #[test]
+    fn test_optional_columns_short_headers() {
+        let csv = "x,y,z,Power,Pressure,Feed\n1.0,2.0,3.0,100,10,1200";
+        let job = parse_csv(csv, 1000).unwrap();
+        
+        assert_eq!(job.steps[0].power_w, Some(100.0));
+        assert_eq!(job.steps[0].pressure_psi, Some(10.0));
+        assert_eq!(job.steps[0].feed_mm_min, Some(1200.0));
+    }
+
+    #[test]
+    fn test_blank_optional_cells_inherit_previous() {
+        let csv = "x,y,z,power_w,pressure_psi,feed_mm_min\n\
+                   0,0,0,,,\n\
+                   1,0,0,100,10,600\n\
+                   2,0,0,,12,\n\
+                   3,0,0,120, ,900";
+        let job = parse_csv(csv, 1000).unwrap();
+        
+        assert_eq!(job.steps.len(), 4);
+        assert_eq!(job.steps[0].power_w, None);
+        assert_eq!(job.steps[0].pressure_psi, None);
+        assert_eq!(job.steps[1].power_w, Some(100.0));
+        assert_eq!(job.steps[2].power_w, Some(100.0));
+        assert_eq!(job.steps[2].pressure_psi, Some(12.0));
+        assert_eq!(job.steps[2].feed_mm_min, Some(600.0));
+        assert_eq!(job.steps[3].power_w, Some(120.0));
+        assert_eq!(job.steps[3].pressure_psi, Some(12.0));
+        assert_eq!(job.steps[3].feed_mm_min, Some(900.0));
+    }
+
+    #[test]
+    fn test_pulse_columns_parsed_and_inherited() {
+        let csv = "x,y,z,duty,period_us\n\
+                   0,0,0,40,2000\n\
+                   1,0,0,,\n\
+                   2,0,0,100,";
+        let job = parse_csv(csv, 1000).unwrap();
+        
+        assert_eq!(job.steps[0].duty_pct, Some(40.0));
+        assert_eq!(job.steps[0].pulse_period_us, Some(2000.0));
+        assert_eq!(job.steps[1].duty_pct, Some(40.0));
+        assert_eq!(job.steps[2].duty_pct, Some(100.0));
+        assert_eq!(job.steps[2].pulse_period_us, Some(2000.0));
+    }
+
+    #[test]
+    fn test_named_pressure_channels() {
+        let csv = "x,y,z,pressure_psi,Pressure_Sheath_psi,pressure_aux\n\
+                   0,0,0,20,5,\n\
+                   1,0,0,,6,2\n\
+                   2,0,0,22,,";
+        let job = parse_csv(csv, 1000).unwrap();
+        
+        assert_eq!(job.pressure_channels, vec!["sheath", "aux"]);
+        assert_eq!(job.steps[0].pressure_psi, Some(20.0));
+        assert_eq!(job.steps[0].channel_pressures_psi, vec![Some(5.0), None]);
+        assert_eq!(job.steps[1].channel_pressures_psi, vec![Some(6.0), Some(2.0)]);
+        assert_eq!(job.steps[2].pressure_psi, Some(22.0));
+        assert_eq!(job.steps[2].channel_pressures_psi, vec![Some(6.0), Some(2.0)]);
+
+        let result = parse_csv("x,y,z,pressure_sheath\n0,0,0,lots", 1000);
+        assert!(matches!(result, Err(JobParseError::BadNumber { ref column, .. }) if column == "pressure_sheath"));
+    }
+
+    #[test]
+    fn test_bad_number_in_optional_column() {
+        let csv = "x,y,z,power_w\n1.0,2.0,3.0,high";
+        let result = parse_csv(csv, 1000);
+        
+        match result {
+            Err(JobParseError::BadNumber { column, row, value }) => {
+                assert_eq!(column, "power_w");
+                assert_eq!(row, 1);
+                assert_eq!(value, "high");
+            }
+            _ => panic!("Expected BadNumber error, got {:?}", result),
+        }
+    }
+
     #[test]
     fn test_warnings_empty_by_default() {
         let csv = "x,y,z\n1.0,2.0,3.0";
@@ -14,11 +14,13 @@ mod config;
 mod controllers;
 mod job;
 mod print_engine;
+mod interlock;
 
 
 
 // imports
 use crate::config::config::*;
+use crate::config::file::AppConfig;
 use crate::ui::app::AppUI;
 // wiring is centralized in AppUI::new via feature flags
 use eframe::egui;
@@ -32,6 +34,15 @@ use eframe::egui;
 #[tokio::main()]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
+    // Runtime config: --config <path>, else meteorite.json if present, else defaults
+    let app_config = match AppConfig::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Config error: {}", e);
+            std::process::exit(2);
+        }
+    };
+
     // Device tasks and channels are set up by AppUI::new()
 
 
@@ -45,7 +56,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "AppUI",
         options,
         Box::new(|_cc| 
-            Ok(Box::new(AppUI::new()))
+            Ok(Box::new(AppUI::new(app_config)))
         ),
     )?;
     tokio::signal::ctrl_c().await?;
@@ -0,0 +1,1288 @@
+/*!
+ * Print engine state machine
+ *
+ * `PrintEngine` owns every PrintStatus transition. Commands and ticks are fed in
+ * by `print_engine_task`; each transition goes through `enter()`, which runs the
+ * entry actions for the new state and mirrors it into the shared `PrintState`.
+ *
+ * Guards (checked every tick):
+ * - Homing:           motion settled after G28              -> Traveling
+ * - Traveling:        motion settled at the start position  -> Preheating
+ * - Preheating:       RF on, ramped to power, `preheat_s`    -> PressureSettling
+ * - PressureSettling: every driven channel within `pressure_tolerance_psi` -> Running
+ * - Running:          last step sent                        -> Finishing
+ * - Finishing:        postamble complete                    -> Idle
+ *
+ * Timeouts and lost connections move any active state to Faulted, which turns
+ * outputs off and stays latched until `PrintCommand::ClearFault`.
+ *
+ * An RF interlock trip (`PrintCommand::InterlockTripped`) pauses a run that drives
+ * RF. While the interlock is latched the engine will not enter Preheating, and
+ * Resume/Start of RF runs are refused until `PrintCommand::InterlockCleared`.
+ *
+ * The run mode decides which controllers are driven; stages for the others are
+ * skipped (e.g. a dry run goes Traveling -> Running, RF-only burn-in starts at
+ * Preheating and paces steps by time instead of streaming G-code).
+ *
+ * While Running, a step that changes power, pulse mode or pressure waits for the
+ * moves already queued on the Duet to finish before the change is applied, so
+ * it lands at the step the job puts it on rather than a buffer's depth earlier.
+ *
+ * Runs that drive pressure drive the first Arduino channel, set by the job's
+ * plain pressure column, plus every channel the job names a pressure column for.
+ *
+ * Time is passed in explicitly so transitions can be unit-tested without a runtime.
+ */
+
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+use tokio::time::{Duration, Instant};
+
+use crate::config::config::{ArduinoState, DuetState, PulseSettings, DUET_MIN_BUFFER_FREE};
+use crate::controllers::{DuetController, MicrowaveController, ArduinoController};
+use crate::job::{Job, Step};
+use crate::print_engine::{PrintCommand, PrintConfig, PrintState, PrintStatus, PrintSummary, Postamble, RunMode};
+
+// How long the Duet must report idle, with nothing in flight, before motion counts as complete.
+// Longer than two status polls so a stale "idle" from before the last move is never trusted.
+const MOTION_SETTLE: Duration = Duration::from_millis(400);
+// Interval between pressure setpoint updates while ramping down
+const RAMP_UPDATE: Duration = Duration::from_millis(100);
+
+/// Back-pressure gate for streaming moves.
+/// The Duet is ready for the next step once every previously queued command has
+/// been acknowledged (so moves cannot be reordered or dropped in the command
+/// channel) and its last reported G-code buffer has room for another line.
+fn duet_ready(duet: &DuetState) -> bool {
+    duet.connected
+        && duet.gcode_in_flight == 0
+        && duet.buffer_free.is_none_or(|b| b >= DUET_MIN_BUFFER_FREE)
+}
+
+/// Tracks how long the Duet has continuously been idle with nothing queued.
+#[derive(Default)]
+struct MotionWatch {
+    idle_since: Option<Instant>,
+}
+
+impl MotionWatch {
+    fn settled(&mut self, duet: &DuetState, now: Instant) -> bool {
+        let idle = duet_ready(duet) && duet.status.as_deref() == Some("idle");
+        if !idle {
+            self.idle_since = None;
+            return false;
+        }
+        let since = *self.idle_since.get_or_insert(now);
+        now.duration_since(since) >= MOTION_SETTLE
+    }
+}
+
+/// Travel moves to `step` that never dip below `safe_z_mm`: rise, move XY, then descend.
+fn travel_moves(from_z_mm: f32, step: &Step, safe_z_mm: f32) -> VecDeque<String> {
+    let travel_z = safe_z_mm.max(from_z_mm).max(step.z_mm);
+    VecDeque::from([
+        format!("G0 Z{:.3}", travel_z),
+        format!("G0 X{:.3} Y{:.3}", step.x_mm, step.y_mm),
+        format!("G1 Z{:.3}", step.z_mm),
+    ])
+}
+
+/// True when `position` is within `tolerance_mm` of `step` on every axis.
+fn at_step(position: [f32; 3], step: &Step, tolerance_mm: f32) -> bool {
+    (position[0] - step.x_mm).abs() <= tolerance_mm
+        && (position[1] - step.y_mm).abs() <= tolerance_mm
+        && (position[2] - step.z_mm).abs() <= tolerance_mm
+}
+
+/// Time to run from `from` to `to` at `feed_mm_min`, if the feed rate is known.
+fn segment_duration(from: &Step, to: &Step, feed_mm_min: Option<f32>) -> Option<Duration> {
+    let feed = feed_mm_min.filter(|f| *f > 0.0)?;
+    let distance = ((to.x_mm - from.x_mm).powi(2)
+        + (to.y_mm - from.y_mm).powi(2)
+        + (to.z_mm - from.z_mm).powi(2))
+    .sqrt();
+    Some(Duration::from_secs_f32(distance / (feed / 60.0)))
+}
+
+/// Pulse mode a step asks for: None when the job has no duty cycle column,
+/// Some(None) for continuous wave (duty cycle of 100 % or more).
+fn step_pulse(step: &Step) -> Option<Option<PulseSettings>> {
+    let duty_pct = step.duty_pct?;
+    if duty_pct >= 100.0 {
+        return Some(None);
+    }
+    let period_us = step
+        .pulse_period_us
+        .map(|us| us.round().max(0.0) as u32)
+        .unwrap_or(PulseSettings::default().period_us);
+    Some(Some(PulseSettings { period_us, duty_pct }))
+}
+
+/// First step whose pulse settings the generator cannot run, as a rejection reason.
+fn invalid_pulse(job: &Job) -> Option<String> {
+    job.steps.iter().enumerate().find_map(|(i, step)| {
+        let problems = step_pulse(step).flatten()?.validate();
+        (!problems.is_empty()).then(|| format!("step {}: {}", i, problems.join(", ")))
+    })
+}
+
+/// A pressure channel the run drives.
+struct DrivenChannel {
+    channel: usize,                 // index into `ArduinoState::channels`
+    column: Option<usize>,          // index into `Job::pressure_channels`
+    applied_psi: Option<f32>,       // setpoint last sent
+}
+
+impl DrivenChannel {
+    /// The step's setpoint for this channel. The first channel falls back to the
+    /// plain pressure column.
+    fn step_psi(&self, step: &Step) -> Option<f32> {
+        self.column
+            .and_then(|column| step.channel_pressures_psi.get(column).copied().flatten())
+            .or_else(|| step.pressure_psi.filter(|_| self.channel == 0))
+    }
+}
+
+/// Channels a run drives: the first one plus every channel the job names,
+/// resolved against the Arduino's channel names.
+fn driven_channels(job: &Job, arduino: &ArduinoState) -> Result<Vec<DrivenChannel>, String> {
+    let mut driven = vec![DrivenChannel { channel: 0, column: None, applied_psi: None }];
+    for (column, name) in job.pressure_channels.iter().enumerate() {
+        let channel = arduino
+            .channel_index(name)
+            .ok_or_else(|| format!("job sets pressure for unknown channel {:?}", name))?;
+        match driven.iter_mut().find(|d| d.channel == channel) {
+            Some(d) => d.column = Some(column),
+            None => driven.push(DrivenChannel { channel, column: Some(column), applied_psi: None }),
+        }
+    }
+    Ok(driven)
+}
+
+/// Postamble progress while in `PrintStatus::Finishing`.
+enum FinishStage {
+    DrainMotion,
+    RampPressure { from_psi: Vec<f32>, started: Instant, last_update: Instant },  // per driven channel
+    Moves,
+    WaitMotion,
+}
+
+/// Z lift and park moves for the postamble, in absolute coordinates.
+fn postamble_moves(postamble: &Postamble, last_step: Option<&Step>) -> VecDeque<String> {
+    let mut moves = VecDeque::new();
+    if let (Some(lift), Some(step)) = (postamble.z_lift_mm, last_step) {
+        moves.push_back(format!("G1 Z{:.3}", step.z_mm + lift));
+    }
+    if let Some([x, y]) = postamble.park_xy_mm {
+        moves.push_back(format!("G1 X{:.3} Y{:.3}", x, y));
+    }
+    moves
+}
+
+pub struct PrintEngine {
+    state: Arc<RwLock<PrintState>>,
+    duet: Arc<Box<dyn DuetController + Send + Sync>>,
+    microwave: Arc<Box<dyn MicrowaveController + Send + Sync>>,
+    arduino: Arc<Box<dyn ArduinoController + Send + Sync>>,
+    // access to UI-setpoints:
+    microwave_power_setpoint: Arc<RwLock<f32>>,
+    pressure_setpoint_psi: Arc<RwLock<Vec<f32>>>,  // per Arduino channel
+    config: PrintConfig,
+
+    status: PrintStatus,
+    mode: RunMode,
+    entered_at: Instant,            // when `status` was entered, for timeouts
+    job: Option<Arc<Job>>,
+    current_index: usize,           // next step to send
+    start_index: usize,
+    started_at: Option<(Instant, chrono::DateTime<chrono::Local>)>,
+    // Per-step process values last sent to the controllers
+    applied_power_w: Option<f32>,
+    driven: Vec<DrivenChannel>,     // pressure channels, with their setpoints last sent
+    applied_pulse: Option<Option<PulseSettings>>,
+    motion: MotionWatch,
+    moves: VecDeque<String>,        // pending homing, travel or postamble moves
+    next_step_at: Instant,          // step pacing when the run mode has no motion
+    finish_stage: FinishStage,
+    interlock: Option<String>,      // latched RF interlock reason
+}
+
+impl PrintEngine {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        state: Arc<RwLock<PrintState>>,
+        duet: Arc<Box<dyn DuetController + Send + Sync>>,
+        microwave: Arc<Box<dyn MicrowaveController + Send + Sync>>,
+        arduino: Arc<Box<dyn ArduinoController + Send + Sync>>,
+        microwave_power_setpoint: Arc<RwLock<f32>>,
+        pressure_setpoint_psi: Arc<RwLock<Vec<f32>>>,
+        config: PrintConfig,
+    ) -> Self {
+        Self {
+            state,
+            duet,
+            microwave,
+            arduino,
+            microwave_power_setpoint,
+            pressure_setpoint_psi,
+            config,
+            status: PrintStatus::Idle,
+            mode: RunMode::Full,
+            entered_at: Instant::now(),
+            job: None,
+            current_index: 0,
+            start_index: 0,
+            started_at: None,
+            applied_power_w: None,
+            driven: Vec::new(),
+            applied_pulse: None,
+            motion: MotionWatch::default(),
+            moves: VecDeque::new(),
+            next_step_at: Instant::now(),
+            finish_stage: FinishStage::DrainMotion,
+            interlock: None,
+        }
+    }
+
+    pub fn handle_command(&mut self, cmd: PrintCommand, now: Instant) {
+        match cmd {
+            PrintCommand::Start { job, mode } => self.start(job, 0, mode, now),
+            PrintCommand::StartAt { job, index, mode } => self.start(job, index, mode, now),
+            PrintCommand::Pause => {
+                // Outputs may already be on before Running; Paused turns them off
+                if matches!(
+                    self.status,
+                    PrintStatus::Preheating | PrintStatus::PressureSettling | PrintStatus::Running
+                ) {
+                    self.enter(PrintStatus::Paused, now);
+                }
+            }
+            PrintCommand::Resume => {
+                // Paused -> Preheating: RF and pressure are re-established before streaming again
+                if self.status == PrintStatus::Paused && self.mode.drives_rf() && self.interlock.is_some() {
+                    self.state.write().unwrap().last_error =
+                        Some("RF interlock tripped; acknowledge it before resuming".to_string());
+                } else if self.status == PrintStatus::Paused {
+                    let next = self.stage_from(PrintStatus::Preheating);
+                    self.enter(next, now);
+                }
+            }
+            PrintCommand::Abort => {
+                // Faulted stays latched; only ClearFault leaves it
+                if self.status != PrintStatus::Idle && self.status != PrintStatus::Faulted {
+                    self.outputs_off();
+                    self.job = None;
+                    self.started_at = None;
+                    self.moves.clear();
+                    self.enter(PrintStatus::Idle, now);
+                    let mut s = self.state.write().unwrap();
+                    s.current_index = 0;
+                    s.total_steps = 0;
+                    s.last_gcode = None;
+                    // Keep last_error so user can see what happened
+                }
+            }
+            PrintCommand::ClearFault => {
+                if self.status == PrintStatus::Faulted {
+                    self.job = None;
+                    self.started_at = None;
+                    self.enter(PrintStatus::Idle, now);
+                    // Keep current_index so the operator can restart from the faulted step
+                    self.state.write().unwrap().fault = None;
+                }
+            }
+            PrintCommand::InterlockTripped(reason) => {
+                self.interlock = Some(reason.clone());
+                self.state.write().unwrap().interlock = Some(reason.clone());
+                let outputs_on = matches!(
+                    self.status,
+                    PrintStatus::Preheating | PrintStatus::PressureSettling | PrintStatus::Running
+                );
+                if outputs_on && self.mode.drives_rf() {
+                    self.state.write().unwrap().last_error = Some(format!("RF interlock: {}", reason));
+                    self.enter(PrintStatus::Paused, now);
+                }
+            }
+            PrintCommand::InterlockCleared => {
+                // No automatic resume: the operator resumes once the cause is dealt with
+                self.interlock = None;
+                self.state.write().unwrap().interlock = None;
+            }
+        }
+    }
+
+    /// Idle -> first stage of the run, guarded on a valid start step and on the
+    /// controllers the run mode drives being connected.
+    fn start(&mut self, job: Arc<Job>, index: usize, mode: RunMode, now: Instant) {
+        let rejection = if self.status != PrintStatus::Idle {
+            Some(format!("cannot start while {:?}", self.status))
+        } else if index >= job.steps.len() {
+            Some(format!("start step {} out of range (job has {} steps)", index, job.steps.len()))
+        } else if mode.drives_motion() && !self.duet.state().connected {
+            Some("Duet not connected".to_string())
+        } else if mode.drives_rf() && !self.microwave.state().connected {
+            Some("Microwave not connected".to_string())
+        } else if mode.drives_rf() && self.interlock.is_some() {
+            Some("RF interlock tripped; acknowledge it before starting".to_string())
+        } else if mode.drives_rf() && let Some(reason) = invalid_pulse(&job) {
+            Some(reason)
+        } else if mode.drives_pressure() && !self.arduino.state().connected {
+            Some("Arduino not connected".to_string())
+        } else {
+            None
+        };
+        let driven = match rejection {
+            Some(reason) => Err(reason),
+            None if mode.drives_pressure() => driven_channels(&job, &self.arduino.state()),
+            None => Ok(Vec::new()),
+        };
+        let driven = match driven {
+            Ok(driven) => driven,
+            Err(reason) => {
+                self.state.write().unwrap().last_error = Some(reason);
+                return;
+            }
+        };
+
+        {
+            let mut s = self.state.write().unwrap();
+            s.mode = mode;
+            s.current_index = index;
+            s.total_steps = job.steps.len();
+            s.last_error = None;
+        }
+        self.mode = mode;
+        self.job = Some(job);
+        self.current_index = index;
+        self.start_index = index;
+        self.applied_power_w = None;
+        self.driven = driven;
+        self.applied_pulse = None;
+        self.started_at = Some((now, chrono::Local::now()));
+
+        let next = self.stage_from(PrintStatus::Homing);
+        self.enter(next, now);
+    }
+
+    /// Whether the current run mode and config use a start-sequence stage.
+    fn uses_stage(&self, stage: PrintStatus) -> bool {
+        match stage {
+            PrintStatus::Homing => self.mode.drives_motion() && self.config.home_before_start,
+            PrintStatus::Traveling => self.mode.drives_motion(),
+            PrintStatus::Preheating => self.mode.drives_rf(),
+            PrintStatus::PressureSettling => self.mode.drives_pressure(),
+            _ => true,
+        }
+    }
+
+    /// First stage at or after `from` in the start sequence that this run uses.
+    fn stage_from(&self, from: PrintStatus) -> PrintStatus {
+        [
+            PrintStatus::Homing,
+            PrintStatus::Traveling,
+            PrintStatus::Preheating,
+            PrintStatus::PressureSettling,
+            PrintStatus::Running,
+        ]
+        .into_iter()
+        .skip_while(|stage| *stage != from)
+        .find(|stage| self.uses_stage(*stage))
+        .unwrap_or(PrintStatus::Running)
+    }
+
+    /// Run entry actions for `next` and publish it.
+    fn enter(&mut self, next: PrintStatus, now: Instant) {
+        // Never switch RF on while the interlock is latched; hold the run paused instead
+        let next = if next == PrintStatus::Preheating && self.interlock.is_some() {
+            self.state.write().unwrap().last_error =
+                Some("RF interlock tripped; paused before RF on".to_string());
+            PrintStatus::Paused
+        } else {
+            next
+        };
+        self.status = next;
+        self.entered_at = now;
+        self.motion = MotionWatch::default();
+
+        match next {
+            PrintStatus::Homing => {
+                self.moves = VecDeque::from(["G28".to_string()]);
+            }
+            PrintStatus::Traveling => {
+                if let Some(step) = self.current_step().cloned() {
+                    let from_z = self.duet.state().position[2];
+                    self.moves = travel_moves(from_z, &step, self.config.safe_z_mm);
+                }
+            }
+            PrintStatus::Preheating => {
+                // Preheat at the power the next step will run at
+                let power_w = self
+                    .current_step()
+                    .and_then(|step| step.power_w)
+                    .unwrap_or_else(|| *self.microwave_power_setpoint.read().unwrap());
+                *self.microwave_power_setpoint.write().unwrap() = power_w;
+                // RF soft-starts: the driver ramps from 0 W up to the step's power
+                self.microwave.set_power(power_w, None);
+                self.apply_pulse();
+                self.microwave.rf_on();
+                self.applied_power_w = Some(power_w);
+            }
+            PrintStatus::PressureSettling => {
+                let step = self.current_step().cloned();
+                for i in 0..self.driven.len() {
+                    let channel = self.driven[i].channel;
+                    let pressure_psi = step
+                        .as_ref()
+                        .and_then(|step| self.driven[i].step_psi(step))
+                        .unwrap_or_else(|| self.pressure_setpoint_psi.read().unwrap().get(channel).copied().unwrap_or(0.0));
+                    self.arduino.enable(channel, true);
+                    self.drive_pressure(i, pressure_psi);
+                }
+            }
+            PrintStatus::Running => self.next_step_at = now,
+            PrintStatus::Paused => self.outputs_off(),
+            PrintStatus::Finishing => self.finish_stage = FinishStage::DrainMotion,
+            PrintStatus::Faulted => {
+                self.outputs_off();
+                self.moves.clear();
+            }
+            PrintStatus::Idle => {}
+        }
+
+        self.state.write().unwrap().status = next;
+    }
+
+    /// Any active state -> Faulted. The reason is latched in `PrintState::fault`.
+    fn fault(&mut self, reason: String, now: Instant) {
+        {
+            let mut s = self.state.write().unwrap();
+            s.fault = Some(reason.clone());
+            s.last_error = Some(reason);
+        }
+        self.enter(PrintStatus::Faulted, now);
+    }
+
+    /// RF off, pressure to zero, pressure control disabled, for the outputs this run drives.
+    fn outputs_off(&self) {
+        if self.mode.drives_rf() {
+            self.microwave.rf_off();
+        }
+        if self.mode.drives_pressure() {
+            for d in &self.driven {
+                self.arduino.set_pressure_setpoint(d.channel, 0.0);
+                self.arduino.enable(d.channel, false);
+            }
+        }
+    }
+
+    /// Send driven channel `i` a new setpoint. The shared setpoint is updated too
+    /// so Pause/Resume restore it.
+    fn drive_pressure(&mut self, i: usize, pressure_psi: f32) {
+        let channel = self.driven[i].channel;
+        if let Some(setpoint) = self.pressure_setpoint_psi.write().unwrap().get_mut(channel) {
+            *setpoint = pressure_psi;
+        }
+        self.arduino.set_pressure_setpoint(channel, pressure_psi);
+        self.driven[i].applied_psi = Some(pressure_psi);
+    }
+
+    /// Send the current step's pulse mode if it differs from the last one sent.
+    fn apply_pulse(&mut self) {
+        let Some(pulse) = self.current_step().and_then(step_pulse) else {
+            return;
+        };
+        if self.applied_pulse != Some(pulse) {
+            self.microwave.set_pulse(pulse);
+            self.applied_pulse = Some(pulse);
+        }
+    }
+
+    /// Whether `step` asks for a power, pulse mode or pressure other than the one last sent.
+    fn step_changes_outputs(&self, step: &Step) -> bool {
+        let rf = self.mode.drives_rf()
+            && (step.power_w.is_some_and(|power_w| self.applied_power_w != Some(power_w))
+                || step_pulse(step).is_some_and(|pulse| self.applied_pulse != Some(pulse)));
+        let pressure = self
+            .driven
+            .iter()
+            .any(|d| d.step_psi(step).is_some_and(|psi| d.applied_psi != Some(psi)));
+        rf || pressure
+    }
+
+    fn current_step(&self) -> Option<&Step> {
+        self.job.as_ref().and_then(|job| job.steps.get(self.current_index))
+    }
+
+    fn elapsed_s(&self, now: Instant) -> f32 {
+        now.duration_since(self.entered_at).as_secs_f32()
+    }
+
+    /// Send the next pending move once the Duet can take it. Returns true while moves remain.
+    fn send_pending_move(&mut self, duet: &DuetState) -> bool {
+        if self.moves.is_empty() {
+            return false;
+        }
+        if duet_ready(duet)
+            && let Some(gcode) = self.moves.pop_front()
+        {
+            self.duet.send_gcode(&gcode);
+            self.state.write().unwrap().last_gcode = Some(gcode);
+        }
+        true
+    }
+
+    pub fn tick(&mut self, now: Instant) {
+        let duet = self.duet.state();
+
+        // Connection guards for every state that depends on the hardware the run drives
+        let active = matches!(
+            self.status,
+            PrintStatus::Homing
+                | PrintStatus::Traveling
+                | PrintStatus::Preheating
+                | PrintStatus::PressureSettling
+                | PrintStatus::Running
+        );
+        let outputs_on = matches!(
+            self.status,
+            PrintStatus::Preheating | PrintStatus::PressureSettling | PrintStatus::Running
+        );
+        if active && self.mode.drives_motion() && !duet.connected {
+            self.fault("Duet disconnected".to_string(), now);
+            return;
+        }
+        if outputs_on && self.mode.drives_rf() && !self.microwave.state().connected {
+            self.fault("Microwave disconnected".to_string(), now);
+            return;
+        }
+        if outputs_on && self.mode.drives_pressure() && !self.arduino.state().connected {
+            self.fault("Arduino disconnected".to_string(), now);
+            return;
+        }
+
+        match self.status {
+            PrintStatus::Idle | PrintStatus::Paused | PrintStatus::Faulted => {}
+            PrintStatus::Homing => {
+                if self.send_pending_move(&duet) {
+                    // still sending G28
+                } else if self.motion.settled(&duet, now) {
+                    let next = self.stage_from(PrintStatus::Traveling);
+                    self.enter(next, now);
+                } else if self.elapsed_s(now) >= self.config.homing_timeout_s {
+                    self.fault("homing timed out".to_string(), now);
+                }
+            }
+            PrintStatus::Traveling => {
+                if self.send_pending_move(&duet) {
+                    // still sending travel moves
+                } else if self.motion.settled(&duet, now) {
+                    let reached = self
+                        .current_step()
+                        .is_some_and(|step| at_step(duet.position, step, self.config.position_tolerance_mm));
+                    if reached {
+                        let next = self.stage_from(PrintStatus::Preheating);
+                        self.enter(next, now);
+                    } else {
+                        self.fault("start position not reached".to_string(), now);
+                    }
+                } else if self.elapsed_s(now) >= self.config.travel_timeout_s {
+                    self.fault("travel to start position timed out".to_string(), now);
+                }
+            }
+            PrintStatus::Preheating => {
+                let microwave = self.microwave.state();
+                let rf_on = microwave.enabled;
+                let ramped = self
+                    .applied_power_w
+                    .is_none_or(|power_w| (microwave.commanded_power_w - power_w).abs() < 0.05);
+                if rf_on && ramped && self.elapsed_s(now) >= self.config.preheat_s {
+                    let next = self.stage_from(PrintStatus::PressureSettling);
+                    self.enter(next, now);
+                } else if !rf_on && self.elapsed_s(now) >= self.config.rf_on_timeout_s {
+                    self.fault("RF did not turn on".to_string(), now);
+                }
+            }
+            PrintStatus::PressureSettling => {
+                let arduino = self.arduino.state();
+                let unsettled = self.driven.iter().find_map(|d| {
+                    let setpoint = d.applied_psi.unwrap_or(0.0);
+                    let Some(channel) = arduino.channels.get(d.channel) else {
+                        return Some(format!("channel {}: missing", d.channel));
+                    };
+                    let error = (channel.pressure_measured_psi - setpoint).abs();
+                    (!channel.enabled || error > self.config.pressure_tolerance_psi).then(|| {
+                        format!("{}: {:.1} of {:.1} psi", channel.name, channel.pressure_measured_psi, setpoint)
+                    })
+                });
+                match unsettled {
+                    None => self.enter(PrintStatus::Running, now),
+                    Some(reading) if self.elapsed_s(now) >= self.config.pressure_settle_timeout_s => {
+                        self.fault(format!("pressure did not settle ({})", reading), now);
+                    }
+                    Some(_) => {}
+                }
+            }
+            PrintStatus::Running => self.tick_running(&duet, now),
+            PrintStatus::Finishing => self.tick_finishing(&duet, now),
+        }
+    }
+
+    /// Stream the next step when the Duet can take it. Without motion, steps are
+    /// paced by the time their segment would take at the step's feed rate.
+    fn tick_running(&mut self, duet: &DuetState, now: Instant) {
+        let motion = self.mode.drives_motion();
+        if motion && !duet_ready(duet) {
+            return;
+        }
+        if !motion && now < self.next_step_at {
+            return;
+        }
+        let Some(job) = self.job.clone() else {
+            return;
+        };
+        let Some(step) = job.steps.get(self.current_index) else {
+            return;
+        };
+
+        // A new power, pulse mode or pressure must not take effect under moves the
+        // Duet still has queued, so wait for those to finish before applying it and
+        // sending the move that uses it. Unchanged values keep the queue full.
+        if motion && self.step_changes_outputs(step) && !self.motion.settled(duet, now) {
+            return;
+        }
+
+        // Apply per-step process values, then send the move that uses them.
+        // The shared setpoints are updated too so Pause/Resume restore them.
+        // Power changes are slewed by the microwave driver, not stepped.
+        if self.mode.drives_rf()
+            && let Some(power_w) = step.power_w
+            && self.applied_power_w != Some(power_w)
+        {
+            *self.microwave_power_setpoint.write().unwrap() = power_w;
+            self.microwave.set_power(power_w, None);
+            self.applied_power_w = Some(power_w);
+        }
+        if self.mode.drives_rf() {
+            self.apply_pulse();
+        }
+        for i in 0..self.driven.len() {
+            if let Some(pressure_psi) = self.driven[i].step_psi(step)
+                && self.driven[i].applied_psi != Some(pressure_psi)
+            {
+                self.drive_pressure(i, pressure_psi);
+            }
+        }
+
+        if motion {
+            // Format G-code command
+            let mut gcode = format!(
+                "G1 X{:.3} Y{:.3} Z{:.3}",
+                step.x_mm, step.y_mm, step.z_mm
+            );
+            if let Some(feed) = step.feed_mm_min {
+                gcode.push_str(&format!(" F{:.1}", feed));
+            }
+
+            // Send to duet (non-blocking enqueue)
+            self.duet.send_gcode(&gcode);
+            self.state.write().unwrap().last_gcode = Some(gcode);
+            // The reported status lags the move just queued; settling starts over
+            self.motion = MotionWatch::default();
+        } else {
+            let dwell = self
+                .current_index
+                .checked_sub(1)
+                .and_then(|prev| segment_duration(&job.steps[prev], step, step.feed_mm_min))
+                .unwrap_or(Duration::from_secs_f32(self.config.rf_only_step_s));
+            self.next_step_at = now + dwell;
+        }
+
+        self.state.write().unwrap().current_index = self.current_index;
+        self.current_index += 1;
+
+        // Last step sent: Running -> Finishing runs the postamble
+        if self.current_index >= job.steps.len() {
+            self.state.write().unwrap().current_index = self.current_index;
+            self.enter(PrintStatus::Finishing, now);
+        }
+    }
+
+    /// Advance the postamble. A lost Duet must not hold RF and pressure on, so every
+    /// wait below gives up on motion once the Duet is disconnected.
+    fn tick_finishing(&mut self, duet: &DuetState, now: Instant) {
+        let postamble = self.config.postamble.clone();
+        let mut completed = false;
+
+        let next = match &mut self.finish_stage {
+            FinishStage::DrainMotion => {
+                let drained = !self.mode.drives_motion() || !duet.connected || self.motion.settled(duet, now);
+                if drained {
+                    if postamble.rf_off && self.mode.drives_rf() {
+                        self.microwave.rf_off();
+                    }
+                    let setpoints = self.pressure_setpoint_psi.read().unwrap();
+                    let from_psi = self.driven.iter().map(|d| setpoints.get(d.channel).copied().unwrap_or(0.0)).collect();
+                    Some(FinishStage::RampPressure { from_psi, started: now, last_update: now })
+                } else {
+                    None
+                }
+            }
+            FinishStage::RampPressure { from_psi, started, last_update } => {
+                let pressure = self.mode.drives_pressure();
+                let targets: Vec<f32> = from_psi
+                    .iter()
+                    .map(|from_psi| match postamble.pressure_ramp_psi_per_s {
+                        Some(rate) if rate > 0.0 && pressure => {
+                            (from_psi - rate * now.duration_since(*started).as_secs_f32()).max(0.0)
+                        }
+                        _ => 0.0,
+                    })
+                    .collect();
+                let vented = targets.iter().all(|target| *target <= 0.0);
+                if pressure && (vented || now.duration_since(*last_update) >= RAMP_UPDATE) {
+                    for (d, target) in self.driven.iter().zip(&targets) {
+                        self.arduino.set_pressure_setpoint(d.channel, *target);
+                    }
+                    *last_update = now;
+                }
+                if vented {
+                    if pressure && postamble.disable_arduino {
+                        for d in &self.driven {
+                            self.arduino.enable(d.channel, false);
+                        }
+                    }
+                    if self.mode.drives_motion() {
+                        let last_step = self.job.as_ref().and_then(|job| job.steps.last());
+                        self.moves = postamble_moves(&postamble, last_step);
+                    }
+                    Some(FinishStage::Moves)
+                } else {
+                    None
+                }
+            }
+            FinishStage::Moves => {
+                if !duet.connected {
+                    self.moves.clear();
+                }
+                if self.moves.is_empty() {
+                    Some(FinishStage::WaitMotion)
+                } else {
+                    None
+                }
+            }
+            FinishStage::WaitMotion => {
+                completed = !self.mode.drives_motion() || !duet.connected || self.motion.settled(duet, now);
+                None
+            }
+        };
+        if matches!(self.finish_stage, FinishStage::Moves) {
+            self.send_pending_move(duet);
+        }
+        if let Some(next) = next {
+            self.finish_stage = next;
+            self.motion = MotionWatch::default();
+        }
+
+        // Finishing -> Idle: record the summary
+        if completed {
+            if let (Some(job), Some((start, start_wall))) = (self.job.take(), self.started_at.take()) {
+                self.state.write().unwrap().last_summary = Some(PrintSummary {
+                    filename: job.filename.clone(),
+                    steps_completed: job.steps.len() - self.start_index,
+                    total_steps: job.steps.len(),
+                    started_at: start_wall,
+                    finished_at: chrono::Local::now(),
+                    duration: now.duration_since(start),
+                });
+            }
+            self.enter(PrintStatus::Idle, now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::config::PidGains;
+    use crate::controllers::arduino::mock::MockArduino;
+    use crate::controllers::duet::mock::MockDuet;
+    use crate::controllers::microwave::mock::MockMicrowave;
+
+    const TICK: Duration = Duration::from_millis(20);
+
+    struct Rig {
+        engine: PrintEngine,
+        state: Arc<RwLock<PrintState>>,
+        duet: Arc<Box<dyn DuetController + Send + Sync>>,
+        microwave: Arc<Box<dyn MicrowaveController + Send + Sync>>,
+        arduino: Arc<Box<dyn ArduinoController + Send + Sync>>,
+        now: Instant,
+    }
+
+    impl Rig {
+        fn with_arduino(arduino: Box<dyn ArduinoController + Send + Sync>) -> Self {
+            Self::with_controllers(Box::new(MockDuet::new()), arduino)
+        }
+
+        fn with_controllers(
+            duet: Box<dyn DuetController + Send + Sync>,
+            arduino: Box<dyn ArduinoController + Send + Sync>,
+        ) -> Self {
+            let state = Arc::new(RwLock::new(PrintState::default()));
+            let duet: Arc<Box<dyn DuetController + Send + Sync>> = Arc::new(duet);
+            let microwave: Arc<Box<dyn MicrowaveController + Send + Sync>> = Arc::new(Box::new(MockMicrowave::new()));
+            let arduino: Arc<Box<dyn ArduinoController + Send + Sync>> = Arc::new(arduino);
+            duet.connect();
+            microwave.connect("mock");
+            arduino.connect("mock");
+            let config = PrintConfig {
+                preheat_s: 1.0,
+                ..PrintConfig::default()
+            };
+            let engine = PrintEngine::new(
+                Arc::clone(&state),
+                Arc::clone(&duet),
+                Arc::clone(&microwave),
+                Arc::clone(&arduino),
+                Arc::new(RwLock::new(100.0)),
+                Arc::new(RwLock::new(vec![10.0, 2.0])),
+                config,
+            );
+            Self { engine, state, duet, microwave, arduino, now: Instant::now() }
+        }
+
+        fn new() -> Self {
+            Self::with_arduino(Box::new(MockArduino::new(&["material".into()])))
+        }
+
+        /// Whether any pressure channel is enabled.
+        fn pressure_on(&self) -> bool {
+            self.arduino.state().channels.iter().any(|c| c.enabled)
+        }
+
+        fn command(&mut self, cmd: PrintCommand) {
+            self.engine.handle_command(cmd, self.now);
+        }
+
+        /// Tick until `status` is reached, returning every status passed through.
+        fn run_until(&mut self, status: PrintStatus, max_ticks: usize) -> Vec<PrintStatus> {
+            let mut seen = vec![self.engine.status];
+            for _ in 0..max_ticks {
+                if self.engine.status == status {
+                    return seen;
+                }
+                self.now += TICK;
+                self.engine.tick(self.now);
+                if seen.last() != Some(&self.engine.status) {
+                    seen.push(self.engine.status);
+                }
+            }
+            panic!("never reached {:?}; passed through {:?}", status, seen);
+        }
+    }
+
+    fn job(n: usize) -> Arc<Job> {
+        let steps = (0..n)
+            .map(|i| Step {
+                x_mm: i as f32,
+                y_mm: 1.0,
+                z_mm: 0.5,
+                power_w: None,
+                pressure_psi: None,
+                feed_mm_min: None,
+                duty_pct: None,
+                pulse_period_us: None,
+                channel_pressures_psi: Vec::new(),
+            })
+            .collect();
+        Arc::new(Job::new("test.csv".to_string(), steps))
+    }
+
+    /// Arduino that never reaches its setpoint.
+    struct StuckArduino(std::sync::Mutex<ArduinoState>);
+
+    impl ArduinoController for StuckArduino {
+        fn connect(&self, _port: &str) {
+            self.0.lock().unwrap().connected = true;
+        }
+        fn disconnect(&self) {
+            self.0.lock().unwrap().connected = false;
+        }
+        fn enable(&self, channel: usize, enable: bool) {
+            self.0.lock().unwrap().channels[channel].enabled = enable;
+        }
+        fn set_pressure_setpoint(&self, channel: usize, psi: f32) {
+            self.0.lock().unwrap().channels[channel].pressure_setpoint_psi = psi;
+        }
+        fn set_ramp_rate(&self, _channel: usize, _psi_per_s: f32) {}
+        fn get_pid(&self, _channel: usize) {}
+        fn set_pid(&self, _channel: usize, _gains: PidGains) {}
+        fn autotune(&self, _channel: usize, _step_psi: f32) {}
+        fn state(&self) -> ArduinoState {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    /// Duet that reports it is still executing queued moves while `moving` is set.
+    struct MovingDuet {
+        inner: MockDuet,
+        moving: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    impl DuetController for MovingDuet {
+        fn connect(&self) {
+            self.inner.connect();
+        }
+        fn disconnect(&self) {
+            self.inner.disconnect();
+        }
+        fn send_gcode(&self, gcode: &str) {
+            self.inner.send_gcode(gcode);
+        }
+        fn send_m_cmd(&self, m_cmd: &str) {
+            self.inner.send_m_cmd(m_cmd);
+        }
+        fn state(&self) -> DuetState {
+            let mut s = self.inner.state();
+            if self.moving.load(std::sync::atomic::Ordering::SeqCst) {
+                s.status = Some("busy".into());
+            }
+            s
+        }
+    }
+
+    #[test]
+    fn test_full_sequence() {
+        let mut rig = Rig::new();
+        rig.command(PrintCommand::Start { job: job(3), mode: RunMode::Full });
+        assert_eq!(rig.engine.status, PrintStatus::Homing);
+
+        let seen = rig.run_until(PrintStatus::Idle, 1000);
+        assert_eq!(
+            seen,
+            vec![
+                PrintStatus::Homing,
+                PrintStatus::Traveling,
+                PrintStatus::Preheating,
+                PrintStatus::PressureSettling,
+                PrintStatus::Running,
+                PrintStatus::Finishing,
+                PrintStatus::Idle,
+            ]
+        );
+
+        let s = rig.state.read().unwrap().clone();
+        let summary = s.last_summary.expect("summary recorded");
+        assert_eq!(summary.steps_completed, 3);
+        assert_eq!(s.current_index, 3);
+        assert!(!rig.microwave.state().enabled);
+        assert!(!rig.pressure_on());
+    }
+
+    #[test]
+    fn test_start_rejected_when_disconnected() {
+        let mut rig = Rig::new();
+        rig.duet.disconnect();
+        rig.command(PrintCommand::Start { job: job(2), mode: RunMode::Full });
+
+        assert_eq!(rig.engine.status, PrintStatus::Idle);
+        assert!(rig.state.read().unwrap().last_error.is_some());
+    }
+
+    #[test]
+    fn test_start_at_out_of_range_rejected() {
+        let mut rig = Rig::new();
+        rig.command(PrintCommand::StartAt { job: job(2), index: 2, mode: RunMode::Full });
+
+        assert_eq!(rig.engine.status, PrintStatus::Idle);
+        assert!(rig.state.read().unwrap().last_error.is_some());
+    }
+
+    #[test]
+    fn test_start_at_travels_to_step_before_outputs() {
+        let mut rig = Rig::new();
+        rig.command(PrintCommand::StartAt { job: job(5), index: 3, mode: RunMode::Full });
+        rig.run_until(PrintStatus::Preheating, 1000);
+
+        // Outputs are only enabled once the head is at step 3
+        let position = rig.duet.state().position;
+        assert_eq!(position, [3.0, 1.0, 0.5]);
+        assert!(rig.microwave.state().enabled);
+
+        rig.run_until(PrintStatus::Idle, 1000);
+        let summary = rig.state.read().unwrap().last_summary.clone().unwrap();
+        assert_eq!(summary.steps_completed, 2);
+    }
+
+    #[test]
+    fn test_preheat_waits_for_configured_time() {
+        let mut rig = Rig::new();
+        rig.command(PrintCommand::Start { job: job(2), mode: RunMode::Full });
+        rig.run_until(PrintStatus::Preheating, 1000);
+
+        // preheat_s is 1 s in the test config
+        for _ in 0..40 {
+            rig.now += TICK;
+            rig.engine.tick(rig.now);
+        }
+        assert_eq!(rig.engine.status, PrintStatus::Preheating);
+        rig.run_until(PrintStatus::PressureSettling, 20);
+    }
+
+    #[test]
+    fn test_pressure_settle_timeout_faults() {
+        let stuck = StuckArduino(std::sync::Mutex::new(ArduinoState::new(&["material".into()])));
+        let mut rig = Rig::with_arduino(Box::new(stuck));
+        rig.command(PrintCommand::Start { job: job(2), mode: RunMode::Full });
+        rig.run_until(PrintStatus::PressureSettling, 1000);
+        rig.run_until(PrintStatus::Faulted, 2000);
+
+        let s = rig.state.read().unwrap().clone();
+        assert!(s.fault.unwrap().contains("material: 0.0 of 10.0 psi"));
+        assert!(!rig.microwave.state().enabled);
+    }
+
+    #[test]
+    fn test_fault_is_latched_until_cleared() {
+        let mut rig = Rig::new();
+        rig.command(PrintCommand::Start { job: job(2), mode: RunMode::Full });
+        rig.run_until(PrintStatus::Preheating, 1000);
+
+        rig.duet.disconnect();
+        rig.run_until(PrintStatus::Faulted, 5);
+        assert!(!rig.microwave.state().enabled);
+        assert!(!rig.pressure_on());
+
+        // Neither Abort nor Start leaves Faulted
+        rig.duet.connect();
+        rig.command(PrintCommand::Abort);
+        rig.command(PrintCommand::Start { job: job(2), mode: RunMode::Full });
+        rig.run_until(PrintStatus::Faulted, 5);
+        assert!(rig.state.read().unwrap().fault.is_some());
+
+        rig.command(PrintCommand::ClearFault);
+        assert_eq!(rig.engine.status, PrintStatus::Idle);
+        assert!(rig.state.read().unwrap().fault.is_none());
+    }
+
+    #[test]
+    fn test_pause_and_resume_reestablish_outputs() {
+        let mut rig = Rig::new();
+        rig.command(PrintCommand::Start { job: job(200), mode: RunMode::Full });
+        rig.run_until(PrintStatus::Running, 1000);
+
+        rig.command(PrintCommand::Pause);
+        assert_eq!(rig.engine.status, PrintStatus::Paused);
+        assert!(!rig.microwave.state().enabled);
+        assert!(!rig.pressure_on());
+
+        // Resume is ignored unless paused; from Paused it goes back through preheat
+        rig.command(PrintCommand::Resume);
+        assert_eq!(rig.engine.status, PrintStatus::Preheating);
+        let seen = rig.run_until(PrintStatus::Running, 1000);
+        assert_eq!(
+            seen,
+            vec![PrintStatus::Preheating, PrintStatus::PressureSettling, PrintStatus::Running]
+        );
+    }
+
+    #[test]
+    fn test_pause_before_running() {
+        for stage in [PrintStatus::Preheating, PrintStatus::PressureSettling] {
+            let mut rig = Rig::new();
+            rig.command(PrintCommand::Start { job: job(200), mode: RunMode::Full });
+            rig.run_until(stage, 1000);
+            assert!(rig.microwave.state().enabled);
+
+            rig.command(PrintCommand::Pause);
+            assert_eq!(rig.engine.status, PrintStatus::Paused);
+            assert!(!rig.microwave.state().enabled);
+            assert!(!rig.pressure_on());
+
+            rig.command(PrintCommand::Resume);
+            assert_eq!(rig.engine.status, PrintStatus::Preheating);
+            rig.run_until(PrintStatus::Running, 1000);
+        }
+    }
+
+    #[test]
+    fn test_abort_turns_outputs_off() {
+        let mut rig = Rig::new();
+        rig.command(PrintCommand::Start { job: job(200), mode: RunMode::Full });
+        rig.run_until(PrintStatus::Running, 1000);
+
+        rig.command(PrintCommand::Abort);
+        assert_eq!(rig.engine.status, PrintStatus::Idle);
+        assert!(!rig.microwave.state().enabled);
+        assert!(!rig.pressure_on());
+        assert_eq!(rig.arduino.state().channels[0].pressure_setpoint_psi, 0.0);
+    }
+
+    #[test]
+    fn test_dry_run_never_enables_outputs() {
+        let mut rig = Rig::new();
+        rig.microwave.disconnect();
+        rig.arduino.disconnect();
+        rig.command(PrintCommand::Start { job: job(3), mode: RunMode::DryRun });
+        assert_eq!(rig.engine.status, PrintStatus::Homing);
+
+        let seen = rig.run_until(PrintStatus::Idle, 1000);
+        assert_eq!(
+            seen,
+            vec![
+                PrintStatus::Homing,
+                PrintStatus::Traveling,
+                PrintStatus::Running,
+                PrintStatus::Finishing,
+                PrintStatus::Idle,
+            ]
+        );
+        assert!(!rig.microwave.state().enabled);
+        assert!(!rig.pressure_on());
+        assert_eq!(rig.state.read().unwrap().mode, RunMode::DryRun);
+        assert_eq!(rig.state.read().unwrap().current_index, 3);
+    }
+
+    #[test]
+    fn test_rf_only_sends_no_motion() {
+        let mut rig = Rig::new();
+        rig.duet.disconnect();
+        rig.command(PrintCommand::Start { job: job(3), mode: RunMode::RfOnly });
+        assert_eq!(rig.engine.status, PrintStatus::Preheating);
+
+        rig.run_until(PrintStatus::Running, 1000);
+        assert!(rig.microwave.state().enabled);
+        assert!(!rig.pressure_on());
+        rig.run_until(PrintStatus::Idle, 1000);
+        assert!(rig.duet.state().last_command.is_none());
+        assert!(rig.state.read().unwrap().last_gcode.is_none());
+        assert!(!rig.microwave.state().enabled);
+    }
+
+    #[test]
+    fn test_interlock_pauses_rf_run_until_cleared() {
+        let mut rig = Rig::new();
+        rig.command(PrintCommand::Start { job: job(200), mode: RunMode::Full });
+        rig.run_until(PrintStatus::Running, 1000);
+
+        rig.command(PrintCommand::InterlockTripped("VSWR 4.0 exceeded limit 3.0".to_string()));
+        assert_eq!(rig.engine.status, PrintStatus::Paused);
+        assert!(!rig.microwave.state().enabled);
+        assert!(rig.state.read().unwrap().interlock.is_some());
+
+        // Resume is refused while latched
+        rig.command(PrintCommand::Resume);
+        assert_eq!(rig.engine.status, PrintStatus::Paused);
+        assert!(!rig.microwave.state().enabled);
+
+        rig.command(PrintCommand::InterlockCleared);
+        assert_eq!(rig.engine.status, PrintStatus::Paused);
+        rig.command(PrintCommand::Resume);
+        assert_eq!(rig.engine.status, PrintStatus::Preheating);
+        assert!(rig.microwave.state().enabled);
+    }
+
+    #[test]
+    fn test_interlock_holds_run_before_rf_on() {
+        let mut rig = Rig::new();
+        rig.command(PrintCommand::InterlockTripped("PA temperature 80.0°C exceeded limit 70.0°C".to_string()));
+        rig.command(PrintCommand::Start { job: job(3), mode: RunMode::Full });
+        assert_eq!(rig.engine.status, PrintStatus::Idle);
+
+        // Dry runs do not drive RF and are unaffected
+        rig.command(PrintCommand::Start { job: job(3), mode: RunMode::DryRun });
+        assert_eq!(rig.engine.status, PrintStatus::Homing);
+        rig.run_until(PrintStatus::Idle, 1000);
+        assert!(!rig.microwave.state().enabled);
+    }
+
+    #[test]
+    fn test_power_change_waits_for_queued_moves() {
+        let moving = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let duet = MovingDuet { inner: MockDuet::new(), moving: Arc::clone(&moving) };
+        let mut rig = Rig::with_controllers(Box::new(duet), Box::new(MockArduino::new(&["material".into()])));
+        let mut steps = job(4).steps.clone();
+        for (i, step) in steps.iter_mut().enumerate() {
+            step.power_w = Some(if i < 2 { 100.0 } else { 50.0 });
+        }
+        let graded = Arc::new(Job::new("graded.csv".to_string(), steps));
+        rig.command(PrintCommand::Start { job: graded, mode: RunMode::Full });
+        rig.run_until(PrintStatus::Running, 1000);
+
+        // Steps at the same power stream straight into the Duet's queue
+        moving.store(true, std::sync::atomic::Ordering::SeqCst);
+        for _ in 0..50 {
+            rig.now += TICK;
+            rig.engine.tick(rig.now);
+        }
+        assert_eq!(rig.engine.current_index, 2);
+        assert_eq!(rig.engine.applied_power_w, Some(100.0));
+        assert_eq!(*rig.engine.microwave_power_setpoint.read().unwrap(), 100.0);
+
+        // Once the queued moves are done the new power goes out with step 2's move
+        moving.store(false, std::sync::atomic::Ordering::SeqCst);
+        rig.run_until(PrintStatus::Finishing, 100);
+        assert_eq!(rig.engine.applied_power_w, Some(50.0));
+        assert_eq!(rig.microwave.state().target_power_w, 50.0);
+    }
+
+    #[test]
+    fn test_job_pulse_settings_applied_per_step() {
+        let mut rig = Rig::new();
+        let mut steps = job(3).steps.clone();
+        steps[0].duty_pct = Some(40.0);
+        steps[0].pulse_period_us = Some(2000.0);
+        steps[1].duty_pct = Some(40.0);
+        steps[2].duty_pct = Some(100.0);
+        let pulsed = Arc::new(Job::new("pulsed.csv".to_string(), steps.clone()));
+
+        rig.command(PrintCommand::Start { job: pulsed, mode: RunMode::RfOnly });
+        assert_eq!(rig.engine.status, PrintStatus::Preheating);
+        // Pulse mode is set before RF comes on
+        assert_eq!(
+            rig.microwave.state().pulse,
+            Some(PulseSettings { period_us: 2000, duty_pct: 40.0 })
+        );
+        rig.run_until(PrintStatus::Idle, 1000);
+        assert_eq!(rig.microwave.state().pulse, None);
+
+        // Settings the generator cannot run are refused before anything starts
+        steps[1].pulse_period_us = Some(10.0);
+        let bad = Arc::new(Job::new("bad.csv".to_string(), steps));
+        rig.command(PrintCommand::Start { job: bad, mode: RunMode::RfOnly });
+        assert_eq!(rig.engine.status, PrintStatus::Idle);
+        assert!(rig.state.read().unwrap().last_error.as_deref().unwrap().starts_with("step 1"));
+    }
+
+    #[test]
+    fn test_named_pressure_channels_driven() {
+        let names = vec!["material".to_string(), "sheath".to_string()];
+        let mut rig = Rig::with_arduino(Box::new(MockArduino::new(&names)));
+        let mut steps = job(3).steps.clone();
+        steps[0].pressure_psi = Some(20.0);
+        for (step, sheath_psi) in steps.iter_mut().zip([5.0, 6.0, 7.0]) {
+            step.channel_pressures_psi = vec![Some(sheath_psi)];
+        }
+        let mut sheathed = Job::new("sheath.csv".to_string(), steps);
+        sheathed.pressure_channels = vec!["sheath".to_string()];
+        let sheathed = Arc::new(sheathed);
+
+        rig.command(PrintCommand::Start { job: Arc::clone(&sheathed), mode: RunMode::Full });
+        rig.run_until(PrintStatus::Running, 1000);
+        let arduino = rig.arduino.state();
+        assert!(arduino.channels.iter().all(|c| c.enabled));
+        assert_eq!(arduino.channels[0].pressure_setpoint_psi, 20.0);
+        assert_eq!(arduino.channels[1].pressure_setpoint_psi, 5.0);
+        rig.run_until(PrintStatus::Finishing, 1000);
+        assert_eq!(rig.arduino.state().channels[1].pressure_setpoint_psi, 7.0);
+        rig.run_until(PrintStatus::Idle, 1000);
+        assert!(!rig.pressure_on());
+
+        // A channel the controller does not have is refused before anything starts
+        let mut unknown = (*sheathed).clone();
+        unknown.pressure_channels = vec!["shield".to_string()];
+        rig.command(PrintCommand::Start { job: Arc::new(unknown), mode: RunMode::Full });
+        assert_eq!(rig.engine.status, PrintStatus::Idle);
+        assert!(rig.state.read().unwrap().last_error.as_deref().unwrap().contains("shield"));
+    }
+}
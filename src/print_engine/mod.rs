@@ -1,5 +1,6 @@
 pub mod types;
+pub mod machine;
 pub mod task;
 
-pub use types::{PrintStatus, PrintCommand, PrintState};
+pub use types::{PrintStatus, PrintCommand, PrintState, PrintConfig, Postamble, PrintSummary, RunMode};
 pub use task::print_engine_task;
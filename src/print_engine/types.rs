@@ -1,39 +1,171 @@
 use std::sync::Arc;
+use std::time::Duration;
+use serde::Deserialize;
 
 use crate::job::Job;
 
+/// Print engine states. Transitions and their guards live in `machine.rs`:
+///
+/// Idle -> Homing -> Traveling -> Preheating -> PressureSettling -> Running -> Finishing -> Idle
+///
+/// Running <-> Paused (Resume re-enters Preheating), any active state -> Idle on Abort,
+/// and any active state -> Faulted when a guard fails. Faulted is latched until ClearFault.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PrintStatus {
     Idle,
-    Printing,
+    Homing,            // G28 sent, waiting for motion to complete
+    Traveling,         // moving to the start step at safe Z before enabling outputs
+    Preheating,        // RF on, waiting for the generator to stabilise
+    PressureSettling,  // pressure control on, waiting for the measurement to reach setpoint
+    Running,           // streaming steps
     Paused,
+    Finishing,         // running the postamble after the last step
+    Faulted,           // outputs off; latched until the operator acknowledges
+}
+
+/// Which controllers a run drives. Stages for controllers a mode does not drive
+/// are skipped, and those controllers are neither required nor commanded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RunMode {
+    #[default]
+    Full,    // motion, RF and pressure
+    DryRun,  // motion only: rehearse the toolpath with RF and pneumatics off
+    RfOnly,  // RF burn-in: step through per-step power without motion or pressure
+}
+
+impl RunMode {
+    pub const ALL: [RunMode; 3] = [RunMode::Full, RunMode::DryRun, RunMode::RfOnly];
+
+    pub fn drives_motion(self) -> bool {
+        matches!(self, RunMode::Full | RunMode::DryRun)
+    }
+
+    pub fn drives_rf(self) -> bool {
+        matches!(self, RunMode::Full | RunMode::RfOnly)
+    }
+
+    pub fn drives_pressure(self) -> bool {
+        matches!(self, RunMode::Full)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RunMode::Full => "Full",
+            RunMode::DryRun => "Dry run (motion only)",
+            RunMode::RfOnly => "RF only (no motion)",
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum PrintCommand {
-    Start(Arc<Job>),
+    Start { job: Arc<Job>, mode: RunMode },
+    StartAt { job: Arc<Job>, index: usize, mode: RunMode }, // resume a job from step `index`
     Pause,
     Resume,
     Abort,
+    ClearFault,  // operator acknowledgement of a latched fault
+    InterlockTripped(String),  // RF interlock latched: pause an RF run, block RF until cleared
+    InterlockCleared,          // interlock acknowledged; Resume is allowed again
+}
+
+/// Shutdown sequence run after the last step of a job.
+/// Steps run in order: wait for motion to finish, RF off, pressure ramp to 0,
+/// Arduino disable, then the optional Z lift and park moves.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Postamble {
+    pub rf_off: bool,
+    pub pressure_ramp_psi_per_s: Option<f32>, // None drops pressure to 0 immediately
+    pub disable_arduino: bool,
+    pub z_lift_mm: Option<f32>,               // relative to the last step's Z
+    pub park_xy_mm: Option<[f32; 2]>,         // absolute XY park position
+}
+
+impl Default for Postamble {
+    fn default() -> Self {
+        Self {
+            rf_off: true,
+            pressure_ramp_psi_per_s: Some(5.0),
+            disable_arduino: true,
+            z_lift_mm: Some(5.0),
+            park_xy_mm: None,
+        }
+    }
+}
+
+/// Print engine settings fixed for the lifetime of the task.
+/// Loaded from the `print` section of the config file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PrintConfig {
+    pub postamble: Postamble,
+    pub safe_z_mm: f32,                 // minimum absolute Z for travel moves to a start step
+    pub home_before_start: bool,        // send G28 before traveling to the start step
+    pub homing_timeout_s: f32,
+    pub travel_timeout_s: f32,
+    pub position_tolerance_mm: f32,     // start position check after traveling
+    pub preheat_s: f32,                 // RF stabilisation time before pressure is applied
+    pub rf_on_timeout_s: f32,           // time allowed for the generator to report RF on
+    pub pressure_tolerance_psi: f32,    // |measured - setpoint| required to start running
+    pub pressure_settle_timeout_s: f32,
+    pub rf_only_step_s: f32,            // per-step dwell in RfOnly mode for steps without a feed rate
+}
+
+impl Default for PrintConfig {
+    fn default() -> Self {
+        Self {
+            postamble: Postamble::default(),
+            safe_z_mm: 10.0,
+            home_before_start: true,
+            homing_timeout_s: 60.0,
+            travel_timeout_s: 60.0,
+            position_tolerance_mm: 0.05,
+            preheat_s: 5.0,
+            rf_on_timeout_s: 2.0,
+            pressure_tolerance_psi: 0.5,
+            pressure_settle_timeout_s: 30.0,
+            rf_only_step_s: 0.1,
+        }
+    }
+}
+
+/// Summary recorded when a job runs to completion.
+#[derive(Debug, Clone)]
+pub struct PrintSummary {
+    pub filename: String,
+    pub steps_completed: usize,
+    pub total_steps: usize,
+    pub started_at: chrono::DateTime<chrono::Local>,
+    pub finished_at: chrono::DateTime<chrono::Local>,
+    pub duration: Duration,
 }
 
 #[derive(Debug, Clone)]
 pub struct PrintState {
     pub status: PrintStatus,
+    pub mode: RunMode,
     pub current_index: usize,     // index into job.steps
     pub total_steps: usize,       // job.steps.len()
     pub last_gcode: Option<String>,
     pub last_error: Option<String>,
+    pub fault: Option<String>,    // latched fault reason while Faulted
+    pub interlock: Option<String>, // RF interlock trip reason until acknowledged
+    pub last_summary: Option<PrintSummary>,
 }
 
 impl Default for PrintState {
     fn default() -> Self {
         Self {
             status: PrintStatus::Idle,
+            mode: RunMode::Full,
             current_index: 0,
             total_steps: 0,
             last_gcode: None,
             last_error: None,
+            fault: None,
+            interlock: None,
+            last_summary: None,
         }
     }
 }
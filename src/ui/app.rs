@@ -43,8 +43,13 @@ use crate::controllers::{DuetController, MicrowaveController, ArduinoController}
 // In real mode, device tasks are spawned immediately but connect only on command
 use tokio::sync::mpsc;
 use std::sync::{Arc, RwLock};
-use crate::print_engine::{PrintCommand, PrintState, print_engine_task};
+use crate::config::file::AppConfig;
+use crate::utilities::ports::{PortInfo, find_arduino, find_microwave, scan_ports};
+use crate::utilities::units::{dbm_to_watts, format_power, hz_to_mhz, mhz_to_hz, watts_to_dbm};
+use crate::print_engine::{PrintCommand, PrintState, RunMode, print_engine_task};
+use crate::interlock::{InterlockCommand, InterlockState, interlock_task};
 use eframe::egui;
+use egui_plot::{Legend, Line, Plot, VLine};
 use std::time::Instant;
 //use egui_plot::Legend;
 //use tokio::sync::mpsc;
@@ -72,19 +77,39 @@ struct PendingRequest {
 
 // App-level type holding controller trait objects.
 pub struct AppUI{
-    pub duet: Box<dyn DuetController + Send + Sync>,
-    pub microwave: Box<dyn MicrowaveController + Send + Sync>,
-    pub arduino: Box<dyn ArduinoController + Send + Sync>,
+    pub duet: Arc<Box<dyn DuetController + Send + Sync>>,
+    pub microwave: Arc<Box<dyn MicrowaveController + Send + Sync>>,
+    pub arduino: Arc<Box<dyn ArduinoController + Send + Sync>>,
     duet_pending: Option<PendingRequest>,
     microwave_pending: Option<PendingRequest>,
     arduino_pending: Option<PendingRequest>,
     // Print engine
     print_cmd_tx: mpsc::Sender<PrintCommand>,
     print_state: Arc<RwLock<PrintState>>,
+    // RF interlock supervisor
+    interlock_tx: mpsc::Sender<InterlockCommand>,
+    interlock_state: Arc<RwLock<InterlockState>>,
     // Shared setpoints (used by both UI and print engine)
     microwave_power_setpoint: Arc<RwLock<f32>>,
-    microwave_freq_setpoint: i32,
-    arduino_pressure_setpoint: Arc<RwLock<f32>>,
+    microwave_freq_mhz: f64,
+    microwave_band_mhz: [f64; 2],  // configured band, used until the generator reports its own
+    microwave_ramp_w_per_s: f32,  // slew rate for manual power changes
+    // Pulsed RF parameters
+    pulse_enabled: bool,
+    pulse_settings: PulseSettings,
+    // Frequency sweep parameters
+    sweep_start_mhz: f64,
+    sweep_stop_mhz: f64,
+    sweep_step_mhz: f64,
+    sweep_power_w: f32,
+    // Resonance tracking parameters
+    tracking_settings: TrackingSettings,
+    arduino_pressure_setpoint: Arc<RwLock<Vec<f32>>>,  // per pressure channel
+    // Pressure loop tuning parameters
+    tuning_channel: usize,
+    pressure_ramp_psi_per_s: f32,
+    pid_gains: PidGains,
+    autotune_step_psi: f32,
     duet_x_step: f32,
     duet_y_step: f32,
     duet_z_step: f32,
@@ -97,39 +122,72 @@ pub struct AppUI{
     toolpath_start_y: f32,
     toolpath_start_z: f32,
     current_job: Option<std::sync::Arc<crate::job::Job>>,
+    print_start_index: usize,
+    print_run_mode: RunMode,
+    // Serial port selection (Connections panel)
+    serial_ports: Vec<PortInfo>,
+    serial_scan_error: Option<String>,
+    microwave_port: String,
+    arduino_port: String,
+    microwave_usb_id: (u16, u16),
+}
+
+// Serial port drop-down with the selected port's USB details underneath
+fn port_picker(ui: &mut egui::Ui, id: &str, ports: &[PortInfo], selected: &mut String) {
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(selected.clone())
+        .width(ui.available_width())
+        .show_ui(ui, |ui| {
+            for port in ports {
+                ui.selectable_value(selected, port.name.clone(), &port.name)
+                    .on_hover_text(port.details());
+            }
+        });
+    match ports.iter().find(|p| p.name == *selected) {
+        Some(port) => ui.small(port.details()),
+        None => ui.small("not detected"),
+    };
 }
 
 impl AppUI {
-    pub fn new() -> Self {
+    pub fn new(config: AppConfig) -> Self {
         #[cfg(feature="mock")]
         {
-            let duet: Box<dyn DuetController + Send + Sync> = Box::new(MockDuet::new());
-            let microwave: Box<dyn MicrowaveController + Send + Sync> = Box::new(MockMicrowave::new());
-            let arduino: Box<dyn ArduinoController + Send + Sync> = Box::new(MockArduino::new());
+            // Mocks keep their state in-process, so the UI and print engine must share instances
+            let duet: Arc<Box<dyn DuetController + Send + Sync>> = Arc::new(Box::new(MockDuet::new()));
+            let microwave: Arc<Box<dyn MicrowaveController + Send + Sync>> = Arc::new(Box::new(MockMicrowave::new()));
+            let arduino: Arc<Box<dyn ArduinoController + Send + Sync>> = Arc::new(Box::new(MockArduino::new(&config.arduino.channels)));
             
             // Print engine setup
             let (print_cmd_tx, print_cmd_rx) = mpsc::channel::<PrintCommand>(64);
             let print_state = Arc::new(RwLock::new(PrintState::default()));
             let microwave_power_setpoint = Arc::new(RwLock::new(0.0f32));
-            let arduino_pressure_setpoint = Arc::new(RwLock::new(0.0f32));
-            
-            // Controllers for print engine (wrap boxes in Arc)
-            let duet_arc: Arc<Box<dyn DuetController + Send + Sync>> = Arc::new(Box::new(MockDuet::new()));
-            let microwave_arc: Arc<Box<dyn MicrowaveController + Send + Sync>> = Arc::new(Box::new(MockMicrowave::new()));
-            let arduino_arc: Arc<Box<dyn ArduinoController + Send + Sync>> = Arc::new(Box::new(MockArduino::new()));
+            let arduino_pressure_setpoint = Arc::new(RwLock::new(vec![0.0f32; config.arduino.channels.len()]));
             
             // Spawn print engine task
             tokio::spawn(print_engine_task(
                 print_cmd_rx,
                 Arc::clone(&print_state),
-                duet_arc,
-                microwave_arc,
-                arduino_arc,
+                Arc::clone(&duet),
+                Arc::clone(&microwave),
+                Arc::clone(&arduino),
                 Arc::clone(&microwave_power_setpoint),
                 Arc::clone(&arduino_pressure_setpoint),
+                config.print,
+            ));
+
+            // RF interlock supervisor, watching the same microwave instance
+            let (interlock_tx, interlock_rx) = mpsc::channel::<InterlockCommand>(8);
+            let interlock_state = Arc::new(RwLock::new(InterlockState::default()));
+            tokio::spawn(interlock_task(
+                interlock_rx,
+                Arc::clone(&interlock_state),
+                Arc::clone(&microwave),
+                print_cmd_tx.clone(),
+                config.interlock,
             ));
             
-            return Self {
+            let mut app = Self {
                 duet,
                 microwave,
                 arduino,
@@ -138,9 +196,24 @@ impl AppUI {
                 arduino_pending: None,
                 print_cmd_tx,
                 print_state,
+                interlock_tx,
+                interlock_state,
                 microwave_power_setpoint,
-                microwave_freq_setpoint: 0,
+                microwave_freq_mhz: config.microwave.band_mhz[0],
+                microwave_band_mhz: config.microwave.band_mhz,
+                microwave_ramp_w_per_s: config.microwave.power_ramp_w_per_s,
+                pulse_enabled: false,
+                pulse_settings: PulseSettings::default(),
+                sweep_start_mhz: config.microwave.band_mhz[0],
+                sweep_stop_mhz: config.microwave.band_mhz[1],
+                sweep_step_mhz: 1.0,
+                sweep_power_w: 10.0,
+                tracking_settings: config.microwave.tracking,
                 arduino_pressure_setpoint,
+                tuning_channel: 0,
+                pressure_ramp_psi_per_s: 5.0,
+                pid_gains: PidGains::default(),
+                autotune_step_psi: 5.0,
                 duet_x_step: 0.0,
                 duet_y_step: 0.0,
                 duet_z_step: 0.0,
@@ -152,7 +225,16 @@ impl AppUI {
                 toolpath_start_y: 0.0,
                 toolpath_start_z: 0.0,
                 current_job: None,
+                print_start_index: 0,
+                print_run_mode: RunMode::Full,
+                serial_ports: Vec::new(),
+                serial_scan_error: None,
+                microwave_port: config.microwave.serial_port.clone(),
+                arduino_port: config.arduino.serial_port.clone(),
+                microwave_usb_id: (config.microwave.vendor_id, config.microwave.product_id),
             };
+            app.refresh_serial_ports();
+            return app;
         }
 
         #[cfg(feature="real")]
@@ -160,7 +242,7 @@ impl AppUI {
             // Shared state
             let duet_state = Arc::new(RwLock::new(DuetState::default()));
             let microwave_state = Arc::new(RwLock::new(MicrowaveState::default()));
-            let arduino_state = Arc::new(RwLock::new(ArduinoState::default()));
+            let arduino_state = Arc::new(RwLock::new(ArduinoState::new(&config.arduino.channels)));
 
             // Command channels (mpsc end-to-end)
             let (duet_cmd_tx, duet_cmd_rx) = mpsc::channel::<DuetCommand>(64);
@@ -170,7 +252,7 @@ impl AppUI {
             // Duet device task. Connect only on command.
             tokio::spawn({
                 let state_for_task = Arc::clone(&duet_state);
-                let ip = duet_ip.to_string();
+                let ip = config.duet.ip.clone();
                 async move {
                     crate::drivers::duet::duet_control(&ip, duet_cmd_rx, state_for_task).await;
                 }
@@ -179,33 +261,36 @@ impl AppUI {
             // Microwave device task. Connect only on command.
             tokio::spawn({
                 let state_for_task = Arc::clone(&microwave_state);
+                let microwave_config = config.microwave.clone();
                 async move {
-                    let _ = crate::drivers::microwave::microwave_control(MICROWAVE_SERIAL_PORT, MICROWAVE_BAUD_RATE, mw_cmd_rx, state_for_task).await;
+                    let _ = crate::drivers::microwave::microwave_control(microwave_config, mw_cmd_rx, state_for_task).await;
                 }
             });
 
             // Arduino device task. Connect only on command.
             tokio::spawn({
                 let state_for_task = Arc::clone(&arduino_state);
+                let arduino_config = config.arduino.clone();
                 async move {
-                    let _ = crate::drivers::arduino::task::arduino_control(ARDUINO_SERIAL_PORT, ARDUINO_BAUD_RATE, arduino_cmd_rx, state_for_task).await;
+                    let _ = crate::drivers::arduino::task::arduino_control(arduino_config, arduino_cmd_rx, state_for_task).await;
                 }
             });
 
-            let duet: Box<dyn DuetController + Send + Sync> = Box::new(DuetClient::new(duet_cmd_tx.clone(), Arc::clone(&duet_state)));
-            let microwave: Box<dyn MicrowaveController + Send + Sync> = Box::new(MicrowaveClient::new(mw_cmd_tx.clone(), Arc::clone(&microwave_state)));
-            let arduino: Box<dyn ArduinoController + Send + Sync> = Box::new(ArduinoClient::new(arduino_cmd_tx.clone(), Arc::clone(&arduino_state)));
+            let duet: Arc<Box<dyn DuetController + Send + Sync>> = Arc::new(Box::new(DuetClient::new(duet_cmd_tx.clone(), Arc::clone(&duet_state))));
+            let microwave: Arc<Box<dyn MicrowaveController + Send + Sync>> = Arc::new(Box::new(MicrowaveClient::new(mw_cmd_tx.clone(), Arc::clone(&microwave_state))));
+            let arduino: Arc<Box<dyn ArduinoController + Send + Sync>> = Arc::new(Box::new(ArduinoClient::new(arduino_cmd_tx.clone(), Arc::clone(&arduino_state))));
             
             // Print engine setup
             let (print_cmd_tx, print_cmd_rx) = mpsc::channel::<PrintCommand>(64);
             let print_state = Arc::new(RwLock::new(PrintState::default()));
             let microwave_power_setpoint = Arc::new(RwLock::new(0.0f32));
-            let arduino_pressure_setpoint = Arc::new(RwLock::new(0.0f32));
+            let arduino_pressure_setpoint = Arc::new(RwLock::new(vec![0.0f32; config.arduino.channels.len()]));
             
             // Controllers for print engine (wrap boxes in Arc)
             let duet_arc: Arc<Box<dyn DuetController + Send + Sync>> = Arc::new(Box::new(DuetClient::new(duet_cmd_tx, Arc::clone(&duet_state))));
-            let microwave_arc: Arc<Box<dyn MicrowaveController + Send + Sync>> = Arc::new(Box::new(MicrowaveClient::new(mw_cmd_tx, Arc::clone(&microwave_state))));
+            let microwave_arc: Arc<Box<dyn MicrowaveController + Send + Sync>> = Arc::new(Box::new(MicrowaveClient::new(mw_cmd_tx.clone(), Arc::clone(&microwave_state))));
             let arduino_arc: Arc<Box<dyn ArduinoController + Send + Sync>> = Arc::new(Box::new(ArduinoClient::new(arduino_cmd_tx, Arc::clone(&arduino_state))));
+            let interlock_microwave: Arc<Box<dyn MicrowaveController + Send + Sync>> = Arc::new(Box::new(MicrowaveClient::new(mw_cmd_tx, Arc::clone(&microwave_state))));
             
             // Spawn print engine task
             tokio::spawn(print_engine_task(
@@ -216,9 +301,21 @@ impl AppUI {
                 arduino_arc,
                 Arc::clone(&microwave_power_setpoint),
                 Arc::clone(&arduino_pressure_setpoint),
+                config.print,
+            ));
+
+            // RF interlock supervisor
+            let (interlock_tx, interlock_rx) = mpsc::channel::<InterlockCommand>(8);
+            let interlock_state = Arc::new(RwLock::new(InterlockState::default()));
+            tokio::spawn(interlock_task(
+                interlock_rx,
+                Arc::clone(&interlock_state),
+                interlock_microwave,
+                print_cmd_tx.clone(),
+                config.interlock,
             ));
             
-            return Self {
+            let mut app = Self {
                 duet,
                 microwave,
                 arduino,
@@ -227,9 +324,24 @@ impl AppUI {
                 arduino_pending: None,
                 print_cmd_tx,
                 print_state,
+                interlock_tx,
+                interlock_state,
                 microwave_power_setpoint,
-                microwave_freq_setpoint: 0,
+                microwave_freq_mhz: config.microwave.band_mhz[0],
+                microwave_band_mhz: config.microwave.band_mhz,
+                microwave_ramp_w_per_s: config.microwave.power_ramp_w_per_s,
+                pulse_enabled: false,
+                pulse_settings: PulseSettings::default(),
+                sweep_start_mhz: config.microwave.band_mhz[0],
+                sweep_stop_mhz: config.microwave.band_mhz[1],
+                sweep_step_mhz: 1.0,
+                sweep_power_w: 10.0,
+                tracking_settings: config.microwave.tracking,
                 arduino_pressure_setpoint,
+                tuning_channel: 0,
+                pressure_ramp_psi_per_s: 5.0,
+                pid_gains: PidGains::default(),
+                autotune_step_psi: 5.0,
                 duet_x_step: 0.0,
                 duet_y_step: 0.0,
                 duet_z_step: 0.0,
@@ -241,7 +353,38 @@ impl AppUI {
                 toolpath_start_y: 0.0,
                 toolpath_start_z: 0.0,
                 current_job: None,
+                print_start_index: 0,
+                print_run_mode: RunMode::Full,
+                serial_ports: Vec::new(),
+                serial_scan_error: None,
+                microwave_port: config.microwave.serial_port.clone(),
+                arduino_port: config.arduino.serial_port.clone(),
+                microwave_usb_id: (config.microwave.vendor_id, config.microwave.product_id),
             };
+            app.refresh_serial_ports();
+            app
+        }
+    }
+
+    // Rescan serial ports and auto-select devices recognised by their USB IDs;
+    // otherwise keep the current (or configured) port
+    fn refresh_serial_ports(&mut self) {
+        match scan_ports() {
+            Ok(ports) => {
+                self.serial_ports = ports;
+                self.serial_scan_error = None;
+            }
+            Err(e) => {
+                self.serial_ports.clear();
+                self.serial_scan_error = Some(e);
+            }
+        }
+        let (vendor_id, product_id) = self.microwave_usb_id;
+        if let Some(port) = find_microwave(&self.serial_ports, vendor_id, product_id) {
+            self.microwave_port = port.name.clone();
+        }
+        if let Some(port) = find_arduino(&self.serial_ports, ARDUINO_VENDOR_IDS) {
+            self.arduino_port = port.name.clone();
         }
     }
 
@@ -251,21 +394,33 @@ impl AppUI {
         self.duet.send_gcode(&gcode);
     }
 
-    // Stub method for sending microwave power command
+    // Send a microwave power target, ramped at the panel's rate
     fn send_microwave_set_power(&mut self, watts: f32) {
-        // TODO: call microwave controller
-        self.microwave.set_power(watts);
+        self.microwave.set_power(watts, Some(self.microwave_ramp_w_per_s));
     }
 
-    // Stub method for sending microwave frequency command
-    fn send_microwave_set_frequency(&mut self, hz: i32) {
-        self.microwave.set_frequency(hz);
+    // Band of the connected generator in MHz, else the configured one
+    fn microwave_band_mhz(&self, state: &MicrowaveState) -> [f64; 2] {
+        match state.band_hz {
+            Some((min_hz, max_hz)) => [hz_to_mhz(min_hz), hz_to_mhz(max_hz)],
+            None => self.microwave_band_mhz,
+        }
+    }
+
+    // Send a frequency entered in MHz
+    fn send_microwave_set_frequency(&mut self, mhz: f64) {
+        if let Some(hz) = mhz_to_hz(mhz) {
+            self.microwave.set_frequency(hz);
+        }
     }
 
     // Render microwave control section
     fn ui_center_microwave(&mut self, ui: &mut egui::Ui) {
         let microwave_state = self.microwave.state();
-        let panel_h = 165.0;
+        let [band_min, band_max] = self.microwave_band_mhz(&microwave_state);
+        // Keep the entry inside the band if the band changed under it
+        self.microwave_freq_mhz = self.microwave_freq_mhz.clamp(band_min, band_max);
+        let panel_h = 255.0;
 
         ui.add_enabled_ui(microwave_state.connected, |ui| {
             ui.allocate_ui_with_layout(
@@ -311,22 +466,63 @@ impl AppUI {
                             }
                             ui.end_row();
 
-                            // Frequency row
-                            ui.label("Frequency (Hz)");
-                            if ui.button("-5").clicked() {
-                                self.microwave_freq_setpoint = (self.microwave_freq_setpoint - 5).max(0);
-                                if microwave_state.enabled {
-                                    self.send_microwave_set_frequency(self.microwave_freq_setpoint);
-                                }
+                            // Power in dBm, same setpoint
+                            ui.label("Power (dBm)");
+                            ui.label("");
+                            let power_w = *self.microwave_power_setpoint.read().unwrap();
+                            let mut power_dbm = watts_to_dbm(power_w).unwrap_or(0.0);
+                            if ui.add(egui::DragValue::new(&mut power_dbm)
+                                .speed(0.1)
+                                .max_decimals(1)
+                                .range(0.0..=70.0)).changed() {
+                                *self.microwave_power_setpoint.write().unwrap() = dbm_to_watts(power_dbm);
                             }
-                            ui.add(egui::DragValue::new(&mut self.microwave_freq_setpoint)
-                                .speed(1)
-                                .range(0..=i32::MAX));
-                            if ui.button("+5").clicked() {
-                                self.microwave_freq_setpoint = (self.microwave_freq_setpoint + 5).max(0);
-                                if microwave_state.enabled {
-                                    self.send_microwave_set_frequency(self.microwave_freq_setpoint);
-                                }
+                            ui.label("");
+                            ui.end_row();
+
+                            // Frequency row, limited to the generator's band
+                            ui.label("Frequency (MHz)");
+                            if ui.button("-1").clicked() {
+                                self.microwave_freq_mhz = (self.microwave_freq_mhz - 1.0).clamp(band_min, band_max);
+                                self.send_microwave_set_frequency(self.microwave_freq_mhz);
+                            }
+                            let freq_response = ui.add(egui::DragValue::new(&mut self.microwave_freq_mhz)
+                                .speed(0.01)
+                                .max_decimals(3)
+                                .range(band_min..=band_max))
+                                .on_hover_text(format!("Band {:.3}–{:.3} MHz", band_min, band_max));
+                            // Send once the value is committed, not on every drag step
+                            if freq_response.drag_stopped() || (freq_response.changed() && !freq_response.dragged()) {
+                                self.send_microwave_set_frequency(self.microwave_freq_mhz);
+                            }
+                            if ui.button("+1").clicked() {
+                                self.microwave_freq_mhz = (self.microwave_freq_mhz + 1.0).clamp(band_min, band_max);
+                                self.send_microwave_set_frequency(self.microwave_freq_mhz);
+                            }
+                            ui.end_row();
+
+                            // Ramp rate row
+                            ui.label("Ramp (W/s)");
+                            ui.label("");
+                            ui.add(egui::DragValue::new(&mut self.microwave_ramp_w_per_s)
+                                .speed(0.5)
+                                .range(0.1..=1000.0));
+                            ui.label("");
+                            ui.end_row();
+
+                            // Pulse row: period and duty cycle, sent with Apply
+                            ui.checkbox(&mut self.pulse_enabled, "Pulsed");
+                            ui.add_enabled(self.pulse_enabled, egui::DragValue::new(&mut self.pulse_settings.period_us)
+                                .speed(10)
+                                .range(PULSE_PERIOD_MIN_US..=PULSE_PERIOD_MAX_US)
+                                .suffix(" µs"));
+                            ui.add_enabled(self.pulse_enabled, egui::DragValue::new(&mut self.pulse_settings.duty_pct)
+                                .speed(0.5)
+                                .range(1.0..=99.0)
+                                .suffix(" %"));
+                            if ui.button("Apply").clicked() {
+                                let pulse = self.pulse_enabled.then_some(self.pulse_settings);
+                                self.microwave.set_pulse(pulse);
                             }
                             ui.end_row();
                         });
@@ -358,8 +554,9 @@ impl AppUI {
                                 self.microwave.rf_off();
                             } else {
                                 // Turn ON with current setpoint
+                                // Soft start: the driver ramps up from 0 W
                                 let power_sp = *self.microwave_power_setpoint.read().unwrap();
-                                self.microwave.set_power(power_sp);
+                                self.send_microwave_set_power(power_sp);
                                 self.microwave.rf_on();
                             }
                         }
@@ -370,11 +567,134 @@ impl AppUI {
     });
 }
 
-    // Render pressure control section
+    // Render frequency sweep controls and the last sweep curve
+    fn ui_center_sweep(&mut self, ui: &mut egui::Ui) {
+        let microwave_state = self.microwave.state();
+        let [band_min, band_max] = self.microwave_band_mhz(&microwave_state);
+
+        // A sweep takes RF over, so never while RF is on or a print is active
+        let print_status = self.print_state.read().unwrap().status;
+        let print_active = !matches!(print_status, crate::print_engine::PrintStatus::Idle | crate::print_engine::PrintStatus::Faulted);
+        let sweep_allowed = microwave_state.connected && !microwave_state.sweep_in_progress && !microwave_state.enabled && !print_active;
+
+        egui::CollapsingHeader::new("Frequency Sweep").show(ui, |ui| {
+            if print_active || microwave_state.enabled {
+                ui.small("Switch RF off and finish the print to sweep.");
+            }
+            ui.add_enabled_ui(sweep_allowed, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Start (MHz)");
+                    ui.add(egui::DragValue::new(&mut self.sweep_start_mhz).speed(0.1).max_decimals(3).range(band_min..=band_max));
+                    ui.label("Stop (MHz)");
+                    ui.add(egui::DragValue::new(&mut self.sweep_stop_mhz).speed(0.1).max_decimals(3).range(band_min..=band_max));
+                    ui.label("Step (MHz)");
+                    ui.add(egui::DragValue::new(&mut self.sweep_step_mhz).speed(0.01).max_decimals(3).range(0.001..=100.0));
+                    ui.label("Power (W)");
+                    ui.add(egui::DragValue::new(&mut self.sweep_power_w).speed(1.0).range(0.0..=f32::INFINITY));
+                    if ui.button("Sweep").clicked() {
+                        let hz = (mhz_to_hz(self.sweep_start_mhz), mhz_to_hz(self.sweep_stop_mhz), mhz_to_hz(self.sweep_step_mhz));
+                        if let (Some(start_hz), Some(stop_hz), Some(step_hz)) = hz {
+                            self.microwave.sweep(start_hz, stop_hz, step_hz, self.sweep_power_w);
+                        }
+                    }
+                });
+            });
+
+            ui.horizontal(|ui| {
+                if microwave_state.sweep_in_progress {
+                    ui.spinner();
+                    ui.label(format!("Sweeping… {} points", microwave_state.sweep.len()));
+                } else if let Some(best_hz) = microwave_state.sweep_best_hz {
+                    ui.label(format!("Best match: {:.3} MHz", hz_to_mhz(best_hz)));
+                    // The generator is already parked there; keep the UI setpoint in step
+                    if ui.button("Use as setpoint").clicked() {
+                        self.microwave_freq_mhz = hz_to_mhz(best_hz);
+                    }
+                }
+            });
+
+            let forward: Vec<[f64; 2]> = microwave_state.sweep
+                .iter()
+                .map(|p| [hz_to_mhz(p.frequency_hz), p.forward_w as f64])
+                .collect();
+            let reflected: Vec<[f64; 2]> = microwave_state.sweep
+                .iter()
+                .map(|p| [hz_to_mhz(p.frequency_hz), p.reflected_w as f64])
+                .collect();
+            Plot::new("sweep_plot")
+                .height(160.0)
+                .legend(Legend::default())
+                .x_axis_label("Frequency (MHz)")
+                .y_axis_label("Power (W)")
+                .show(ui, |plot_ui| {
+                    plot_ui.line(Line::new("Forward", forward));
+                    plot_ui.line(Line::new("Reflected", reflected));
+                    if let Some(best_hz) = microwave_state.sweep_best_hz {
+                        plot_ui.vline(VLine::new("Best match", hz_to_mhz(best_hz)));
+                    }
+                });
+        });
+    }
+
+    // Render resonance tracking controls and loop status
+    fn ui_center_tracking(&mut self, ui: &mut egui::Ui) {
+        let microwave_state = self.microwave.state();
+        let tracking = microwave_state.tracking;
+
+        egui::CollapsingHeader::new("Frequency Tracking").show(ui, |ui| {
+            ui.add_enabled_ui(microwave_state.connected, |ui| {
+                ui.horizontal(|ui| {
+                    let mut enabled = tracking.enabled;
+                    if ui.checkbox(&mut enabled, "Track resonance").changed() {
+                        self.microwave.set_tracking(enabled.then_some(self.tracking_settings));
+                    }
+                    // Parameters apply the next time tracking is switched on
+                    ui.add_enabled_ui(!tracking.enabled, |ui| {
+                        ui.label("Step (Hz)");
+                        ui.add(egui::DragValue::new(&mut self.tracking_settings.step_hz).speed(1000).range(1..=i32::MAX));
+                        ui.label("Interval (ms)");
+                        ui.add(egui::DragValue::new(&mut self.tracking_settings.interval_ms).speed(10).range(50..=60_000));
+                        ui.label("Span ± (Hz)");
+                        ui.add(egui::DragValue::new(&mut self.tracking_settings.span_hz).speed(10_000).range(1..=i32::MAX));
+                    });
+                });
+            });
+
+            if tracking.enabled {
+                let state_text = if !tracking.active {
+                    "waiting for RF on"
+                } else if tracking.at_limit {
+                    "at limit"
+                } else {
+                    "tracking"
+                };
+                let frequency_text = tracking.frequency_hz
+                    .map(|hz| format!("{:.3} MHz", hz_to_mhz(hz)))
+                    .unwrap_or_else(|| "—".to_string());
+                let bounds_text = tracking.bounds_hz
+                    .map(|(lo, hi)| format!("{:.3}–{:.3} MHz", hz_to_mhz(lo), hz_to_mhz(hi)))
+                    .unwrap_or_else(|| "—".to_string());
+                let ratio_text = tracking.reflection_ratio
+                    .map(|r| format!("{:.1} %", r * 100.0))
+                    .unwrap_or_else(|| "—".to_string());
+                let text = format!(
+                    "{}  frequency {}  bounds {}  reflected {}",
+                    state_text, frequency_text, bounds_text, ratio_text
+                );
+                if tracking.at_limit {
+                    ui.colored_label(egui::Color32::ORANGE, text);
+                } else {
+                    ui.label(text);
+                }
+            }
+        });
+    }
+
+    // Render pressure control section, one row per channel
     fn ui_center_pressure(&mut self, ui: &mut egui::Ui) {
         let arduino_state = self.arduino.state();
         let panel_w = 360.0;
-        let panel_h = 165.0;
+        let panel_h = 60.0 + 36.0 * arduino_state.channels.len() as f32;
 
         ui.add_enabled_ui(arduino_state.connected, |ui| {
             ui.allocate_ui_with_layout(
@@ -387,70 +707,200 @@ impl AppUI {
                         ui.add_space(5.0);
 
                         egui::Grid::new("pressure_grid")
-                            .num_columns(4)
+                            .num_columns(6)
                             .spacing([10.0, 8.0])
                             .show(ui, |ui| {
-                                // Pressure row
-                                ui.label("Pressure (psi)");
-                                if ui.button("-1").clicked() {
-                                    let new_val = {
-                                        let mut sp = self.arduino_pressure_setpoint.write().unwrap();
-                                        *sp = (*sp - 1.0).max(0.0);
-                                        *sp
-                                    };
-                                    if arduino_state.enabled {
-                                        self.arduino.set_pressure_setpoint(new_val);
+                                ui.label("Channel");
+                                ui.label("");
+                                ui.label("Setpoint (psi)");
+                                ui.label("");
+                                ui.label("Measured");
+                                ui.label("");
+                                ui.end_row();
+
+                                for (channel, c) in arduino_state.channels.iter().enumerate() {
+                                    ui.label(&c.name);
+                                    let mut setpoint = self.arduino_pressure_setpoint.read().unwrap().get(channel).copied().unwrap_or(0.0);
+                                    let mut send = false;
+                                    if ui.button("-1").clicked() {
+                                        setpoint = (setpoint - 1.0).max(0.0);
+                                        send = true;
                                     }
-                                }
-                                let mut pressure_val = self.arduino_pressure_setpoint.read().unwrap().clone();
-                                if ui.add(egui::DragValue::new(&mut pressure_val)
-                                    .speed(0.1)
-                                    .range(0.0..=f32::INFINITY)).changed() {
-                                    *self.arduino_pressure_setpoint.write().unwrap() = pressure_val;
-                                }
-                                if ui.button("+1").clicked() {
-                                    let new_val = {
-                                        let mut sp = self.arduino_pressure_setpoint.write().unwrap();
-                                        *sp = (*sp + 1.0).max(0.0);
-                                        *sp
-                                    };
-                                    if arduino_state.enabled {
-                                        self.arduino.set_pressure_setpoint(new_val);
+                                    let edited = ui.add(egui::DragValue::new(&mut setpoint)
+                                        .speed(0.1)
+                                        .range(0.0..=f32::INFINITY)).changed();
+                                    if ui.button("+1").clicked() {
+                                        setpoint += 1.0;
+                                        send = true;
+                                    }
+                                    if (edited || send)
+                                        && let Some(sp) = self.arduino_pressure_setpoint.write().unwrap().get_mut(channel)
+                                    {
+                                        *sp = setpoint;
+                                    }
+                                    if send && c.enabled {
+                                        self.arduino.set_pressure_setpoint(channel, setpoint);
                                     }
-                                }
-                                ui.end_row();
 
-                                // Row 2: current pressure readback (gauge)
-                                ui.label("Current Pressure:");
-                                ui.label(format!("{:.1} psi", arduino_state.pressure_measured_psi));
-                                ui.label(""); // spacer to fill columns 3-4
-                                ui.label("");
-                                ui.end_row();
-                            });
+                                    ui.label(format!("{:.1} psi", c.pressure_measured_psi));
 
-                        ui.add_space(8.0);
-                        ui.horizontal_centered(|ui| {
-                            let button_label = if arduino_state.enabled { "ON" } else { "OFF" };
-                            let button = egui::Button::new(button_label);
-                            if ui.add_sized([200.0, 40.0], button).clicked() {
-                                if arduino_state.enabled {
-                                    // Turn OFF
-                                    self.arduino.set_pressure_setpoint(0.0);
-                                    self.arduino.enable(false);
-                                } else {
-                                    // Turn ON with current setpoint
-                                    let pressure_sp = *self.arduino_pressure_setpoint.read().unwrap();
-                                    self.arduino.set_pressure_setpoint(pressure_sp);
-                                    self.arduino.enable(true);
+                                    let button_label = if c.enabled { "ON" } else { "OFF" };
+                                    if ui.add_sized([60.0, 24.0], egui::Button::new(button_label)).clicked() {
+                                        if c.enabled {
+                                            // Turn OFF
+                                            self.arduino.set_pressure_setpoint(channel, 0.0);
+                                            self.arduino.enable(channel, false);
+                                        } else {
+                                            // Turn ON with current setpoint
+                                            self.arduino.set_pressure_setpoint(channel, setpoint);
+                                            self.arduino.enable(channel, true);
+                                        }
+                                    }
+                                    ui.end_row();
                                 }
-                            }
-                        });
+                            });
                     });
             },
         );
     });
 }
 
+    // Render pressure ramp, PID gains and the autotune step test
+    fn ui_center_tuning(&mut self, ui: &mut egui::Ui) {
+        let arduino_state = self.arduino.state();
+        let running = matches!(arduino_state.autotune, Some(AutotuneStatus::Running { .. }));
+        if self.tuning_channel >= arduino_state.channels.len() {
+            self.tuning_channel = 0;
+        }
+        let channel = self.tuning_channel;
+        let tuned = arduino_state.channels.get(channel).cloned().unwrap_or_default();
+
+        ui.add_enabled_ui(arduino_state.connected, |ui| {
+            egui::Frame::group(ui.style()).show(ui, |ui| {
+                ui.set_width(360.0);
+                ui.heading("Pressure Tuning");
+                ui.add_space(5.0);
+
+                egui::Grid::new("tuning_grid")
+                    .num_columns(2)
+                    .spacing([10.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Channel");
+                        egui::ComboBox::from_id_salt("tuning_channel")
+                            .selected_text(tuned.name.as_str())
+                            .show_ui(ui, |ui| {
+                                for (i, c) in arduino_state.channels.iter().enumerate() {
+                                    ui.selectable_value(&mut self.tuning_channel, i, c.name.as_str());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("Ramp (psi/s)");
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut self.pressure_ramp_psi_per_s).speed(0.1).range(0.0..=f32::INFINITY));
+                            if ui.button("Set").clicked() {
+                                self.arduino.set_ramp_rate(channel, self.pressure_ramp_psi_per_s);
+                            }
+                            let current = tuned.ramp_rate_psi_per_s
+                                .map(|rate| if rate > 0.0 { format!("{:.1} psi/s", rate) } else { "step".to_string() })
+                                .unwrap_or_else(|| "—".to_string());
+                            ui.label(format!("controller: {}", current));
+                        });
+                        ui.end_row();
+
+                        ui.label("PID gains");
+                        ui.horizontal(|ui| {
+                            ui.label("Kp");
+                            ui.add(egui::DragValue::new(&mut self.pid_gains.kp).speed(0.01).max_decimals(4).range(0.0..=f32::INFINITY));
+                            ui.label("Ki");
+                            ui.add(egui::DragValue::new(&mut self.pid_gains.ki).speed(0.01).max_decimals(4).range(0.0..=f32::INFINITY));
+                            ui.label("Kd");
+                            ui.add(egui::DragValue::new(&mut self.pid_gains.kd).speed(0.001).max_decimals(4).range(0.0..=f32::INFINITY));
+                        });
+                        ui.end_row();
+
+                        ui.label("");
+                        ui.horizontal(|ui| {
+                            if ui.button("Read").clicked() {
+                                self.arduino.get_pid(channel);
+                            }
+                            if ui.button("Write").clicked() {
+                                self.arduino.set_pid(channel, self.pid_gains);
+                            }
+                            match tuned.pid {
+                                Some(pid) => {
+                                    ui.label(format!("controller: {:.3} / {:.3} / {:.3}", pid.kp, pid.ki, pid.kd));
+                                    if pid != self.pid_gains && ui.small_button("Edit these").clicked() {
+                                        self.pid_gains = pid;
+                                    }
+                                }
+                                None => {
+                                    ui.label("controller: —");
+                                }
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("Autotune step (psi)");
+                        ui.horizontal(|ui| {
+                            ui.add(egui::DragValue::new(&mut self.autotune_step_psi).speed(0.1));
+                            let can_start = tuned.enabled && !running;
+                            if ui.add_enabled(can_start, egui::Button::new("Run step test")).clicked() {
+                                self.arduino.autotune(channel, self.autotune_step_psi);
+                            }
+                        });
+                        ui.end_row();
+                    });
+
+                match arduino_state.autotune {
+                    Some(AutotuneStatus::Running { channel: tuning, from_psi, to_psi }) => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            let name = arduino_state.channels.get(tuning).map_or("?", |c| c.name.as_str());
+                            ui.label(format!("Step test on {}: {:.1} → {:.1} psi…", name, from_psi, to_psi));
+                        });
+                    }
+                    Some(AutotuneStatus::Done(result)) => {
+                        ui.horizontal(|ui| {
+                            let gains = result.gains;
+                            ui.label(format!(
+                                "Suggested {:.3} / {:.3} / {:.3}  rise {:.2} s  overshoot {:.1} %",
+                                gains.kp, gains.ki, gains.kd, result.rise_time_s, result.overshoot_pct
+                            ));
+                            // Suggestions only take effect once written
+                            if ui.button("Apply").clicked() {
+                                self.pid_gains = gains;
+                                self.arduino.set_pid(channel, gains);
+                            }
+                        });
+                    }
+                    Some(AutotuneStatus::Failed(ref reason)) => {
+                        ui.colored_label(egui::Color32::RED, format!("Autotune failed: {}", reason));
+                    }
+                    None => {}
+                }
+
+                let setpoint: Vec<[f64; 2]> = arduino_state.step_test
+                    .iter()
+                    .map(|p| [p.t_s as f64, p.setpoint_psi as f64])
+                    .collect();
+                let measured: Vec<[f64; 2]> = arduino_state.step_test
+                    .iter()
+                    .map(|p| [p.t_s as f64, p.measured_psi as f64])
+                    .collect();
+                Plot::new("step_test_plot")
+                    .height(140.0)
+                    .legend(Legend::default())
+                    .x_axis_label("Time (s)")
+                    .y_axis_label("Pressure (psi)")
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(Line::new("Setpoint", setpoint));
+                        plot_ui.line(Line::new("Measured", measured));
+                    });
+            });
+        });
+    }
+
     // Render duet control section (jog + send)
     fn ui_center_duet(&mut self, ui: &mut egui::Ui) {
         let duet_state = self.duet.state();
@@ -674,10 +1124,10 @@ impl AppUI {
 
     // Render print controls section
     fn ui_print_controls(&mut self, ui: &mut egui::Ui) {
-        // Panel enabled only if job loaded and duet connected
+        // Panel enabled only if a job is loaded; the engine checks the connections
+        // each run mode needs when it starts
         let has_job = self.current_job.is_some();
-        let duet_connected = self.duet.state().connected;
-        let panel_enabled = has_job && duet_connected;
+        let panel_enabled = has_job;
 
         ui.add_enabled_ui(panel_enabled, |ui| {
             egui::Frame::group(ui.style()).show(ui, |ui| {
@@ -690,16 +1140,48 @@ impl AppUI {
                 // Button enable states based on PrintStatus
                 let (start_enabled, pause_enabled, resume_enabled, abort_enabled) = match ps.status {
                     crate::print_engine::PrintStatus::Idle => (true, false, false, false),
-                    crate::print_engine::PrintStatus::Printing => (false, true, false, true),
+                    crate::print_engine::PrintStatus::Preheating
+                    | crate::print_engine::PrintStatus::PressureSettling
+                    | crate::print_engine::PrintStatus::Running => (false, true, false, true),
                     crate::print_engine::PrintStatus::Paused => (false, false, true, true),
+                    crate::print_engine::PrintStatus::Faulted => (false, false, false, false),
+                    crate::print_engine::PrintStatus::Homing
+                    | crate::print_engine::PrintStatus::Traveling
+                    | crate::print_engine::PrintStatus::Finishing => (false, false, false, true),
                 };
+                let faulted = ps.status == crate::print_engine::PrintStatus::Faulted;
+                // An RF run cannot resume until the interlock is acknowledged
+                let interlocked = ps.interlock.is_some() && ps.mode.drives_rf();
+                let resume_enabled = resume_enabled && !interlocked;
+
+                // Offer a restart from the step that faulted
+                if faulted {
+                    self.print_start_index = ps.current_index;
+                }
+
+                // Run mode for the next start
+                ui.horizontal(|ui| {
+                    ui.label("Mode:");
+                    ui.add_enabled_ui(start_enabled, |ui| {
+                        egui::ComboBox::from_id_salt("print_run_mode")
+                            .selected_text(self.print_run_mode.label())
+                            .show_ui(ui, |ui| {
+                                for mode in RunMode::ALL {
+                                    ui.selectable_value(&mut self.print_run_mode, mode, mode.label());
+                                }
+                            });
+                    });
+                });
 
                 // Row of control buttons
                 ui.horizontal(|ui| {
                     // Start button
                     if ui.add_enabled(start_enabled, egui::Button::new("Start")).clicked() {
                         if let Some(ref job) = self.current_job {
-                            let cmd = crate::print_engine::PrintCommand::Start(Arc::clone(job));
+                            let cmd = crate::print_engine::PrintCommand::Start {
+                                job: Arc::clone(job),
+                                mode: self.print_run_mode,
+                            };
                             if let Err(e) = self.print_cmd_tx.try_send(cmd) {
                                 // Could set error in state, but for now just log
                                 eprintln!("Failed to send Start command: {}", e);
@@ -730,6 +1212,39 @@ impl AppUI {
                             eprintln!("Failed to send Abort command: {}", e);
                         }
                     }
+
+                    // Acknowledge a latched fault
+                    if ui.add_enabled(faulted, egui::Button::new("Clear Fault")).clicked() {
+                        let cmd = crate::print_engine::PrintCommand::ClearFault;
+                        if let Err(e) = self.print_cmd_tx.try_send(cmd) {
+                            eprintln!("Failed to send ClearFault command: {}", e);
+                        }
+                    }
+                });
+
+                // Start from an arbitrary step (e.g. after a fault)
+                ui.horizontal(|ui| {
+                    let last_index = self.current_job.as_ref().map_or(0, |job| job.steps.len().saturating_sub(1));
+                    self.print_start_index = self.print_start_index.min(last_index);
+                    ui.label("Start at step");
+                    ui.add_enabled(
+                        start_enabled,
+                        egui::DragValue::new(&mut self.print_start_index)
+                            .speed(1)
+                            .range(0..=last_index),
+                    );
+                    if ui.add_enabled(start_enabled, egui::Button::new("Start At")).clicked()
+                        && let Some(ref job) = self.current_job
+                    {
+                        let cmd = crate::print_engine::PrintCommand::StartAt {
+                            job: Arc::clone(job),
+                            index: self.print_start_index,
+                            mode: self.print_run_mode,
+                        };
+                        if let Err(e) = self.print_cmd_tx.try_send(cmd) {
+                            eprintln!("Failed to send StartAt command: {}", e);
+                        }
+                    }
                 });
 
                 ui.add_space(8.0);
@@ -739,14 +1254,36 @@ impl AppUI {
                     ui.label("Status:");
                     let last_gcode_display = ps.last_gcode.as_deref().unwrap_or("—");
                     let status_text = format!(
-                        "{:?}  step {}/{}  last: {}",
-                        ps.status, ps.current_index, ps.total_steps, last_gcode_display
+                        "{:?} ({})  step {}/{}  last: {}",
+                        ps.status, ps.mode.label(), ps.current_index, ps.total_steps, last_gcode_display
                     );
                     ui.label(status_text);
                 });
 
-                // Display last error if present
-                if let Some(ref error) = ps.last_error {
+                // Summary of the last completed job
+                if let Some(ref summary) = ps.last_summary {
+                    ui.horizontal(|ui| {
+                        ui.label("Last job:");
+                        ui.label(format!(
+                            "{}  {}/{} steps  {} - {}  ({:.0} s)",
+                            summary.filename,
+                            summary.steps_completed,
+                            summary.total_steps,
+                            summary.started_at.format("%H:%M:%S"),
+                            summary.finished_at.format("%H:%M:%S"),
+                            summary.duration.as_secs_f32()
+                        ));
+                    });
+                }
+
+                // Latched fault takes precedence over the last error
+                if let Some(ref fault) = ps.fault {
+                    ui.add_space(4.0);
+                    ui.horizontal(|ui| {
+                        ui.label("Fault:");
+                        ui.colored_label(egui::Color32::RED, fault);
+                    });
+                } else if let Some(ref error) = ps.last_error {
                     ui.add_space(4.0);
                     ui.horizontal(|ui| {
                         ui.label("Error:");
@@ -810,6 +1347,18 @@ impl AppUI {
                 ui.heading("Connections");
                 ui.add_space(10.0);
 
+                // Serial port scan
+                ui.horizontal(|ui| {
+                    ui.label(format!("Serial ports: {}", self.serial_ports.len()));
+                    if ui.button("Refresh").clicked() {
+                        self.refresh_serial_ports();
+                    }
+                });
+                if let Some(ref err) = self.serial_scan_error {
+                    ui.colored_label(egui::Color32::RED, err);
+                }
+                ui.add_space(10.0);
+
                 // Duet section
                 ui.group(|ui| {
                     ui.label("Duet");
@@ -857,6 +1406,9 @@ impl AppUI {
                 ui.group(|ui| {
                     ui.label("Microwave");
                     let microwave_state = self.microwave.state();
+                    ui.add_enabled_ui(!microwave_state.connected && self.microwave_pending.is_none(), |ui| {
+                        port_picker(ui, "microwave_port", &self.serial_ports, &mut self.microwave_port);
+                    });
                     let (button_label, button_enabled) = if let Some(ref pending) = self.microwave_pending {
                         match pending.action {
                             PendingAction::Connect => ("Connecting…", false),
@@ -878,7 +1430,7 @@ impl AppUI {
                                 started_at: Instant::now(),
                             });
                         } else {
-                            self.microwave.connect();
+                            self.microwave.connect(&self.microwave_port);
                             self.microwave_pending = Some(PendingRequest {
                                 action: PendingAction::Connect,
                                 started_at: Instant::now(),
@@ -892,6 +1444,14 @@ impl AppUI {
                     if let Some(ref err) = microwave_state.last_error {
                         ui.colored_label(egui::Color32::RED, format!("Error: {}", err));
                     }
+                    // Latched generator faults; shutdowns in red, warnings in yellow
+                    for fault in &microwave_state.faults {
+                        let color = if fault.shuts_down_rf() { egui::Color32::RED } else { egui::Color32::YELLOW };
+                        ui.colored_label(color, fault.description());
+                    }
+                    if !microwave_state.faults.is_empty() && ui.button("Clear Errors").clicked() {
+                        self.microwave.clear_errors();
+                    }
                 });
 
                 ui.add_space(10.0);
@@ -900,6 +1460,9 @@ impl AppUI {
                 ui.group(|ui| {
                     ui.label("Arduino (Pressure)");
                     let arduino_state = self.arduino.state();
+                    ui.add_enabled_ui(!arduino_state.connected && self.arduino_pending.is_none(), |ui| {
+                        port_picker(ui, "arduino_port", &self.serial_ports, &mut self.arduino_port);
+                    });
                     let (button_label, button_enabled) = if let Some(ref pending) = self.arduino_pending {
                         match pending.action {
                             PendingAction::Connect => ("Connecting…", false),
@@ -921,7 +1484,7 @@ impl AppUI {
                                 started_at: Instant::now(),
                             });
                         } else {
-                            self.arduino.connect();
+                            self.arduino.connect(&self.arduino_port);
                             self.arduino_pending = Some(PendingRequest {
                                 action: PendingAction::Connect,
                                 started_at: Instant::now(),
@@ -932,6 +1495,33 @@ impl AppUI {
                     if let Some(ref status) = arduino_state.status {
                         ui.label(status);
                     }
+                    if arduino_state.connected {
+                        ui.label(match arduino_state.protocol_version {
+                            Some(2) => "Protocol: v2 (framed, acknowledged)".to_string(),
+                            Some(version) => format!("Protocol: v{}", version),
+                            None => "Protocol: negotiating…".to_string(),
+                        });
+                        if let Some(ref fw) = arduino_state.firmware {
+                            let (min, max) = fw.pressure_range_psi;
+                            ui.label(format!("Firmware: {} {} ({}, {}–{} psi)", fw.name, fw.version, fw.sensor, min, max))
+                                .on_hover_text(format!("Commands: {}", fw.commands.join(", ")));
+                        }
+                        // Enabling is refused until this is resolved
+                        if let Some(ref problem) = arduino_state.firmware_problem {
+                            ui.colored_label(egui::Color32::RED, format!("Firmware: {}", problem));
+                        }
+                        // Heartbeat health of the firmware watchdog
+                        if arduino_state.watchdog_tripped {
+                            ui.colored_label(egui::Color32::RED, "Heartbeat: watchdog TRIPPED, pressure vented");
+                        } else if let Some(ms) = arduino_state.watchdog_ms {
+                            match arduino_state.heartbeat_misses {
+                                0 => ui.label(format!("Heartbeat: OK (watchdog {} ms)", ms)),
+                                misses => ui.colored_label(egui::Color32::YELLOW, format!("Heartbeat: {} missed (watchdog {} ms)", misses, ms)),
+                            };
+                        } else if arduino_state.firmware.is_some() {
+                            ui.label("Heartbeat: watchdog off or not supported by firmware");
+                        }
+                    }
                     if let Some(ref err) = arduino_state.last_error {
                         ui.colored_label(egui::Color32::RED, format!("Error: {}", err));
                     }
@@ -939,6 +1529,33 @@ impl AppUI {
             });
     }
 
+    // Latched RF interlock banner with the operator acknowledgement
+    fn ui_interlock_banner(&mut self, ui: &mut egui::Ui) {
+        let interlock = self.interlock_state.read().unwrap().clone();
+        let Some(trip) = interlock.tripped else {
+            return;
+        };
+        egui::Frame::group(ui.style())
+            .fill(egui::Color32::from_rgb(90, 0, 0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        egui::Color32::WHITE,
+                        format!("RF INTERLOCK at {}: {}. RF is held off.", trip.at.format("%H:%M:%S"), trip),
+                    );
+                    if ui.button("Acknowledge").clicked()
+                        && let Err(e) = self.interlock_tx.try_send(InterlockCommand::Acknowledge)
+                    {
+                        eprintln!("Failed to send interlock Acknowledge: {}", e);
+                    }
+                });
+                if let Some(ref err) = interlock.last_error {
+                    ui.colored_label(egui::Color32::YELLOW, err);
+                }
+            });
+        ui.add_space(8.0);
+    }
+
     // Render right panel with telemetry displays
     fn ui_right_panel(&mut self, ctx: &egui::Context) {
         egui::SidePanel::right("right_panel")
@@ -998,29 +1615,107 @@ impl AppUI {
                         ui.label(microwave_state.status.as_deref().unwrap_or("—"));
                         ui.end_row();
 
+                        ui.label("RF Mode");
+                        ui.label(match microwave_state.pulse {
+                            Some(pulse) => format!(
+                                "Pulsed {} µs ({} Hz) @ {:.0} %",
+                                pulse.period_us,
+                                pulse.frequency_hz(),
+                                pulse.duty_pct
+                            ),
+                            None => "CW".to_string(),
+                        });
+                        ui.end_row();
+
+                        ui.label("Frequency");
+                        ui.label(microwave_state.frequency_hz
+                            .map(|hz| format!("{:.3} MHz", hz_to_mhz(hz)))
+                            .unwrap_or_else(|| "—".to_string()));
+                        ui.end_row();
+
+                        ui.label("Frequency (readback)");
+                        ui.label(microwave_state.frequency_readback_hz
+                            .map(|hz| format!("{:.3} MHz", hz_to_mhz(hz)))
+                            .unwrap_or_else(|| "—".to_string()));
+                        ui.end_row();
+
+                        ui.label("Target Power");
+                        ui.label(format_power(microwave_state.target_power_w));
+                        ui.end_row();
+
+                        ui.label("Commanded Power");
+                        let ramping = microwave_state.enabled
+                            && (microwave_state.commanded_power_w - microwave_state.target_power_w).abs() >= 0.05;
+                        ui.label(format!(
+                            "{}{}",
+                            format_power(microwave_state.commanded_power_w),
+                            if ramping { " (ramping)" } else { "" }
+                        ));
+                        ui.end_row();
+
                         ui.label("Power");
-                        ui.label(format!("{:.1} W", microwave_state.power_watts));
+                        ui.label(format_power(microwave_state.power_watts));
+                        ui.end_row();
+
+                        // Readings absent until the first telemetry poll answers
+                        let reading = |value: Option<f32>, unit: &str| {
+                            value
+                                .map(|v| format!("{:.1}{}", v, unit))
+                                .unwrap_or_else(|| "—".to_string())
+                        };
+
+                        ui.label("Forward Power");
+                        ui.label(microwave_state.forward_power_w.map(format_power).unwrap_or_else(|| "—".to_string()));
+                        ui.end_row();
+
+                        ui.label("Reflected Power");
+                        ui.label(microwave_state.reflected_power_w.map(format_power).unwrap_or_else(|| "—".to_string()));
                         ui.end_row();
 
                         ui.label("VSWR");
-                        let vswr_text = microwave_state.vswr
-                            .map(|v| format!("{:.1}", v))
-                            .unwrap_or_else(|| "—".to_string());
-                        ui.label(vswr_text);
+                        ui.label(reading(microwave_state.vswr, ""));
                         ui.end_row();
 
                         ui.label("Forward Ratio");
                         let fr_text = microwave_state.forward_ratio
-                            .map(|v| format!("{:.1}", v))
+                            .map(|v| format!("{:.0} %", v * 100.0))
                             .unwrap_or_else(|| "—".to_string());
                         ui.label(fr_text);
                         ui.end_row();
 
-                        ui.label("Temperature");
-                        let temp_text = microwave_state.temperature_c
-                            .map(|t| format!("{:.1} °C", t))
+                        ui.label("PA Temperature");
+                        ui.label(reading(microwave_state.temperature_c, " °C"));
+                        ui.end_row();
+
+                        ui.label("PA Current");
+                        ui.label(reading(microwave_state.pa_current_a, " A"));
+                        ui.end_row();
+
+                        ui.label("PA Voltage");
+                        ui.label(reading(microwave_state.pa_voltage_v, " V"));
+                        ui.end_row();
+
+                        ui.label("Status Word");
+                        let status_word_text = microwave_state.status_word
+                            .map(|w| format!("0x{:08X}", w))
                             .unwrap_or_else(|| "—".to_string());
-                        ui.label(temp_text);
+                        ui.label(status_word_text);
+                        ui.end_row();
+
+                        ui.label("Generator Faults");
+                        if microwave_state.faults.is_empty() {
+                            ui.label("None");
+                        } else {
+                            let text: Vec<String> = microwave_state.faults.iter().map(|f| f.description()).collect();
+                            ui.colored_label(egui::Color32::RED, text.join(", "));
+                        }
+                        ui.end_row();
+
+                        ui.label("RF Interlock");
+                        match self.interlock_state.read().unwrap().tripped {
+                            Some(ref trip) => ui.colored_label(egui::Color32::RED, format!("TRIPPED: {}", trip.kind.label())),
+                            None => ui.label("OK"),
+                        };
                         ui.end_row();
 
                         // Spacing row
@@ -1028,9 +1723,37 @@ impl AppUI {
                         ui.label("");
                         ui.end_row();
 
-                        // Pressure (placeholder)
-                        ui.label("Pressure");
-                        ui.label("—");
+                        // Pressure section
+                        let arduino_state = self.arduino.state();
+                        for c in &arduino_state.channels {
+                            ui.label(format!("Pressure ({})", c.name));
+                            ui.label(match (arduino_state.connected, c.loop_current_ma) {
+                                (false, _) => "—".to_string(),
+                                (true, Some(ma)) => format!("{:.2} psi  {:.1} mA", c.pressure_measured_psi, ma),
+                                (true, None) => format!("{:.2} psi", c.pressure_measured_psi),
+                            });
+                            ui.end_row();
+                        }
+
+                        ui.label("Pressure Telemetry");
+                        match (arduino_state.stream_hz, arduino_state.sample_rate_hz) {
+                            _ if !arduino_state.connected => ui.label("—"),
+                            (Some(hz), Some(rate)) => ui.label(format!(
+                                "streaming {:.1} Hz (set {} Hz), {} dropped",
+                                rate, hz, arduino_state.dropped_samples
+                            )),
+                            (Some(hz), None) => ui.colored_label(
+                                egui::Color32::YELLOW,
+                                format!("stream at {} Hz: no samples, polling", hz),
+                            ),
+                            (None, _) => ui.label("polled 10 Hz"),
+                        };
+                        ui.end_row();
+
+                        ui.label("Device Clock");
+                        ui.label(arduino_state.device_time_ms
+                            .map(|ms| format!("{:.3} s", ms as f64 / 1000.0))
+                            .unwrap_or_else(|| "—".to_string()));
                         ui.end_row();
                     });
             });
@@ -1059,17 +1782,27 @@ impl eframe::App for AppUI {
         self.ui_right_panel(ctx);
 
         egui::CentralPanel::default().show(ctx, |ui| {
+            self.ui_interlock_banner(ui);
+
             // Top row: Microwave and Pressure control sections side-by-side
             ui.horizontal(|ui| {
                 // Left: Microwave control
                 self.ui_center_microwave(ui);
                 ui.add_space(12.0);
-                // Right: Pressure control
+                // Right: Pressure control, with its tuning next to it
                 self.ui_center_pressure(ui);
+                ui.add_space(12.0);
+                self.ui_center_tuning(ui);
             });
 
             ui.add_space(12.0);
 
+            // Frequency sweep and tracking (collapsed by default)
+            self.ui_center_sweep(ui);
+            self.ui_center_tracking(ui);
+
+            ui.add_space(12.0);
+
             // Bottom: Duet control section
             self.ui_center_duet(ui);
 
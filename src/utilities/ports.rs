@@ -0,0 +1,112 @@
+/******************** Serial Port Discovery ********************/
+
+// enumerates serial ports for the Connections panel and picks the ones that
+// look like the MiniCircuits generator and the Arduino
+
+use serialport::SerialPortType;
+
+/// One serial port as shown in the Connections panel.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PortInfo {
+    pub name: String,
+    pub vid: Option<u16>,
+    pub pid: Option<u16>,
+    pub serial_number: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+}
+
+impl PortInfo {
+    /// Single-line description, e.g. "VID:PID 2341:0043  SN 7573  Arduino Uno".
+    pub fn details(&self) -> String {
+        let mut parts = Vec::new();
+        if let (Some(vid), Some(pid)) = (self.vid, self.pid) {
+            parts.push(format!("VID:PID {:04X}:{:04X}", vid, pid));
+        }
+        if let Some(ref serial) = self.serial_number {
+            parts.push(format!("SN {}", serial));
+        }
+        match (&self.manufacturer, &self.product) {
+            (Some(m), Some(p)) => parts.push(format!("{} {}", m, p)),
+            (Some(s), None) | (None, Some(s)) => parts.push(s.clone()),
+            (None, None) => {}
+        }
+        if parts.is_empty() {
+            "no USB info".to_string()
+        } else {
+            parts.join("  ")
+        }
+    }
+}
+
+/// List the serial ports currently present, sorted by name.
+pub fn scan_ports() -> Result<Vec<PortInfo>, String> {
+    let mut ports: Vec<PortInfo> = serialport::available_ports()
+        .map_err(|e| format!("port scan failed: {}", e))?
+        .into_iter()
+        .map(|p| match p.port_type {
+            SerialPortType::UsbPort(usb) => PortInfo {
+                name: p.port_name,
+                vid: Some(usb.vid),
+                pid: Some(usb.pid),
+                serial_number: usb.serial_number,
+                manufacturer: usb.manufacturer,
+                product: usb.product,
+            },
+            _ => PortInfo { name: p.port_name, ..PortInfo::default() },
+        })
+        .collect();
+    ports.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(ports)
+}
+
+/// First port whose USB IDs match the MiniCircuits generator.
+pub fn find_microwave(ports: &[PortInfo], vendor_id: u16, product_id: u16) -> Option<&PortInfo> {
+    ports
+        .iter()
+        .find(|p| p.vid == Some(vendor_id) && p.pid == Some(product_id))
+}
+
+/// First port with one of the known Arduino vendor IDs.
+pub fn find_arduino<'a>(ports: &'a [PortInfo], vendor_ids: &[u16]) -> Option<&'a PortInfo> {
+    ports
+        .iter()
+        .find(|p| p.vid.is_some_and(|vid| vendor_ids.contains(&vid)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usb(name: &str, vid: u16, pid: u16) -> PortInfo {
+        PortInfo {
+            name: name.to_string(),
+            vid: Some(vid),
+            pid: Some(pid),
+            ..PortInfo::default()
+        }
+    }
+
+    #[test]
+    fn test_find_devices_by_usb_id() {
+        let ports = vec![
+            PortInfo { name: "/dev/ttyS0".to_string(), ..PortInfo::default() },
+            usb("/dev/ttyUSB0", 0x0403, 0x6001),
+            usb("/dev/ttyACM0", 0x2341, 0x0043),
+            usb("/dev/ttyUSB1", 0x20CE, 0x0023),
+        ];
+        assert_eq!(find_microwave(&ports, 0x20CE, 0x0023).unwrap().name, "/dev/ttyUSB1");
+        assert!(find_microwave(&ports, 0x20CE, 0x0042).is_none());
+        assert_eq!(find_arduino(&ports, &[0x2A03, 0x2341]).unwrap().name, "/dev/ttyACM0");
+        assert!(find_arduino(&ports, &[0x1A86]).is_none());
+    }
+
+    #[test]
+    fn test_details() {
+        let mut port = usb("COM5", 0x2341, 0x43);
+        port.serial_number = Some("7573".to_string());
+        port.product = Some("Arduino Uno".to_string());
+        assert_eq!(port.details(), "VID:PID 2341:0043  SN 7573  Arduino Uno");
+        assert_eq!(PortInfo::default().details(), "no USB info");
+    }
+}
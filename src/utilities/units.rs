@@ -0,0 +1,56 @@
+/******************** Unit Conversions ********************/
+
+// RF power and frequency conversions for display and entry.
+// The controllers work in W and Hz; the UI shows dBm and MHz alongside.
+
+/// Power in dBm, None for zero or negative power.
+pub fn watts_to_dbm(watts: f32) -> Option<f32> {
+    (watts > 0.0).then(|| 10.0 * (watts * 1000.0).log10())
+}
+
+pub fn dbm_to_watts(dbm: f32) -> f32 {
+    10f32.powf(dbm / 10.0) / 1000.0
+}
+
+pub fn hz_to_mhz(hz: i32) -> f64 {
+    hz as f64 / 1e6
+}
+
+/// Frequency in Hz rounded to the nearest Hz; None when it does not fit the controller's i32.
+pub fn mhz_to_hz(mhz: f64) -> Option<i32> {
+    let hz = (mhz * 1e6).round();
+    (hz.is_finite() && hz >= 0.0 && hz <= i32::MAX as f64).then_some(hz as i32)
+}
+
+/// e.g. "12.0 W (40.8 dBm)"
+pub fn format_power(watts: f32) -> String {
+    match watts_to_dbm(watts) {
+        Some(dbm) => format!("{:.1} W ({:.1} dBm)", watts, dbm),
+        None => format!("{:.1} W", watts),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dbm_conversions() {
+        assert!((watts_to_dbm(1.0).unwrap() - 30.0).abs() < 1e-4);
+        assert!((watts_to_dbm(0.001).unwrap() - 0.0).abs() < 1e-4);
+        assert!((watts_to_dbm(100.0).unwrap() - 50.0).abs() < 1e-4);
+        assert_eq!(watts_to_dbm(0.0), None);
+        assert!((dbm_to_watts(47.0) - 50.12).abs() < 0.01);
+        assert!((dbm_to_watts(watts_to_dbm(25.0).unwrap()) - 25.0).abs() < 1e-3);
+        assert_eq!(format_power(1.0), "1.0 W (30.0 dBm)");
+    }
+
+    #[test]
+    fn test_mhz_conversions() {
+        assert_eq!(mhz_to_hz(915.0), Some(915_000_000));
+        assert_eq!(mhz_to_hz(902.1234565), Some(902_123_457));
+        assert_eq!(mhz_to_hz(2450.0), None);
+        assert_eq!(mhz_to_hz(-1.0), None);
+        assert_eq!(hz_to_mhz(915_500_000), 915.5);
+    }
+}